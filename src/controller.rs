@@ -0,0 +1,894 @@
+use crate::filter::NoteFilter;
+use crate::graph_layout::{ForceLayout, GraphLayout};
+use crate::highlight::NoteMatch;
+use crate::history::{self, DiffLine, NoteRevision};
+use crate::links;
+use crate::note::{AppNote, KnowledgeGraph, NoteEdit, NoteRelation, RelationError, RelationType, SerializableColor, WorkflowStatus};
+use crate::plan::TaskPlan;
+use crate::query::{Query, QueryError, SavedSearch};
+use crate::search::{SearchIndex, TypoTolerance};
+use crate::sort::SortOrder;
+use crate::storage::{NoteStore, StorageResult, WorkspaceSettings, DEFAULT_TRASH_RETENTION_DAYS};
+use crate::undo::{CommandKind, NoteCommand, UndoStack};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// A pending write against the note store, applied in order by the storage writer task
+#[derive(Debug)]
+pub enum StorageOp {
+    Save(Box<AppNote>),
+    Delete(String),
+    SaveGraph(Box<KnowledgeGraph>),
+    SaveSavedSearches(Vec<SavedSearch>),
+    SaveSettings(WorkspaceSettings),
+    /// Read a note's revisions once every operation queued before this one is applied
+    ListRevisions(String, oneshot::Sender<StorageResult<Vec<NoteRevision>>>),
+}
+
+/// Spawn the background task that applies storage operations one at a time.
+/// Running them sequentially keeps a save followed by a delete from racing each other.
+pub fn spawn_storage_writer(storage: Arc<dyn NoteStore>) -> (mpsc::UnboundedSender<StorageOp>, StorageWriter) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<StorageOp>();
+    let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+    let writer_storage = storage.clone();
+    let handle = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                op = rx.recv() => match op {
+                    Some(op) => apply_storage_op(writer_storage.as_ref(), op).await,
+                    None => break,
+                },
+                _ = &mut stop_rx => {
+                    // Refuse new operations but apply everything already queued
+                    rx.close();
+                    while let Some(op) = rx.recv().await {
+                        apply_storage_op(writer_storage.as_ref(), op).await;
+                    }
+                    break;
+                }
+            }
+        }
+    });
+    (tx, StorageWriter { storage, handle, stop: stop_tx })
+}
+
+async fn apply_storage_op(storage: &dyn NoteStore, op: StorageOp) {
+    let result = match op {
+        StorageOp::Save(note) => storage.upsert(&note).await,
+        StorageOp::Delete(note_id) => storage.delete(&note_id).await.map(|_| ()),
+        StorageOp::SaveGraph(graph) => storage.save_graph(&graph).await,
+        StorageOp::SaveSavedSearches(saved_searches) => storage.save_saved_searches(&saved_searches).await,
+        StorageOp::SaveSettings(settings) => storage.save_settings(&settings).await,
+        StorageOp::ListRevisions(note_id, reply) => {
+            // Whoever asked may have stopped waiting
+            let _ = reply.send(storage.list_revisions(&note_id).await);
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("⚠️ Failed to persist note change: {}", e);
+    }
+}
+
+/// The running storage writer task
+pub struct StorageWriter {
+    storage: Arc<dyn NoteStore>,
+    handle: tokio::task::JoinHandle<()>,
+    stop: oneshot::Sender<()>,
+}
+
+impl StorageWriter {
+    /// Apply the operations queued so far, stop the writer and flush the store, so
+    /// changes still waiting out a debounce are written too. Unlike waiting for every
+    /// sender to be dropped, this finishes while callbacks still hold senders.
+    pub async fn shutdown(self) -> StorageResult<()> {
+        // The writer has already stopped if nobody is left to receive this
+        let _ = self.stop.send(());
+        self.handle.await?;
+        self.storage.flush().await
+    }
+}
+
+/// Application state and behavior, independent of any UI toolkit
+///
+/// Owns the notes, the trash, the knowledge graph, the undo history, the saved searches
+/// and the current filter, search and sort order. Every note mutation is an undoable command and is written through
+/// to storage by sending [`StorageOp`]s to the storage writer. The Slint window is only
+/// an adapter that forwards callbacks here and renders what the controller exposes.
+pub struct AppController {
+    // Live notes in display order
+    notes: Vec<AppNote>,
+    // Notes in the trash, most recently deleted first
+    trash: Vec<AppNote>,
+    graph: KnowledgeGraph,
+    // Full-text index over the live notes
+    search_index: SearchIndex,
+    undo_stack: UndoStack,
+    filter: NoteFilter,
+    search_text: String,
+    // Parsed search text; an invalid query matches everything and keeps its error
+    search_query: Query,
+    search_error: Option<QueryError>,
+    saved_searches: Vec<SavedSearch>,
+    sort_order: SortOrder,
+    trash_retention_days: u32,
+    // Bumped by every change to the notes, relations or saved searches other than a move
+    revision: u64,
+    storage_tx: mpsc::UnboundedSender<StorageOp>,
+}
+
+impl AppController {
+    /// Create a controller over loaded notes (live and trashed) and their graph
+    pub fn new(notes: Vec<AppNote>, graph: KnowledgeGraph, storage_tx: mpsc::UnboundedSender<StorageOp>) -> Self {
+        let (mut trash, mut notes): (Vec<AppNote>, Vec<AppNote>) = notes.into_iter().partition(AppNote::is_in_trash);
+        // Storage hands notes back in hash order, so restore a stable creation order
+        notes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        trash.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| a.id.cmp(&b.id)));
+        let search_index = SearchIndex::from_notes(&notes);
+        
+        let mut controller = Self {
+            notes,
+            trash,
+            graph,
+            search_index,
+            undo_stack: UndoStack::new(),
+            filter: NoteFilter::new(),
+            search_text: String::new(),
+            search_query: Query::default(),
+            search_error: None,
+            saved_searches: Vec::new(),
+            sort_order: SortOrder::default(),
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            revision: 0,
+            storage_tx,
+        };
+        // Pick up links written before they were tracked
+        if controller.graph.sync_links(&controller.notes) {
+            controller.persist_graph();
+        }
+        controller
+    }
+    
+    /// Set how many days notes stay in the trash (only used to report the days left)
+    pub fn with_trash_retention_days(mut self, trash_retention_days: u32) -> Self {
+        self.trash_retention_days = trash_retention_days;
+        self
+    }
+    
+    /// Set how many typos a search word may contain and still match
+    pub fn with_typo_tolerance(mut self, typo_tolerance: TypoTolerance) -> Self {
+        self.search_index = self.search_index.with_typo_tolerance(typo_tolerance);
+        self
+    }
+    
+    /// Start with the saved searches loaded from storage
+    pub fn with_saved_searches(mut self, saved_searches: Vec<SavedSearch>) -> Self {
+        self.saved_searches = saved_searches;
+        self
+    }
+    
+    /// Start with the view settings remembered by the store
+    pub fn with_settings(mut self, settings: WorkspaceSettings) -> Self {
+        self.sort_order = settings.sort_order;
+        self
+    }
+    
+    /// Live notes in display order
+    pub fn notes(&self) -> &[AppNote] {
+        &self.notes
+    }
+    
+    /// Notes in the trash, most recently deleted first
+    pub fn trash(&self) -> &[AppNote] {
+        &self.trash
+    }
+    
+    pub fn graph(&self) -> &KnowledgeGraph {
+        &self.graph
+    }
+    
+    /// Look up a live or trashed note
+    pub fn note(&self, note_id: &str) -> Option<&AppNote> {
+        self.live_note(note_id)
+            .or_else(|| self.trash.iter().find(|note| note.id == note_id))
+    }
+    
+    pub fn filter(&self) -> &NoteFilter {
+        &self.filter
+    }
+    
+    pub fn search_text(&self) -> &str {
+        &self.search_text
+    }
+    
+    pub fn trash_retention_days(&self) -> u32 {
+        self.trash_retention_days
+    }
+    
+    /// Changes whenever the notes, relations or saved searches change, except when a note is
+    /// only moved, so views derived from them (blocked-by titles, the plan, saved search
+    /// counts) only need recomputing when it does
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    
+    /// Days until a trashed note is purged
+    pub fn days_left_in_trash(&self, note: &AppNote) -> u32 {
+        let days_in_trash = note.days_in_trash().unwrap_or(0).max(0) as u32;
+        self.trash_retention_days.saturating_sub(days_in_trash)
+    }
+    
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack.can_undo()
+    }
+    
+    pub fn can_redo(&self) -> bool {
+        self.undo_stack.can_redo()
+    }
+    
+    /// Live notes matching the current filter and search query, in the current sort order.
+    /// Ties keep their relevance order when the query has words to rank by, and their
+    /// display order otherwise.
+    pub fn visible_notes(&self) -> Vec<&AppNote> {
+        let mut visible = self.query_results(&self.search_query, Some(&self.filter));
+        self.sort_order.sort(&mut visible);
+        visible
+    }
+    
+    pub fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+    
+    /// Change how the visible notes are ordered. The order is remembered by the store.
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        if self.sort_order == sort_order {
+            return;
+        }
+        self.sort_order = sort_order;
+        let _ = self.storage_tx.send(StorageOp::SaveSettings(self.settings()));
+    }
+    
+    /// The view settings to remember for this workspace
+    pub fn settings(&self) -> WorkspaceSettings {
+        WorkspaceSettings {
+            sort_order: self.sort_order,
+        }
+    }
+    
+    /// Whether the search has words to rank and highlight (field filters alone don't)
+    pub fn is_searching_text(&self) -> bool {
+        !self.search_query.ranking_terms().is_empty()
+    }
+    
+    /// Where the current search matched a note, for highlighting. `None` when the search
+    /// has no words to highlight.
+    pub fn note_match(&self, note: &AppNote) -> Option<NoteMatch> {
+        let terms = self.search_query.ranking_terms();
+        if terms.is_empty() {
+            return None;
+        }
+        Some(NoteMatch::new(note, &terms, self.search_index.typo_tolerance()))
+    }
+    
+    /// Why the current search text couldn't be used, if it is not a valid query
+    pub fn search_error(&self) -> Option<&QueryError> {
+        self.search_error.as_ref()
+    }
+    
+    pub fn search_index(&self) -> &SearchIndex {
+        &self.search_index
+    }
+    
+    pub fn saved_searches(&self) -> &[SavedSearch] {
+        &self.saved_searches
+    }
+    
+    /// The saved search whose query is in the search box, if any
+    pub fn active_saved_search(&self) -> Option<&SavedSearch> {
+        let text = self.search_text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        self.saved_searches.iter().find(|saved| saved.query.trim() == text)
+    }
+    
+    /// Live notes a saved search finds right now, regardless of the filter.
+    /// Empty if there is no such saved search or its query no longer parses.
+    pub fn saved_search_results(&self, saved_search_id: &str) -> Vec<&AppNote> {
+        let query = self
+            .saved_searches
+            .iter()
+            .find(|saved| saved.id == saved_search_id)
+            .and_then(|saved| saved.parse().ok());
+        match query {
+            Some(query) => self.query_results(&query, None),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Relations between live notes; relations of trashed notes stay in the graph for restore
+    pub fn visible_relations(&self) -> Vec<&NoteRelation> {
+        self.graph
+            .relations
+            .iter()
+            .filter(|relation| self.live_note(&relation.from_note_id).is_some() && self.live_note(&relation.to_note_id).is_some())
+            .collect()
+    }
+    
+    /// Live notes whose content links to the given note, in display order
+    pub fn backlinks(&self, note_id: &str) -> Vec<&AppNote> {
+        let sources = self.graph.backlinks(note_id);
+        self.notes.iter().filter(|note| sources.contains(&note.id.as_str())).collect()
+    }
+    
+    /// Live notes the given note is waiting for: the prerequisites that aren't done yet
+    pub fn blocked_by(&self, note_id: &str) -> Vec<&AppNote> {
+        self.graph
+            .prerequisites(note_id)
+            .into_iter()
+            .filter_map(|prerequisite| self.live_note(prerequisite))
+            .filter(|prerequisite| prerequisite.workflow_status != WorkflowStatus::Done)
+            .collect()
+    }
+    
+    /// The order to work through the open live notes in, with their critical path
+    pub fn plan(&self) -> TaskPlan {
+        self.graph.plan(&self.notes)
+    }
+    
+    /// Where to draw the live notes and their relations in the knowledge graph view
+    pub fn graph_layout(&self, settings: &ForceLayout) -> GraphLayout {
+        self.graph.layout(&self.notes, settings)
+    }
+    
+    /// The live note a `[[title]]` link in the given note points to
+    pub fn linked_note(&self, from_id: &str, title: &str) -> Option<&AppNote> {
+        links::resolve(&self.notes, from_id, title)
+    }
+    
+    /// Every tag on a live note, sorted, for picking filter tags
+    pub fn all_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.notes.iter().flat_map(|note| note.tags.iter().map(String::as_str)).collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+    
+    /// Every color of a live note, in display order, for picking filter colors
+    pub fn all_colors(&self) -> Vec<SerializableColor> {
+        let mut colors: Vec<SerializableColor> = Vec::new();
+        for note in &self.notes {
+            if !colors.contains(&note.color) {
+                colors.push(note.color);
+            }
+        }
+        colors
+    }
+    
+    /// Replace the filter the visible notes are narrowed down with
+    pub fn set_filter(&mut self, filter: NoteFilter) {
+        self.filter = filter;
+    }
+    
+    /// Change the filter in place, e.g. to toggle one status
+    pub fn filter_mut(&mut self) -> &mut NoteFilter {
+        &mut self.filter
+    }
+    
+    /// Show only notes with the given status (`None` shows all), keeping the rest of the filter
+    pub fn set_filter_status(&mut self, status: Option<WorkflowStatus>) {
+        self.filter.statuses = status.into_iter().collect();
+    }
+    
+    /// Search with a query (see [`Query`]). While the text has a syntax error the search
+    /// is ignored and the error is reported by [`AppController::search_error`].
+    pub fn set_search_text(&mut self, text: &str) {
+        self.search_text = text.to_string();
+        match Query::parse(text) {
+            Ok(query) => {
+                self.search_query = query;
+                self.search_error = None;
+            }
+            Err(error) => {
+                self.search_query = Query::default();
+                self.search_error = Some(error);
+            }
+        }
+    }
+    
+    /// Save the current search under a name, replacing the query of a saved search with
+    /// the same name. Returns the saved search's ID, or `None` if the name is blank or the
+    /// search is empty or invalid.
+    pub fn save_search(&mut self, name: &str) -> Option<String> {
+        let name = name.trim();
+        let query = self.search_text.trim();
+        if name.is_empty() || self.search_query.is_empty() || self.search_error.is_some() {
+            return None;
+        }
+        
+        let id = match self.saved_searches.iter_mut().find(|saved| saved.name.eq_ignore_ascii_case(name)) {
+            Some(saved) => {
+                saved.query = query.to_string();
+                saved.id.clone()
+            }
+            None => {
+                let saved = SavedSearch::new(name, query);
+                let id = saved.id.clone();
+                self.saved_searches.push(saved);
+                id
+            }
+        };
+        self.persist_saved_searches();
+        Some(id)
+    }
+    
+    /// Put a saved search's query in the search box. Returns false if there is no such saved search.
+    pub fn apply_saved_search(&mut self, saved_search_id: &str) -> bool {
+        let Some(query) = self
+            .saved_searches
+            .iter()
+            .find(|saved| saved.id == saved_search_id)
+            .map(|saved| saved.query.clone())
+        else {
+            return false;
+        };
+        self.set_search_text(&query);
+        true
+    }
+    
+    /// Forget a saved search (the search box is left as it is)
+    pub fn delete_saved_search(&mut self, saved_search_id: &str) -> bool {
+        let count = self.saved_searches.len();
+        self.saved_searches.retain(|saved| saved.id != saved_search_id);
+        if self.saved_searches.len() == count {
+            return false;
+        }
+        self.persist_saved_searches();
+        true
+    }
+    
+    /// Create a note from a line of quick-add text, returning its ID
+    pub fn quick_add(&mut self, text: &str) -> String {
+        let title = if text.chars().count() > 30 {
+            format!("{}...", text.chars().take(27).collect::<String>())
+        } else {
+            text.to_string()
+        };
+        let mut note = AppNote::new(title, text.to_string());
+        
+        // Cascade new notes so they don't overlap
+        let offset = self.notes.len() as f32 * 20.0;
+        note.set_position(50.0 + offset, 50.0 + offset);
+        
+        note.extract_keywords();
+        note.analyze_sentiment();
+        
+        let note_id = note.id.clone();
+        self.execute(NoteCommand::create(note));
+        note_id
+    }
+    
+    /// Add a new note
+    pub fn create_note(&mut self, note: AppNote) {
+        self.execute(NoteCommand::create(note));
+    }
+    
+    /// Replace a live note with an edited version of it. Returns false if there is no such note.
+    pub fn update_note(&mut self, note: AppNote) -> bool {
+        let Some(before) = self.live_note(&note.id).cloned() else {
+            return false;
+        };
+        self.execute(NoteCommand::update(CommandKind::Edit, before, note));
+        true
+    }
+    
+    /// The revisions recorded for a note, oldest first. The storage writer reads them after
+    /// applying every change sent before, so the latest edit is among them. The future
+    /// doesn't borrow the controller, so it can be awaited while the controller is used.
+    pub fn revisions(&self, note_id: &str) -> impl Future<Output = StorageResult<Vec<NoteRevision>>> + 'static {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let sent = self.storage_tx.send(StorageOp::ListRevisions(note_id.to_string(), reply_tx));
+        async move {
+            sent.map_err(|_| "the storage writer has stopped")?;
+            reply_rx.await?
+        }
+    }
+    
+    /// Line changes from one of a live note's revisions to the note as it is now, which is
+    /// what restoring the revision would undo; the title is compared as the first line
+    pub fn diff_revisions(&self, note_id: &str, revision: &NoteRevision) -> Option<Vec<DiffLine>> {
+        let note = self.live_note(note_id)?;
+        let current = NoteRevision { title: note.title.clone(), content: note.content.clone(), ..revision.clone() };
+        Some(history::diff_revisions(revision, &current))
+    }
+    
+    /// Roll a live note's title and content back to one of its revisions, as an edit that
+    /// is saved and undone like any other. Returns false if the note isn't live, the
+    /// revision belongs to another note or its title can't be given back (see `check_title`).
+    pub fn restore_revision(&mut self, note_id: &str, revision: &NoteRevision) -> bool {
+        if revision.note_id != note_id || self.check_title(note_id, &revision.title).is_err() {
+            return false;
+        }
+        self.modify(note_id, CommandKind::Edit, |note| {
+            note.update_content(revision.title.clone(), revision.content.clone());
+        })
+    }
+    
+    /// Whether a note (`""` for a new one) can be given a title. A note keeps the title it
+    /// has, but a new one must be one links can name (see `NoteEdit::check_title`), and
+    /// can't be another note's title when that would move links between the two: links
+    /// resolve to the older note, so the newer one would lose its backlinks.
+    pub fn check_title(&self, note_id: &str, title: &str) -> Result<(), String> {
+        let Some(note) = self.live_note(note_id) else {
+            return NoteEdit::check_title(title);
+        };
+        if note.title == title {
+            return Ok(());
+        }
+        NoteEdit::check_title(title)?;
+        if links::same_title(&note.title, title) {
+            return Ok(());
+        }
+        let has_backlinks = |note_id: &str| !self.graph.backlinks(note_id).is_empty();
+        let moves_links = self
+            .notes
+            .iter()
+            .filter(|other| other.id != note_id && links::same_title(&other.title, title))
+            .any(|other| {
+                let newer = if other.created_at <= note.created_at { note_id } else { other.id.as_str() };
+                has_backlinks(newer)
+            });
+        if moves_links {
+            return Err(format!("Another note is already called \"{}\" and links would move between the two", title.trim()));
+        }
+        Ok(())
+    }
+    
+    /// Apply the note editor's values to a live note. Only changed fields are written, and
+    /// saving without changes records nothing. A title `check_title` refuses isn't applied.
+    /// Returns whether the note changed.
+    pub fn edit_note(&mut self, note_id: &str, edit: &NoteEdit) -> bool {
+        let Some(before) = self.live_note(note_id).cloned() else {
+            return false;
+        };
+        if self.check_title(note_id, &edit.title).is_err() {
+            return false;
+        }
+        let mut after = before.clone();
+        if !after.apply_edit(edit) {
+            return false;
+        }
+        self.execute(NoteCommand::update(CommandKind::Edit, before, after));
+        true
+    }
+    
+    /// Move a note; each move, such as one drag, is its own undo step
+    pub fn move_note(&mut self, note_id: &str, x: f32, y: f32) -> bool {
+        self.modify(note_id, CommandKind::Move, |note| note.set_position(x, y))
+    }
+    
+    pub fn set_workflow_status(&mut self, note_id: &str, status: WorkflowStatus) -> bool {
+        self.modify(note_id, CommandKind::ChangeStatus, |note| note.set_workflow_status(status))
+    }
+    
+    /// Check or uncheck a checklist item of a live note; the note's completion follows
+    pub fn toggle_checklist_item(&mut self, note_id: &str, item_id: &str) -> bool {
+        let has_item = self
+            .live_note(note_id)
+            .is_some_and(|note| note.checklist.iter().any(|item| item.id == item_id));
+        has_item && self.modify(note_id, CommandKind::Checklist, |note| {
+            note.toggle_checklist_item(item_id);
+        })
+    }
+    
+    /// Move a note to the trash
+    pub fn trash_note(&mut self, note_id: &str) -> bool {
+        self.modify(note_id, CommandKind::Trash, AppNote::move_to_trash)
+    }
+    
+    /// Take a note back out of the trash
+    pub fn restore_note(&mut self, note_id: &str) -> bool {
+        let Some(before) = self.trash.iter().find(|note| note.id == note_id).cloned() else {
+            return false;
+        };
+        let mut after = before.clone();
+        after.restore_from_trash();
+        self.execute(NoteCommand::update(CommandKind::Restore, before, after));
+        true
+    }
+    
+    /// Permanently delete a note from the trash. This can't be undone.
+    pub fn delete_forever(&mut self, note_id: &str) -> bool {
+        if !self.trash.iter().any(|note| note.id == note_id) {
+            return false;
+        }
+        self.apply_note_state(note_id, None, None);
+        self.undo_stack.forget_note(note_id);
+        true
+    }
+    
+    /// Permanently delete everything in the trash, returning how many notes were deleted
+    pub fn empty_trash(&mut self) -> usize {
+        let note_ids: Vec<String> = self.trash.iter().map(|note| note.id.clone()).collect();
+        for note_id in &note_ids {
+            self.delete_forever(note_id);
+        }
+        note_ids.len()
+    }
+    
+    /// Revert the most recent command, returning what kind of change was undone. The
+    /// notes it changed along the way are put back as they were, too.
+    pub fn undo(&mut self) -> Option<CommandKind> {
+        let command = self.undo_stack.undo()?;
+        let linked = command.linked.iter().map(|(before, _)| before.clone()).collect();
+        match (&command.before, &command.after) {
+            // An undone create goes to the trash rather than away for good, so redoing it
+            // brings the note back with its relations and revisions
+            (None, Some(created)) => {
+                let mut trashed = created.clone();
+                trashed.move_to_trash();
+                self.apply_note_state(&command.note_id, Some(&trashed), Some(linked));
+            }
+            (before, _) => {
+                self.apply_note_state(&command.note_id, before.as_ref(), Some(linked));
+            }
+        }
+        Some(command.kind)
+    }
+    
+    /// Reapply the most recently undone command, returning what kind of change was redone
+    pub fn redo(&mut self) -> Option<CommandKind> {
+        let command = self.undo_stack.redo()?;
+        let linked = command.linked.iter().map(|(_, after)| after.clone()).collect();
+        self.apply_note_state(&command.note_id, command.after.as_ref(), Some(linked));
+        Some(command.kind)
+    }
+    
+    /// Discover relations between live notes on top of the existing graph.
+    /// Returns how many relations were added.
+    pub fn auto_discover_relations(&mut self) -> usize {
+        let before = self.graph.relations.len();
+        self.graph.auto_discover_relations(&self.notes);
+        let discovered = self.graph.relations.len() - before;
+        if discovered > 0 {
+            self.persist_graph();
+        }
+        discovered
+    }
+    
+    /// Relate two live notes by hand, returning the new relation's ID
+    pub fn create_relation(
+        &mut self,
+        from_id: &str,
+        to_id: &str,
+        relation_type: RelationType,
+        strength: f32,
+        description: Option<String>,
+    ) -> Result<String, RelationError> {
+        for note_id in [from_id, to_id] {
+            if self.live_note(note_id).is_none() {
+                return Err(RelationError::UnknownNote(note_id.to_string()));
+            }
+        }
+        let relation_id = self.graph.create_relation(from_id, to_id, relation_type, strength, description)?;
+        self.persist_graph();
+        Ok(relation_id)
+    }
+    
+    /// Change the type, strength and description of a relation
+    pub fn update_relation(
+        &mut self,
+        relation_id: &str,
+        relation_type: RelationType,
+        strength: f32,
+        description: Option<String>,
+    ) -> Result<(), RelationError> {
+        self.graph.update_relation(relation_id, relation_type, strength, description)?;
+        self.persist_graph();
+        Ok(())
+    }
+    
+    /// Delete a relation
+    pub fn delete_relation(&mut self, relation_id: &str) -> Result<(), RelationError> {
+        self.graph.delete_relation(relation_id)?;
+        self.persist_graph();
+        Ok(())
+    }
+    
+    // Private helper methods
+    
+    fn live_note(&self, note_id: &str) -> Option<&AppNote> {
+        self.notes.iter().find(|note| note.id == note_id)
+    }
+    
+    /// Live notes matching a query and filter, ranked by the query's words if it has any
+    fn query_results(&self, query: &Query, filter: Option<&NoteFilter>) -> Vec<&AppNote> {
+        let matches_filter = |note: &&AppNote| match filter {
+            Some(filter) => filter.matches(note),
+            None => true,
+        };
+        
+        if query.is_empty() {
+            return self.notes.iter().filter(matches_filter).collect();
+        }
+        
+        // Look every word up in the index once instead of scanning each note's text
+        let term_matches: HashMap<&str, HashSet<&str>> = query
+            .terms()
+            .into_iter()
+            .map(|term| (term, self.search_index.notes_matching(term)))
+            .collect();
+        let mut results: Vec<&AppNote> = self
+            .notes
+            .iter()
+            .filter(matches_filter)
+            .filter(|note| {
+                query.matches_with(note, |term| term_matches.get(term).is_some_and(|ids| ids.contains(note.id.as_str())))
+            })
+            .collect();
+        
+        let ranking_terms = query.ranking_terms();
+        if !ranking_terms.is_empty() {
+            let scores = self.search_index.scores(&ranking_terms);
+            let score = |note: &AppNote| scores.get(note.id.as_str()).copied().unwrap_or(0.0);
+            // Stable, so equally relevant notes keep their display order
+            results.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+        results
+    }
+    
+    fn persist_graph(&mut self) {
+        self.revision += 1;
+        let _ = self.storage_tx.send(StorageOp::SaveGraph(Box::new(self.graph.clone())));
+    }
+    
+    fn persist_saved_searches(&mut self) {
+        self.revision += 1;
+        let _ = self.storage_tx.send(StorageOp::SaveSavedSearches(self.saved_searches.clone()));
+    }
+    
+    /// Apply a change to a copy of a live note as an undoable command
+    fn modify(&mut self, note_id: &str, kind: CommandKind, change: impl FnOnce(&mut AppNote)) -> bool {
+        let Some(before) = self.live_note(note_id).cloned() else {
+            return false;
+        };
+        let mut after = before.clone();
+        change(&mut after);
+        self.execute(NoteCommand::update(kind, before, after));
+        true
+    }
+    
+    /// Apply a command and record it so it can be undone, together with the other notes
+    /// it changed along the way
+    fn execute(&mut self, mut command: NoteCommand) {
+        command.linked = self.apply_note_state(&command.note_id, command.after.as_ref(), None);
+        self.undo_stack.record(command);
+    }
+    
+    /// Put a note into the given state in memory and in storage. `None` deletes it for good.
+    ///
+    /// Renaming the note rewrites the links to it in other notes, unless `linked_notes` has the
+    /// states to put those notes in instead, as undo and redo do. Returns the other notes
+    /// changed along the way, before and after.
+    fn apply_note_state(
+        &mut self,
+        note_id: &str,
+        state: Option<&AppNote>,
+        linked_notes: Option<Vec<AppNote>>,
+    ) -> Vec<(AppNote, AppNote)> {
+        // The title and content links see, while the note is live
+        let linked_before = self.live_note(note_id).map(|note| (note.title.clone(), note.content.clone()));
+        let moved_only = self.live_note(note_id).zip(state).is_some_and(|(before, after)| is_move(before, after));
+        if !moved_only {
+            self.revision += 1;
+        }
+        let position = self.notes.iter().position(|note| note.id == note_id);
+        self.trash.retain(|note| note.id != note_id);
+        
+        match state {
+            Some(note) if !note.is_in_trash() => {
+                match position {
+                    Some(index) => self.notes[index] = note.clone(),
+                    None => self.notes.push(note.clone()),
+                }
+                self.search_index.upsert(note);
+            }
+            _ => {
+                if let Some(index) = position {
+                    self.notes.remove(index);
+                }
+                self.search_index.remove(note_id);
+            }
+        }
+        
+        match state {
+            Some(note) => {
+                if note.is_in_trash() {
+                    self.trash.insert(0, note.clone());
+                    self.trash.sort_by_key(|note| std::cmp::Reverse(note.deleted_at));
+                }
+                let _ = self.storage_tx.send(StorageOp::Save(Box::new(note.clone())));
+            }
+            None => {
+                // Storage drops the note's relations and history together with the note
+                self.graph.remove_note_relations(note_id);
+                let _ = self.storage_tx.send(StorageOp::Delete(note_id.to_string()));
+            }
+        }
+        
+        let mut renamed = Vec::new();
+        match linked_notes {
+            Some(linked_notes) => {
+                for note in &linked_notes {
+                    self.replace_linked_note(note);
+                }
+            }
+            None => {
+                if let (Some((old_title, _)), Some(note)) = (&linked_before, state.filter(|note| !note.is_in_trash())) {
+                    if !links::same_title(old_title, &note.title) {
+                        renamed = self.rename_links_to(note_id, old_title, &note.title);
+                    }
+                }
+            }
+        }
+        
+        let linked_after = state.filter(|note| !note.is_in_trash()).map(|note| (note.title.clone(), note.content.clone()));
+        if linked_before != linked_after && self.graph.sync_links(&self.notes) {
+            self.persist_graph();
+        }
+        renamed
+    }
+    
+    /// Point other notes' links to a renamed note at its new title, so they keep
+    /// resolving to it, and return those notes before and after. A title links can't name
+    /// isn't written into them, and neither is one that would resolve to another, older note.
+    fn rename_links_to(&mut self, note_id: &str, old_title: &str, new_title: &str) -> Vec<(AppNote, AppNote)> {
+        if !links::is_linkable_title(new_title) {
+            return Vec::new();
+        }
+        let sources: Vec<String> = self
+            .graph
+            .backlinks(note_id)
+            .into_iter()
+            .filter(|source| links::resolve(&self.notes, source, new_title).is_some_and(|note| note.id == note_id))
+            .map(str::to_string)
+            .collect();
+        let renamed: Vec<(AppNote, AppNote)> = self
+            .notes
+            .iter()
+            .filter(|note| sources.contains(&note.id))
+            .filter_map(|source| {
+                let content = links::rename_links(&source.content, old_title, new_title)?;
+                let mut renamed = source.clone();
+                renamed.update_content(source.title.clone(), content);
+                Some((source.clone(), renamed))
+            })
+            .collect();
+        for (_, note) in &renamed {
+            self.replace_linked_note(note);
+        }
+        renamed
+    }
+    
+    /// Put a live note that another note's command changed along the way into the given
+    /// state, in memory and in storage
+    fn replace_linked_note(&mut self, note: &AppNote) {
+        let Some(index) = self.notes.iter().position(|live| live.id == note.id) else {
+            return;
+        };
+        self.notes[index] = note.clone();
+        self.search_index.upsert(note);
+        let _ = self.storage_tx.send(StorageOp::Save(Box::new(note.clone())));
+    }
+}
+
+/// Whether `after` is `before` with only its position changed
+fn is_move(before: &AppNote, after: &AppNote) -> bool {
+    let mut moved = before.clone();
+    moved.x = after.x;
+    moved.y = after.y;
+    moved.updated_at = after.updated_at;
+    moved == *after
+}
@@ -0,0 +1,239 @@
+use crate::note::{AppNote, Priority, SerializableColor, WorkflowStatus};
+use crate::query::parse_date;
+use crate::timestamp::local_date;
+use chrono::{Local, NaiveDate};
+
+/// A note date a [`DateRange`] can be applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    Created,
+    Updated,
+    Due,
+}
+
+impl DateField {
+    pub const ALL: [DateField; 3] = [DateField::Created, DateField::Updated, DateField::Due];
+    
+    /// Label shown in the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateField::Created => "Created",
+            DateField::Updated => "Updated",
+            DateField::Due => "Due",
+        }
+    }
+    
+    /// Parse a UI label (case-insensitive)
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.label().eq_ignore_ascii_case(label.trim()))
+    }
+    
+    /// The note's day for this field, if it has one. Timestamps count on the local
+    /// day they fall on, like the dates typed into the filter.
+    pub fn date_of(&self, note: &AppNote) -> Option<NaiveDate> {
+        match self {
+            DateField::Created => Some(local_date(&note.created_at)),
+            DateField::Updated => Some(local_date(&note.updated_at)),
+            DateField::Due => note.due_date,
+        }
+    }
+}
+
+/// An inclusive range of days. A missing end leaves that side open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        Self { from, to }
+    }
+    
+    /// Parse the ends of a range as typed in the filter panel. Each end is empty (open),
+    /// a `YYYY-MM-DD` date, `today`, or days (`-7d`) or weeks (`+2w`) from today.
+    pub fn parse(from: &str, to: &str) -> Result<Self, String> {
+        let today = Local::now().date_naive();
+        let parse_end = |value: &str| -> Result<Option<NaiveDate>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            parse_date(value, today)
+                .map(Some)
+                .ok_or_else(|| format!("`{}` is not a date (use YYYY-MM-DD, today or -7d)", value))
+        };
+        let range = Self::new(parse_end(from)?, parse_end(to)?);
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from > to {
+                return Err(format!("the range starts ({}) after it ends ({})", from, to));
+            }
+        }
+        Ok(range)
+    }
+    
+    /// Whether neither end is set, so the range lets everything through
+    pub fn is_open(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+    
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.unwrap_or(NaiveDate::MIN) <= date && date <= self.to.unwrap_or(NaiveDate::MAX)
+    }
+    
+    /// Short description, e.g. "2026-10-01 – 2026-10-31" or "from 2026-10-01"
+    pub fn label(&self) -> String {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from == to => from.to_string(),
+            (Some(from), Some(to)) => format!("{} – {}", from, to),
+            (Some(from), None) => format!("from {}", from),
+            (None, Some(to)) => format!("until {}", to),
+            (None, None) => "any time".to_string(),
+        }
+    }
+}
+
+/// A single condition of a [`NoteFilter`], shown as a removable chip
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCondition {
+    Status(WorkflowStatus),
+    Priority(Priority),
+    Tag(String),
+    Color(SerializableColor),
+    Date(DateField, DateRange),
+}
+
+impl FilterCondition {
+    /// Chip text, e.g. "Status: Todo" or "#rust"
+    pub fn label(&self) -> String {
+        match self {
+            FilterCondition::Status(status) => format!("Status: {}", status.label()),
+            FilterCondition::Priority(priority) => format!("Priority: {}", priority.label()),
+            FilterCondition::Tag(tag) => format!("#{}", tag),
+            FilterCondition::Color(color) => format!("Color: {}", color.to_hex()),
+            FilterCondition::Date(field, range) => format!("{}: {}", field.label(), range.label()),
+        }
+    }
+}
+
+/// Which notes to show, independent of the text search
+///
+/// Any selected value of a dimension matches (status Todo *or* Review), and a note has
+/// to match every dimension that has a selection (a Todo note *and* tagged `rust`). An
+/// empty dimension or an open date range doesn't filter at all.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteFilter {
+    pub statuses: Vec<WorkflowStatus>,
+    pub priorities: Vec<Priority>,
+    /// Lowercase, like note tags
+    pub tags: Vec<String>,
+    pub colors: Vec<SerializableColor>,
+    pub created: DateRange,
+    pub updated: DateRange,
+    pub due: DateRange,
+}
+
+impl NoteFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Whether the filter lets every note through
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    
+    pub fn matches(&self, note: &AppNote) -> bool {
+        let any_of = |selected: bool, matches: bool| !selected || matches;
+        any_of(!self.statuses.is_empty(), self.statuses.contains(&note.workflow_status))
+            && any_of(!self.priorities.is_empty(), self.priorities.contains(&note.priority))
+            && any_of(!self.tags.is_empty(), self.tags.iter().any(|tag| note.tags.contains(tag)))
+            && any_of(!self.colors.is_empty(), self.colors.contains(&note.color))
+            && DateField::ALL.into_iter().all(|field| {
+                let range = self.date_range(field);
+                // Notes without a due date never fall into a due date range
+                range.is_open() || field.date_of(note).is_some_and(|date| range.contains(date))
+            })
+    }
+    
+    /// Select a status, or deselect it if it was selected. Returns whether it is now selected.
+    pub fn toggle_status(&mut self, status: WorkflowStatus) -> bool {
+        toggle(&mut self.statuses, status)
+    }
+    
+    pub fn toggle_priority(&mut self, priority: Priority) -> bool {
+        toggle(&mut self.priorities, priority)
+    }
+    
+    pub fn toggle_tag(&mut self, tag: &str) -> bool {
+        toggle(&mut self.tags, tag.trim().to_lowercase())
+    }
+    
+    pub fn toggle_color(&mut self, color: SerializableColor) -> bool {
+        toggle(&mut self.colors, color)
+    }
+    
+    pub fn date_range(&self, field: DateField) -> DateRange {
+        match field {
+            DateField::Created => self.created,
+            DateField::Updated => self.updated,
+            DateField::Due => self.due,
+        }
+    }
+    
+    pub fn set_date_range(&mut self, field: DateField, range: DateRange) {
+        match field {
+            DateField::Created => self.created = range,
+            DateField::Updated => self.updated = range,
+            DateField::Due => self.due = range,
+        }
+    }
+    
+    /// Every active condition, in the order the chips are shown
+    pub fn conditions(&self) -> Vec<FilterCondition> {
+        let mut conditions: Vec<FilterCondition> = Vec::new();
+        conditions.extend(self.statuses.iter().cloned().map(FilterCondition::Status));
+        conditions.extend(self.priorities.iter().cloned().map(FilterCondition::Priority));
+        conditions.extend(self.tags.iter().cloned().map(FilterCondition::Tag));
+        conditions.extend(self.colors.iter().copied().map(FilterCondition::Color));
+        for field in DateField::ALL {
+            let range = self.date_range(field);
+            if !range.is_open() {
+                conditions.push(FilterCondition::Date(field, range));
+            }
+        }
+        conditions
+    }
+    
+    /// Drop one condition (a removed chip). Returns false if it wasn't active.
+    pub fn remove(&mut self, condition: &FilterCondition) -> bool {
+        let count = self.conditions().len();
+        match condition {
+            FilterCondition::Status(status) => self.statuses.retain(|s| s != status),
+            FilterCondition::Priority(priority) => self.priorities.retain(|p| p != priority),
+            FilterCondition::Tag(tag) => self.tags.retain(|t| t != tag),
+            FilterCondition::Color(color) => self.colors.retain(|c| c != color),
+            FilterCondition::Date(field, range) => {
+                if self.date_range(*field) == *range {
+                    self.set_date_range(*field, DateRange::default());
+                }
+            }
+        }
+        self.conditions().len() != count
+    }
+}
+
+/// Add the value if it's missing, otherwise remove it. Returns whether it is now present.
+fn toggle<T: PartialEq>(values: &mut Vec<T>, value: T) -> bool {
+    match values.iter().position(|existing| *existing == value) {
+        Some(index) => {
+            values.remove(index);
+            false
+        }
+        None => {
+            values.push(value);
+            true
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::note::{AppNote, KnowledgeGraph};
+use std::collections::HashMap;
+
+/// How much even the weakest relation pulls its notes together
+const MIN_PULL: f32 = 0.2;
+
+/// How strongly notes are drawn to the middle, so ones without relations don't drift off
+/// to the sides
+const GRAVITY: f32 = 0.4;
+
+/// Graphs of up to this many notes feel the full gravity. Notes only push their neighbours
+/// away, so in larger graphs gravity weakens with the number of notes instead of packing
+/// them tight.
+const FULL_GRAVITY_NOTES: usize = 50;
+
+/// A grid cell and the cells around it, as column and row offsets
+const NEIGHBOURS: [(i64, i64); 9] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (0, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A note's place in a [`GraphLayout`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutNode {
+    pub note_id: String,
+    /// Centre of the node in pixels from the left of the drawing
+    pub x: f32,
+    /// Centre of the node in pixels from the top of the drawing
+    pub y: f32,
+}
+
+/// Where to draw each note of a knowledge graph; relations are drawn as edges between them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphLayout {
+    /// In the order the notes were given
+    pub nodes: Vec<LayoutNode>,
+}
+
+impl GraphLayout {
+    /// Where a note is drawn, if it is in the layout
+    pub fn node(&self, note_id: &str) -> Option<&LayoutNode> {
+        self.nodes.iter().find(|node| node.note_id == note_id)
+    }
+}
+
+/// Force-directed layout of a knowledge graph in a drawing of a given size
+///
+/// Notes close to each other push each other apart while each relation pulls its notes
+/// together, the stronger the relation the harder, and the notes move a little less every
+/// round until they settle. Only notes in neighbouring cells of a grid are compared, so a
+/// round takes time in proportion to the notes rather than to every pair of them. Notes start from spots that depend only on their IDs, so the same graph
+/// always gets the same layout and a note doesn't jump around when other notes change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForceLayout {
+    width: f32,
+    height: f32,
+    margin: f32,
+    iterations: usize,
+}
+
+impl ForceLayout {
+    /// Lay out graphs in a drawing `width` by `height` pixels
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width: width.max(0.0),
+            height: height.max(0.0),
+            margin: 40.0,
+            iterations: 300,
+        }
+    }
+    
+    /// Keep node centres this far from the sides of the drawing (40 pixels by default),
+    /// leaving room for the nodes and their titles
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin.max(0.0);
+        self
+    }
+    
+    /// Rounds of moving the notes before the layout is done (300 by default)
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+    
+    /// Place `notes`, pulled together by the graph's relations between them
+    pub fn layout(&self, graph: &KnowledgeGraph, notes: &[AppNote]) -> GraphLayout {
+        let left = self.margin.min(self.width / 2.0);
+        let top = self.margin.min(self.height / 2.0);
+        let (right, bottom) = (self.width - left, self.height - top);
+        let (area_width, area_height) = (right - left, bottom - top);
+        let center = ((left + right) / 2.0, (top + bottom) / 2.0);
+        
+        let index: HashMap<&str, usize> = notes.iter().enumerate().map(|(i, note)| (note.id.as_str(), i)).collect();
+        let edges: Vec<(usize, usize, f32)> = graph
+            .relations
+            .iter()
+            .filter_map(|relation| {
+                let from = *index.get(relation.from_note_id.as_str())?;
+                let to = *index.get(relation.to_note_id.as_str())?;
+                // A strength that isn't a number pulls as little as the weakest relation
+                let strength = if relation.strength.is_finite() { relation.strength.clamp(MIN_PULL, 1.0) } else { MIN_PULL };
+                (from != to).then_some((from, to, strength))
+            })
+            .collect();
+        
+        // The distance notes settle at: the side of the square each note would get if
+        // they shared the drawing evenly
+        let ideal = (area_width * area_height / notes.len().max(1) as f32).sqrt().max(1.0) * 0.8;
+        // Notes further apart than this don't push each other; it is also the grid's cell size
+        let reach = ideal * 2.0;
+        let mut positions: Vec<(f32, f32)> = notes
+            .iter()
+            .map(|note| {
+                let seed = seed(&note.id);
+                (left + area_width * fraction(seed), top + area_height * fraction(seed >> 32))
+            })
+            .collect();
+        
+        let gravity = GRAVITY * (FULL_GRAVITY_NOTES as f32 / notes.len() as f32).min(1.0);
+        for round in 0..self.iterations {
+            // How far a note may move this round, cooling down to nothing
+            let temperature = area_width.max(area_height) / 10.0 * (1.0 - round as f32 / self.iterations as f32);
+            let mut shifts = vec![(0.0f32, 0.0f32); notes.len()];
+            let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+            for (i, &position) in positions.iter().enumerate() {
+                grid.entry(cell(position, reach)).or_default().push(i);
+            }
+            for a in 0..notes.len() {
+                let (column, row) = cell(positions[a], reach);
+                for (column, row) in NEIGHBOURS.iter().map(|(dc, dr)| (column + dc, row + dr)) {
+                    let Some(others) = grid.get(&(column, row)) else {
+                        continue;
+                    };
+                    for &b in others.iter().filter(|&&b| b > a) {
+                        let (dx, dy, distance) = offset(positions[a], positions[b], a, b);
+                        if distance > reach {
+                            continue;
+                        }
+                        let push = ideal * ideal / distance;
+                        shifts[a].0 += dx / distance * push;
+                        shifts[a].1 += dy / distance * push;
+                        shifts[b].0 -= dx / distance * push;
+                        shifts[b].1 -= dy / distance * push;
+                    }
+                }
+            }
+            for &(a, b, strength) in &edges {
+                let (dx, dy, distance) = offset(positions[a], positions[b], a, b);
+                let pull = distance * distance / ideal * strength;
+                shifts[a].0 -= dx / distance * pull;
+                shifts[a].1 -= dy / distance * pull;
+                shifts[b].0 += dx / distance * pull;
+                shifts[b].1 += dy / distance * pull;
+            }
+            for (position, shift) in positions.iter_mut().zip(shifts) {
+                let shift_x = shift.0 + (center.0 - position.0) * gravity;
+                let shift_y = shift.1 + (center.1 - position.1) * gravity;
+                let length = shift_x.hypot(shift_y);
+                if length > 0.0 {
+                    let moved = length.min(temperature);
+                    position.0 += shift_x / length * moved;
+                    position.1 += shift_y / length * moved;
+                }
+            }
+        }
+        
+        // Notes move freely while settling, as the sides of the drawing would trap them;
+        // the result is then scaled and centred to fill it
+        let (min_x, max_x) = bounds(positions.iter().map(|position| position.0));
+        let (min_y, max_y) = bounds(positions.iter().map(|position| position.1));
+        let scale = [area_width / (max_x - min_x), area_height / (max_y - min_y)]
+            .into_iter()
+            .filter(|scale| scale.is_finite())
+            .reduce(f32::min)
+            .unwrap_or(1.0);
+        for position in &mut positions {
+            position.0 = (center.0 + (position.0 - (min_x + max_x) / 2.0) * scale).clamp(left, right);
+            position.1 = (center.1 + (position.1 - (min_y + max_y) / 2.0) * scale).clamp(top, bottom);
+        }
+        
+        GraphLayout {
+            nodes: notes
+                .iter()
+                .zip(positions)
+                .map(|(note, (x, y))| LayoutNode { note_id: note.id.clone(), x, y })
+                .collect(),
+        }
+    }
+}
+
+/// The column and row of the grid cell of `size` pixels a position falls in
+fn cell(position: (f32, f32), size: f32) -> (i64, i64) {
+    ((position.0 / size).floor() as i64, (position.1 / size).floor() as i64)
+}
+
+/// The vector from note `b` to note `a` and its length, never zero: notes on the same
+/// spot are pulled apart sideways, in an order that depends on their indices
+fn offset(a: (f32, f32), b: (f32, f32), a_index: usize, b_index: usize) -> (f32, f32, f32) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = dx.hypot(dy);
+    if distance < 0.01 {
+        let dx = if a_index < b_index { -0.01 } else { 0.01 };
+        return (dx, 0.0, 0.01);
+    }
+    (dx, dy, distance)
+}
+
+/// The smallest and largest of some coordinates
+fn bounds(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values.fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+/// A stable 64-bit FNV-1a hash of a note ID
+fn seed(note_id: &str) -> u64 {
+    note_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// The low 32 bits of a hash as a fraction from 0 to 1
+fn fraction(hash: u64) -> f32 {
+    (hash & 0xffff_ffff) as f32 / u32::MAX as f32
+}
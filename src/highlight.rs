@@ -0,0 +1,172 @@
+use crate::note::AppNote;
+use crate::search::{match_weight, TypoTolerance};
+use std::ops::Range;
+
+/// Characters of content shown around the first match
+pub const SNIPPET_LENGTH: usize = 60;
+
+// How much of the snippet comes before the first match
+const SNIPPET_LEAD: usize = 20;
+
+const ELLIPSIS: &str = "…";
+
+/// Text with the byte ranges of the words a search matched
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HighlightedText {
+    pub text: String,
+    /// Sorted, non-overlapping byte ranges into `text`
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl HighlightedText {
+    /// Text without highlights
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            highlights: Vec::new(),
+        }
+    }
+    
+    /// Highlight the words of the text that match any of the search terms, the same way
+    /// the search index matches them (whole words, completed words and typos)
+    pub fn find(text: &str, terms: &[&str], tolerance: TypoTolerance) -> Self {
+        let mut highlights = Vec::new();
+        let mut word_start = None;
+        for (index, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+            match (c.is_alphanumeric(), word_start) {
+                (true, None) => word_start = Some(index),
+                (false, Some(start)) => {
+                    let word = text[start..index].to_lowercase();
+                    if terms.iter().any(|term| match_weight(&term.to_lowercase(), &word, tolerance).is_some()) {
+                        highlights.push(start..index);
+                    }
+                    word_start = None;
+                }
+                _ => {}
+            }
+        }
+        Self {
+            text: text.to_string(),
+            highlights,
+        }
+    }
+    
+    pub fn is_highlighted(&self) -> bool {
+        !self.highlights.is_empty()
+    }
+    
+    /// A single line of at most `max_chars` characters (plus ellipses) around the first
+    /// highlight, cut at word boundaries where possible
+    pub fn snippet(&self, max_chars: usize) -> Self {
+        // Same byte length, so the highlights stay valid
+        let line: String = self.text.chars().map(|c| if c.is_ascii_whitespace() { ' ' } else { c }).collect();
+        let boundaries: Vec<usize> = line.char_indices().map(|(index, _)| index).chain([line.len()]).collect();
+        let char_count = boundaries.len() - 1;
+        if char_count <= max_chars {
+            return Self {
+                text: line,
+                highlights: self.highlights.clone(),
+            };
+        }
+        
+        let first_match = self.highlights.first().map_or(0, |range| range.start);
+        let first_match_char = boundaries.partition_point(|index| *index < first_match);
+        let mut start = first_match_char.saturating_sub(SNIPPET_LEAD).min(char_count - max_chars);
+        let mut end = start + max_chars;
+        
+        // Don't start or end in the middle of a word
+        if start > 0 {
+            if let Some(space) = line[boundaries[start]..first_match].find(' ') {
+                start = boundaries.partition_point(|index| *index <= boundaries[start] + space);
+            }
+        }
+        if end < char_count {
+            let first_match_end = self.highlights.first().map_or(0, |range| range.end);
+            if let Some(space) = line[boundaries[start]..boundaries[end]].rfind(' ') {
+                let space = boundaries[start] + space;
+                if space >= first_match_end {
+                    end = boundaries.partition_point(|index| *index < space);
+                }
+            }
+        }
+        
+        let (start_byte, end_byte) = (boundaries[start], boundaries[end]);
+        let prefix = if start > 0 { ELLIPSIS } else { "" };
+        let suffix = if end < char_count { ELLIPSIS } else { "" };
+        let highlights = self
+            .highlights
+            .iter()
+            .filter(|range| range.start >= start_byte && range.end <= end_byte)
+            .map(|range| {
+                let offset = prefix.len() + range.start - start_byte;
+                offset..offset + range.len()
+            })
+            .collect();
+        Self {
+            text: format!("{}{}{}", prefix, line[start_byte..end_byte].trim_end(), suffix),
+            highlights,
+        }
+    }
+    
+    /// The text split into pieces, each flagged with whether it is highlighted
+    pub fn segments(&self) -> Vec<(&str, bool)> {
+        let mut segments = Vec::new();
+        let mut position = 0;
+        for range in &self.highlights {
+            if range.start > position {
+                segments.push((&self.text[position..range.start], false));
+            }
+            segments.push((&self.text[range.clone()], true));
+            position = range.end;
+        }
+        if position < self.text.len() {
+            segments.push((&self.text[position..], false));
+        }
+        segments
+    }
+}
+
+/// Why a note matched a search: its title with the matching words highlighted and a
+/// short highlighted snippet of its content (or of the first matching checklist item,
+/// or of its tags if only a tag matched)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteMatch {
+    pub note_id: String,
+    pub title: HighlightedText,
+    pub snippet: HighlightedText,
+}
+
+impl NoteMatch {
+    pub fn new(note: &AppNote, terms: &[&str], tolerance: TypoTolerance) -> Self {
+        let content = HighlightedText::find(&note.content, terms, tolerance);
+        let checklist = note
+            .checklist
+            .iter()
+            .map(|item| HighlightedText::find(&item.text, terms, tolerance))
+            .find(HighlightedText::is_highlighted);
+        let snippet = if content.is_highlighted() {
+            content.snippet(SNIPPET_LENGTH)
+        } else if let Some(item) = checklist {
+            item.snippet(SNIPPET_LENGTH)
+        } else {
+            let mut tags: Vec<&str> = note.tags.iter().map(String::as_str).collect();
+            tags.sort_unstable();
+            let tags = HighlightedText::find(
+                &tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>().join(" "),
+                terms,
+                tolerance,
+            );
+            if tags.is_highlighted() {
+                tags.snippet(SNIPPET_LENGTH)
+            } else {
+                content.snippet(SNIPPET_LENGTH)
+            }
+        };
+        
+        Self {
+            note_id: note.id.clone(),
+            title: HighlightedText::find(&note.title, terms, tolerance),
+            snippet,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::note::AppNote;
+use crate::timestamp;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Number of revisions kept per note unless a store is configured otherwise
+pub const DEFAULT_REVISION_LIMIT: usize = 50;
+
+/// Marker used in `changed_fields` for the first revision of a note
+pub const CREATED_MARKER: &str = "created";
+
+/// A snapshot of a note taken when it was saved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NoteRevision {
+    pub id: String,
+    pub note_id: String,
+    #[serde(with = "timestamp::serde_timestamp")]
+    pub timestamp: DateTime<Utc>,
+    pub title: String,
+    pub content: String,
+    pub changed_fields: Vec<String>,
+}
+
+impl NoteRevision {
+    /// Snapshot a note, recording which fields changed since the previous save
+    pub fn from_note(note: &AppNote, changed_fields: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            note_id: note.id.clone(),
+            timestamp: Utc::now(),
+            title: note.title.clone(),
+            content: note.content.clone(),
+            changed_fields,
+        }
+    }
+}
+
+/// Fields of a note that differ between two versions of it.
+/// Position and size are layout, not content, so they aren't tracked.
+pub fn changed_fields(before: Option<&AppNote>, after: &AppNote) -> Vec<String> {
+    let Some(before) = before else {
+        return vec![CREATED_MARKER.to_string()];
+    };
+    
+    let mut changed = Vec::new();
+    let mut track = |name: &str, differs: bool| {
+        if differs {
+            changed.push(name.to_string());
+        }
+    };
+    track("title", before.title != after.title);
+    track("content", before.content != after.content);
+    track("color", before.color != after.color);
+    track("text_color", before.text_color != after.text_color);
+    track("tags", before.tags != after.tags);
+    track("workflow_status", before.workflow_status != after.workflow_status);
+    track("priority", before.priority != after.priority);
+    track("due_date", before.due_date != after.due_date);
+    track("estimated_time", before.estimated_time != after.estimated_time);
+    track("checklist", before.checklist != after.checklist);
+    track("completion_percentage", before.completion_percentage != after.completion_percentage);
+    changed
+}
+
+/// Trim a note's revision list (oldest first) down to `limit` entries
+pub fn enforce_retention(revisions: &mut Vec<NoteRevision>, limit: usize) {
+    if revisions.len() > limit {
+        let excess = revisions.len() - limit;
+        revisions.drain(..excess);
+    }
+}
+
+/// One line of a line-level diff
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
+
+/// Line-level diff of two texts based on their longest common subsequence
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let (n, m) = (old_lines.len(), new_lines.len());
+    
+    // lcs[i][j] = length of the LCS of old_lines[i..] and new_lines[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    
+    let mut diff = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            diff.push(DiffLine::Unchanged(old_lines[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old_lines[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new_lines[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old_lines[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+    diff.extend(new_lines[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+    diff
+}
+
+/// Line-level diff between two revisions; the title is compared as the first line
+pub fn diff_revisions(old: &NoteRevision, new: &NoteRevision) -> Vec<DiffLine> {
+    diff_lines(
+        &format!("{}\n{}", old.title, old.content),
+        &format!("{}\n{}", new.title, new.content),
+    )
+}
@@ -0,0 +1,94 @@
+use crate::markdown::{self, parse_wiki_link};
+use crate::note::AppNote;
+
+/// Titles a note's content links to with `[[Title]]` or `[[Title|label]]`, in order of
+/// first appearance and once each. Links inside code aren't links.
+pub fn linked_titles(content: &str) -> Vec<String> {
+    let mut titles: Vec<String> = Vec::new();
+    for block in markdown::parse(content) {
+        for title in block.spans.into_iter().filter_map(|span| span.note_link) {
+            if !titles.iter().any(|known| same_title(known, &title)) {
+                titles.push(title);
+            }
+        }
+    }
+    titles
+}
+
+/// Whether a link title names a note title; case and surrounding whitespace don't matter
+pub fn same_title(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Whether `[[title]]` can link to a note with this title. Brackets, `|` and line breaks
+/// would end the link early or turn the rest into a label.
+pub fn is_linkable_title(title: &str) -> bool {
+    !title.contains(['[', ']', '|', '\n', '\r'])
+}
+
+/// The note a link from `from_id` to `title` points to: the oldest other note with that
+/// title, so a newer note that happens to share it doesn't take existing links over
+pub fn resolve<'a>(notes: &'a [AppNote], from_id: &str, title: &str) -> Option<&'a AppNote> {
+    notes
+        .iter()
+        .filter(|note| note.id != from_id && same_title(&note.title, title))
+        .min_by_key(|note| note.created_at)
+}
+
+/// Point the links to `old_title` at `new_title` instead, keeping their labels. Links
+/// inside code are left alone, as they aren't links. Returns `None` when the content has
+/// no such link.
+pub fn rename_links(content: &str, old_title: &str, new_title: &str) -> Option<String> {
+    let mut renamed = String::with_capacity(content.len());
+    let mut changed = false;
+    // Fenced code blocks are found the way `markdown::parse` finds them
+    let mut in_code = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if is_fence {
+            in_code = !in_code;
+        }
+        if is_fence || in_code {
+            renamed.push_str(line);
+        } else {
+            changed |= rename_inline_links(line, old_title, new_title, &mut renamed);
+        }
+    }
+    changed.then_some(renamed)
+}
+
+/// Rename the links in a line outside code blocks onto `renamed`, stepping over escapes,
+/// code spans and other links the way `markdown::parse_inline` does. Returns whether any
+/// link was renamed.
+fn rename_inline_links(line: &str, old_title: &str, new_title: &str, renamed: &mut String) -> bool {
+    let mut changed = false;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let skipped = match c {
+            '\\' => after.chars().next().filter(char::is_ascii_punctuation).map(|escaped| 1 + escaped.len_utf8()),
+            '`' => after.find('`').map(|end| end + 2),
+            '[' => match parse_wiki_link(rest) {
+                Some((title, label, length)) if same_title(title, old_title) => {
+                    // A link without a label showed the title, so it shows the new one
+                    if label == title {
+                        renamed.push_str(&format!("[[{}]]", new_title));
+                    } else {
+                        renamed.push_str(&format!("[[{}|{}]]", new_title, label));
+                    }
+                    changed = true;
+                    rest = &rest[length..];
+                    continue;
+                }
+                Some((_, _, length)) => Some(length),
+                None => None,
+            },
+            _ => None,
+        };
+        let length = skipped.unwrap_or(c.len_utf8());
+        renamed.push_str(&rest[..length]);
+        rest = &rest[length..];
+    }
+    changed
+}
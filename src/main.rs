@@ -1,20 +1,711 @@
-mod note;
-mod storage;
-
-use slint::{ComponentHandle, ModelRc, VecModel, Model};
-use note::{AppNote, SerializableColor, KnowledgeGraph, WorkflowStatus as AppWorkflowStatus, Priority};
-use std::collections::HashSet;
+use rust_slint_sticky::controller::{spawn_storage_writer, AppController, StorageOp};
+use rust_slint_sticky::filter::{DateField, DateRange, FilterCondition, NoteFilter};
+use rust_slint_sticky::markdown::{self, BlockKind};
+use rust_slint_sticky::note::{AppNote, KnowledgeGraph, NoteEdit, Priority, RelationError, RelationType, SerializableColor, WorkflowStatus};
+use rust_slint_sticky::search::TypoTolerance;
+use rust_slint_sticky::storage::DEFAULT_TRASH_RETENTION_DAYS;
+use rust_slint_sticky::timestamp;
+use rust_slint_sticky::{ForceLayout, GraphLayout, HighlightedText, LayoutNode, NoteMatch, NoteStorage, NoteStore, SortOrder, SqliteNoteStorage};
+use slint::{ComponentHandle, Model, ModelRc, VecModel};
+use std::cell::{Cell, RefCell};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 slint::include_modules!();
 
+/// Checklist items shown on a note card; the rest are only counted
+const CARD_CHECKLIST_ROWS: usize = 3;
+
+/// Lines of rendered content built for a note card (more wouldn't fit)
+const CARD_CONTENT_LINES: usize = 8;
+
+/// Characters per line when wrapping rendered Markdown on a card and in the editor preview
+const CARD_LINE_CHARS: usize = 34;
+const PREVIEW_LINE_CHARS: usize = 60;
+
+/// Radius of a note's circle in the knowledge graph view, and how far its centre is kept
+/// from the sides so its title fits
+const GRAPH_NODE_RADIUS: f32 = 16.0;
+const GRAPH_MARGIN: f32 = 60.0;
+
+/// Open the storage backend selected by `STICKY_NOTES_BACKEND` ("json" by default, or "sqlite").
+/// When SQLite can't be opened the JSON store is used instead, along with a warning to show
+/// in the window so nobody mistakes it for their SQLite notes.
+async fn open_note_store() -> (Arc<dyn NoteStore>, Option<String>) {
+    let backend = std::env::var("STICKY_NOTES_BACKEND").unwrap_or_default();
+    let mut warning = None;
+    if backend.eq_ignore_ascii_case("sqlite") {
+        match SqliteNoteStorage::new().await {
+            Ok(store) => return (Arc::new(store), None),
+            Err(e) => {
+                let message = format!(
+                    "Couldn't open the SQLite notes store ({}). Your SQLite notes are untouched, but this session shows and saves the notes in notes.json instead.",
+                    e
+                );
+                eprintln!("❌ {}", message);
+                warning = Some(message);
+            }
+        }
+    }
+    (Arc::new(NoteStorage::new().await), warning)
+}
+
+/// Days a trashed note is kept, from `STICKY_NOTES_TRASH_DAYS` (30 by default)
+fn trash_retention_days() -> u32 {
+    std::env::var("STICKY_NOTES_TRASH_DAYS")
+        .ok()
+        .and_then(|days| days.trim().parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
+
+/// Typos allowed in search words, from `STICKY_NOTES_TYPO_TOLERANCE` ("off", "auto" or a number)
+fn typo_tolerance() -> TypoTolerance {
+    match std::env::var("STICKY_NOTES_TYPO_TOLERANCE") {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            eprintln!("⚠️ {}, using auto", e);
+            TypoTolerance::Auto
+        }),
+        Err(_) => TypoTolerance::Auto,
+    }
+}
+
+/// Replace the rows of a model, only touching rows that changed. Rows holding models can't
+/// be compared this way, as models are only equal to themselves; use a [`SourcedModel`].
+fn update_model<T: Clone + PartialEq + 'static>(model: &VecModel<T>, rows: Vec<T>) {
+    if model.row_count() != rows.len() {
+        model.set_vec(rows);
+        return;
+    }
+    for (i, row) in rows.into_iter().enumerate() {
+        if model.row_data(i).as_ref() != Some(&row) {
+            model.set_row_data(i, row);
+        }
+    }
+}
+
+/// A model whose rows are built from source values, only rebuilding the rows whose
+/// source changed so unchanged cards are kept
+struct SourcedModel<S, T: 'static> {
+    model: Rc<VecModel<T>>,
+    sources: RefCell<Vec<S>>,
+}
+
+impl<S: PartialEq, T: Clone + 'static> SourcedModel<S, T> {
+    fn new() -> Self {
+        Self {
+            model: Rc::new(VecModel::default()),
+            sources: RefCell::new(Vec::new()),
+        }
+    }
+    
+    fn update(&self, sources: Vec<S>, build: impl Fn(&S) -> T) {
+        let mut current = self.sources.borrow_mut();
+        if current.len() != sources.len() {
+            self.model.set_vec(sources.iter().map(&build).collect::<Vec<_>>());
+        } else {
+            for (i, source) in sources.iter().enumerate() {
+                if current[i] != *source {
+                    self.model.set_row_data(i, build(source));
+                }
+            }
+        }
+        *current = sources;
+    }
+}
+
+/// What a note card is built from
+#[derive(PartialEq)]
+struct CardSource {
+    note: AppNote,
+    blocked_by: String,
+    note_match: Option<NoteMatch>,
+}
+
+/// What a knowledge graph layout depends on: the notes, the relations pulling them
+/// together (by their strength's bits, so a NaN strength still matches itself) and the
+/// size of the drawing
+#[derive(Clone, PartialEq)]
+struct LayoutKey {
+    note_ids: Vec<String>,
+    relations: Vec<(String, String, u32)>,
+    size: (f32, f32),
+}
+
+/// The knowledge graph layouts, which are laid out off the UI thread as a large graph
+/// takes a while
+#[derive(Default)]
+struct GraphLayouts {
+    /// The last finished layout, drawn until the next one is done
+    done: Option<(LayoutKey, GraphLayout)>,
+    /// What the layout being laid out is for
+    pending: Option<LayoutKey>,
+}
+
+/// A card's rendered Markdown, with the content it was rendered from
+type RenderedContent = (String, ModelRc<MarkdownBlock>);
+
+/// The Slint models rendered by the main window
+#[derive(Clone)]
+struct UiModels {
+    notes: Rc<SourcedModel<CardSource, StickyNote>>,
+    filtered_notes: Rc<SourcedModel<CardSource, StickyNote>>,
+    // Rendered content of each card by note ID, so a card that is rebuilt for another
+    // change doesn't render its Markdown again
+    card_markdown: Rc<RefCell<HashMap<String, RenderedContent>>>,
+    // Blocked-by titles by note ID, and the controller revision they, the plan and the
+    // saved search counts were computed at
+    blocked_by: Rc<RefCell<HashMap<String, String>>>,
+    derived_revision: Rc<Cell<Option<u64>>>,
+    relations: Rc<VecModel<NoteRelation>>,
+    // Titles of the live notes, for picking the notes of a new relation
+    relation_note_titles: Rc<VecModel<slint::SharedString>>,
+    trashed_notes: Rc<VecModel<TrashedNote>>,
+    planned_tasks: Rc<VecModel<PlannedTask>>,
+    graph_nodes: Rc<VecModel<GraphNode>>,
+    graph_edges: Rc<VecModel<GraphEdge>>,
+    graph_legend: Rc<VecModel<FilterChip>>,
+    // The knowledge graph layout, laid out again only once what it depends on changes
+    graph_layout: Arc<Mutex<GraphLayouts>>,
+    saved_searches: Rc<VecModel<SavedSearchItem>>,
+    filter_status_options: Rc<VecModel<FilterOption>>,
+    filter_priority_options: Rc<VecModel<FilterOption>>,
+    filter_tag_options: Rc<VecModel<FilterOption>>,
+    filter_color_options: Rc<VecModel<FilterOption>>,
+    filter_chips: Rc<VecModel<FilterChip>>,
+    // Saved search names for the header picker, after its placeholder entry
+    saved_search_labels: Rc<VecModel<slint::SharedString>>,
+}
+
+impl UiModels {
+    fn new() -> Self {
+        Self {
+            notes: Rc::new(SourcedModel::new()),
+            filtered_notes: Rc::new(SourcedModel::new()),
+            card_markdown: Rc::new(RefCell::new(HashMap::new())),
+            blocked_by: Rc::new(RefCell::new(HashMap::new())),
+            derived_revision: Rc::new(Cell::new(None)),
+            relations: Rc::new(VecModel::default()),
+            relation_note_titles: Rc::new(VecModel::default()),
+            trashed_notes: Rc::new(VecModel::default()),
+            planned_tasks: Rc::new(VecModel::default()),
+            graph_nodes: Rc::new(VecModel::default()),
+            graph_edges: Rc::new(VecModel::default()),
+            graph_legend: Rc::new(VecModel::default()),
+            graph_layout: Arc::new(Mutex::new(GraphLayouts::default())),
+            saved_searches: Rc::new(VecModel::default()),
+            filter_status_options: Rc::new(VecModel::default()),
+            filter_priority_options: Rc::new(VecModel::default()),
+            filter_tag_options: Rc::new(VecModel::default()),
+            filter_color_options: Rc::new(VecModel::default()),
+            filter_chips: Rc::new(VecModel::default()),
+            saved_search_labels: Rc::new(VecModel::default()),
+        }
+    }
+    
+    /// Hand the models to the window
+    fn attach(&self, ui: &MainWindow) {
+        ui.set_notes(ModelRc::from(self.notes.model.clone()));
+        ui.set_filtered_notes(ModelRc::from(self.filtered_notes.model.clone()));
+        ui.set_relations(ModelRc::from(self.relations.clone()));
+        ui.set_relation_note_titles(ModelRc::from(self.relation_note_titles.clone()));
+        ui.set_relation_types(ModelRc::new(VecModel::from(
+            RelationType::ALL.iter().map(|relation_type| relation_type.label().into()).collect::<Vec<slint::SharedString>>(),
+        )));
+        ui.set_trashed_notes(ModelRc::from(self.trashed_notes.clone()));
+        ui.set_planned_tasks(ModelRc::from(self.planned_tasks.clone()));
+        ui.set_graph_nodes(ModelRc::from(self.graph_nodes.clone()));
+        ui.set_graph_edges(ModelRc::from(self.graph_edges.clone()));
+        ui.set_graph_legend(ModelRc::from(self.graph_legend.clone()));
+        ui.set_saved_searches(ModelRc::from(self.saved_searches.clone()));
+        ui.set_saved_search_labels(ModelRc::from(self.saved_search_labels.clone()));
+        ui.set_filter_status_options(ModelRc::from(self.filter_status_options.clone()));
+        ui.set_filter_priority_options(ModelRc::from(self.filter_priority_options.clone()));
+        ui.set_filter_tag_options(ModelRc::from(self.filter_tag_options.clone()));
+        ui.set_filter_color_options(ModelRc::from(self.filter_color_options.clone()));
+        ui.set_filter_chips(ModelRc::from(self.filter_chips.clone()));
+    }
+    
+    /// Render the controller's current state
+    fn sync(&self, ui: &MainWindow, controller: &AppController) {
+        // Moving a note leaves the revision alone, so a drag doesn't recompute any of this
+        let stale = self.derived_revision.get() != Some(controller.revision());
+        if stale {
+            self.sync_derived(ui, controller);
+            self.derived_revision.set(Some(controller.revision()));
+        }
+        
+        let blocked_by = self.blocked_by.borrow();
+        let card = |note: &AppNote, note_match: Option<NoteMatch>| CardSource {
+            note: note.clone(),
+            blocked_by: blocked_by.get(&note.id).cloned().unwrap_or_default(),
+            note_match,
+        };
+        self.notes.update(
+            controller.notes().iter().map(|note| card(note, None)).collect(),
+            |source| self.card(source),
+        );
+        self.filtered_notes.update(
+            controller.visible_notes().into_iter().map(|note| card(note, controller.note_match(note))).collect(),
+            |source| self.card(source),
+        );
+        let live: HashSet<&str> = controller.notes().iter().map(|note| note.id.as_str()).collect();
+        self.card_markdown.borrow_mut().retain(|note_id, _| live.contains(note_id.as_str()));
+        
+        // A note focused in the graph view narrows the relations down to its own
+        let focus = ui.get_graph_focus_id();
+        let focused = controller.notes().iter().find(|note| note.id == focus.as_str());
+        if focused.is_none() && !focus.is_empty() {
+            ui.set_graph_focus_id(Default::default());
+        }
+        let relations: Vec<_> = controller
+            .visible_relations()
+            .into_iter()
+            .filter(|relation| match focused {
+                Some(note) => relation.from_note_id == note.id || relation.to_note_id == note.id,
+                None => true,
+            })
+            .collect();
+        update_model(
+            &self.relations,
+            relations.iter().map(|relation| {
+                let title = |note_id: &str| controller.note(note_id).map(|note| note.title.clone()).unwrap_or_default();
+                NoteRelation {
+                    id: relation.id.clone().into(),
+                    from_note_id: relation.from_note_id.clone().into(),
+                    to_note_id: relation.to_note_id.clone().into(),
+                    from_title: title(&relation.from_note_id).into(),
+                    to_title: title(&relation.to_note_id).into(),
+                    relation_type: relation.relation_type.label().into(),
+                    strength: relation.strength,
+                    description: relation.description.clone().unwrap_or_default().into(),
+                    origin: format!("{:?}", relation.origin).into(),
+                }
+            }).collect(),
+        );
+        self.sync_graph(ui, controller, focused, &relations);
+        update_model(&self.relation_note_titles, controller.notes().iter().map(|note| note.title.clone().into()).collect());
+        update_model(
+            &self.trashed_notes,
+            controller.trash().iter().map(|note| TrashedNote {
+                id: note.id.clone().into(),
+                title: note.title.clone().into(),
+                deleted_at: note.deleted_at.as_ref().map(timestamp::format_local).unwrap_or_default().into(),
+                days_left: controller.days_left_in_trash(note) as i32,
+            }).collect(),
+        );
+        let filter = controller.filter();
+        update_model(
+            &self.filter_status_options,
+            WorkflowStatus::ALL
+                .iter()
+                .map(|status| filter_option(status.label(), filter.statuses.contains(status)))
+                .collect(),
+        );
+        update_model(
+            &self.filter_priority_options,
+            Priority::ALL
+                .iter()
+                .map(|priority| filter_option(priority.label(), filter.priorities.contains(priority)))
+                .collect(),
+        );
+        // Selected tags stay listed even once no note carries them, so they can be deselected
+        let mut tags: Vec<&str> = controller.all_tags();
+        tags.extend(filter.tags.iter().map(String::as_str));
+        tags.sort_unstable();
+        tags.dedup();
+        update_model(
+            &self.filter_tag_options,
+            tags.into_iter()
+                .map(|tag| filter_option(tag, filter.tags.iter().any(|selected| selected == tag)))
+                .collect(),
+        );
+        let mut colors = controller.all_colors();
+        for color in &filter.colors {
+            if !colors.contains(color) {
+                colors.push(*color);
+            }
+        }
+        update_model(
+            &self.filter_color_options,
+            colors
+                .into_iter()
+                .map(|color| FilterOption {
+                    color: color.into(),
+                    ..filter_option(&color.to_hex(), filter.colors.contains(&color))
+                })
+                .collect(),
+        );
+        update_model(
+            &self.filter_chips,
+            filter
+                .conditions()
+                .iter()
+                .map(|condition| FilterChip {
+                    label: condition.label().into(),
+                    color: match condition {
+                        FilterCondition::Color(color) => (*color).into(),
+                        _ => slint::Color::from_argb_u8(0, 0, 0, 0),
+                    },
+                })
+                .collect(),
+        );
+        
+        let active = controller.active_saved_search();
+        ui.set_active_saved_search(active.map(|saved| saved.id.clone()).unwrap_or_default().into());
+        ui.set_saved_search_index(
+            active
+                .and_then(|active| controller.saved_searches().iter().position(|saved| saved.id == active.id))
+                .map_or(0, |index| index as i32 + 1),
+        );
+        
+        ui.set_sort_order(controller.sort_order().label().into());
+        ui.set_show_search_results(controller.is_searching_text());
+        ui.set_search_error(controller.search_error().map(ToString::to_string).unwrap_or_default().into());
+        ui.set_can_undo(controller.can_undo());
+        ui.set_can_redo(controller.can_redo());
+    }
+    
+    /// Recompute the views derived from the notes, relations and saved searches as a whole
+    fn sync_derived(&self, ui: &MainWindow, controller: &AppController) {
+        *self.blocked_by.borrow_mut() = controller
+            .notes()
+            .iter()
+            .map(|note| {
+                let titles: Vec<&str> = controller.blocked_by(&note.id).iter().map(|prerequisite| prerequisite.title.as_str()).collect();
+                (note.id.clone(), titles.join(", "))
+            })
+            .collect();
+        
+        let plan = controller.plan();
+        update_model(
+            &self.planned_tasks,
+            plan.tasks.iter().filter_map(|task| {
+                let note = controller.note(&task.note_id)?;
+                Some(PlannedTask {
+                    id: task.note_id.clone().into(),
+                    title: note.title.clone().into(),
+                    estimate: note.estimated_time.map(format_minutes).unwrap_or_default().into(),
+                    finish: format!("+{}", format_minutes(task.earliest_finish)).into(),
+                    ready: task.ready,
+                    critical: plan.is_critical(&task.note_id),
+                })
+            }).collect(),
+        );
+        ui.set_plan_earliest_finish(format_minutes(plan.earliest_finish).into());
+        
+        // Counts are recomputed on every change so each saved search stays live
+        let saved_searches: Vec<SavedSearchItem> = controller
+            .saved_searches()
+            .iter()
+            .map(|saved| SavedSearchItem {
+                id: saved.id.clone().into(),
+                name: saved.name.clone().into(),
+                query: saved.query.clone().into(),
+                count: controller.saved_search_results(&saved.id).len() as i32,
+            })
+            .collect();
+        let mut labels = vec![slint::SharedString::from("⭐ Saved searches")];
+        labels.extend(saved_searches.iter().map(|saved| slint::format!("{} ({})", saved.name, saved.count)));
+        update_model(&self.saved_search_labels, labels);
+        update_model(&self.saved_searches, saved_searches);
+    }
+    
+    /// Build a note card, reusing its rendered content while the content is unchanged
+    fn card(&self, source: &CardSource) -> StickyNote {
+        let note = &source.note;
+        let content_blocks = {
+            let mut rendered = self.card_markdown.borrow_mut();
+            match rendered.get(&note.id) {
+                Some((content, blocks)) if *content == note.content => blocks.clone(),
+                _ => {
+                    let blocks = markdown_blocks(&note.content, CARD_LINE_CHARS, CARD_CONTENT_LINES);
+                    rendered.insert(note.id.clone(), (note.content.clone(), blocks.clone()));
+                    blocks
+                }
+            }
+        };
+        let mut slint_note = app_note_to_slint_note(note, &source.blocked_by, content_blocks);
+        if let Some(note_match) = &source.note_match {
+            slint_note.title_segments = text_segments(&note_match.title);
+            slint_note.snippet = text_segments(&note_match.snippet);
+        }
+        slint_note
+    }
+    
+    /// Lay out the knowledge graph view, while it is shown, with the focused note's
+    /// `relations` highlighted
+    fn sync_graph(&self, ui: &MainWindow, controller: &AppController, focused: Option<&AppNote>, relations: &[&rust_slint_sticky::note::NoteRelation]) {
+        ui.set_graph_focus_title(focused.map(|note| note.title.clone()).unwrap_or_default().into());
+        let related: HashSet<&str> = relations
+            .iter()
+            .flat_map(|relation| [relation.from_note_id.as_str(), relation.to_note_id.as_str()])
+            .filter(|note_id| focused.is_some_and(|note| note.id != *note_id))
+            .collect();
+        ui.set_graph_focus_summary(
+            format!(
+                "{} {} with {} {} · double-click a note to open it",
+                relations.len(),
+                if relations.len() == 1 { "relation" } else { "relations" },
+                related.len(),
+                if related.len() == 1 { "note" } else { "notes" },
+            )
+            .into(),
+        );
+        
+        let (width, height) = (ui.get_graph_width(), ui.get_graph_height());
+        if !ui.get_show_knowledge_graph() || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        let all_relations = controller.visible_relations();
+        let key = LayoutKey {
+            note_ids: controller.notes().iter().map(|note| note.id.clone()).collect(),
+            relations: all_relations
+                .iter()
+                .map(|relation| (relation.from_note_id.clone(), relation.to_note_id.clone(), relation.strength.to_bits()))
+                .collect(),
+            size: (width, height),
+        };
+        let mut layouts = self.graph_layout.lock().unwrap();
+        let laid_out = layouts.done.as_ref().is_some_and(|(done, _)| *done == key);
+        if laid_out {
+            // Back to what is on screen; a layout still on its way would be out of date
+            layouts.pending = None;
+        } else if layouts.pending.as_ref() != Some(&key) {
+            layouts.pending = Some(key.clone());
+            self.lay_out_graph(ui, controller, key);
+        }
+        let Some((_, layout)) = &layouts.done else {
+            return;
+        };
+        
+        let notes: HashMap<&str, &AppNote> = controller.notes().iter().map(|note| (note.id.as_str(), note)).collect();
+        let nodes: HashMap<&str, &LayoutNode> = layout.nodes.iter().map(|node| (node.note_id.as_str(), node)).collect();
+        update_model(
+            &self.graph_nodes,
+            layout.nodes.iter().filter_map(|node| {
+                let note = *notes.get(node.note_id.as_str())?;
+                let is_focused = focused.is_some_and(|focused| focused.id == note.id);
+                Some(GraphNode {
+                    id: note.id.clone().into(),
+                    title: note.title.clone().into(),
+                    x: node.x,
+                    y: node.y,
+                    color: note.color.into(),
+                    focused: is_focused,
+                    dimmed: focused.is_some() && !is_focused && !related.contains(note.id.as_str()),
+                })
+            }).collect(),
+        );
+        
+        update_model(
+            &self.graph_edges,
+            all_relations.iter().filter_map(|relation| {
+                let from = nodes.get(relation.from_note_id.as_str())?;
+                let to = nodes.get(relation.to_note_id.as_str())?;
+                let (color, line) = relation_style(relation.relation_type);
+                let path = edge_path((from.x, from.y), (to.x, to.y), line, !relation.relation_type.is_symmetric())?;
+                Some(GraphEdge {
+                    path: path.into(),
+                    color,
+                    width: 1.0 + relation.strength.clamp(0.0, 1.0) * 3.0,
+                    dimmed: focused.is_some() && !relations.iter().any(|shown| shown.id == relation.id),
+                })
+            }).collect(),
+        );
+        update_model(
+            &self.graph_legend,
+            RelationType::ALL
+                .iter()
+                .filter(|relation_type| all_relations.iter().any(|relation| relation.relation_type == **relation_type))
+                .map(|&relation_type| {
+                    let (color, line) = relation_style(relation_type);
+                    let arrow = if relation_type.is_symmetric() { "" } else { "▶" };
+                    FilterChip {
+                        label: format!("{}{} {}", line.sample(), arrow, relation_type.label()).into(),
+                        color,
+                    }
+                })
+                .collect(),
+        );
+    }
+    
+    /// Lay out the live notes for `key`, which is pending, on a blocking task and draw the
+    /// result once it is done, unless a layout for something newer is pending by then
+    fn lay_out_graph(&self, ui: &MainWindow, controller: &AppController, key: LayoutKey) {
+        let (width, height) = key.size;
+        let settings = ForceLayout::new(width, height).with_margin(GRAPH_MARGIN);
+        let (graph, notes) = (controller.graph().clone(), controller.notes().to_vec());
+        let layouts = self.graph_layout.clone();
+        let ui_weak = ui.as_weak();
+        tokio::task::spawn_blocking(move || {
+            let layout = graph.layout(&notes, &settings);
+            let mut layouts = layouts.lock().unwrap();
+            if layouts.pending.as_ref() == Some(&key) {
+                layouts.pending = None;
+                layouts.done = Some((key, layout));
+                let _ = ui_weak.upgrade_in_event_loop(|ui| ui.invoke_graph_laid_out());
+            }
+        });
+    }
+}
+
+/// How the relations of a type are drawn in the knowledge graph view
+#[derive(Clone, Copy)]
+enum EdgeLine {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl EdgeLine {
+    /// A few characters that look like the line, for the key
+    fn sample(self) -> &'static str {
+        match self {
+            EdgeLine::Solid => "───",
+            EdgeLine::Dashed => "╌╌╌",
+            EdgeLine::Dotted => "┈┈┈",
+        }
+    }
+}
+
+/// The color and line of a relation type's edges: order between notes is solid, links
+/// dashed and loose relations dotted
+fn relation_style(relation_type: RelationType) -> (slint::Color, EdgeLine) {
+    let ((red, green, blue), line) = match relation_type {
+        RelationType::RelatedTo => ((0x86, 0x8e, 0x96), EdgeLine::Dotted),
+        RelationType::DependsOn => ((0xe6, 0x77, 0x00), EdgeLine::Solid),
+        RelationType::Blocks => ((0xc9, 0x2a, 0x2a), EdgeLine::Solid),
+        RelationType::PartOf => ((0x70, 0x48, 0xe8), EdgeLine::Solid),
+        RelationType::SubtaskOf => ((0x19, 0x71, 0xc2), EdgeLine::Solid),
+        RelationType::References => ((0x2b, 0x8a, 0x3e), EdgeLine::Dashed),
+        RelationType::Conflicts => ((0xe0, 0x31, 0x31), EdgeLine::Dashed),
+        RelationType::Extends => ((0x0c, 0x85, 0x99), EdgeLine::Dotted),
+    };
+    (slint::Color::from_rgb_u8(red, green, blue), line)
+}
+
+/// SVG path commands for an edge between two graph nodes: the line between the edges of
+/// their circles, broken up into dashes or dots, with an arrowhead at `to` when the
+/// relation has a direction. `None` when the circles touch and there is no line to draw.
+fn edge_path(from: (f32, f32), to: (f32, f32), line: EdgeLine, arrow: bool) -> Option<String> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dy);
+    if length <= 2.0 * GRAPH_NODE_RADIUS + 2.0 {
+        return None;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let point = |distance: f32| (from.0 + ux * distance, from.1 + uy * distance);
+    let (start, end) = (GRAPH_NODE_RADIUS + 2.0, length - GRAPH_NODE_RADIUS - 2.0);
+    let (dash, gap) = match line {
+        EdgeLine::Solid => (end - start, 0.0),
+        EdgeLine::Dashed => (8.0, 5.0),
+        EdgeLine::Dotted => (0.5, 5.0),
+    };
+    
+    let mut path = String::new();
+    let mut at = start;
+    while at < end {
+        let (a, b) = (point(at), point((at + dash).min(end)));
+        path.push_str(&format!("M {:.1} {:.1} L {:.1} {:.1} ", a.0, a.1, b.0, b.1));
+        at += dash + gap;
+    }
+    if arrow {
+        let tip = point(end);
+        let wing = |side: f32| (tip.0 - ux * 9.0 - uy * 5.0 * side, tip.1 - uy * 9.0 + ux * 5.0 * side);
+        let (left, right) = (wing(1.0), wing(-1.0));
+        path.push_str(&format!("M {:.1} {:.1} L {:.1} {:.1} L {:.1} {:.1}", left.0, left.1, tip.0, tip.1, right.0, right.1));
+    }
+    Some(path.trim_end().to_string())
+}
+
+/// A number of minutes the way estimates are read, e.g. "45m", "2h" or "1h 30m"
+fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+/// An unselected-or-selected filter panel toggle without a color swatch
+fn filter_option(label: &str, selected: bool) -> FilterOption {
+    FilterOption {
+        value: label.into(),
+        label: label.into(),
+        selected,
+        color: slint::Color::from_argb_u8(0, 0, 0, 0),
+    }
+}
+
+/// Show the filter's date ranges in the filter panel
+fn show_filter_dates(ui: &MainWindow, filter: &NoteFilter) {
+    let text = |date: Option<chrono::NaiveDate>| slint::SharedString::from(date.map(|date| date.to_string()).unwrap_or_default());
+    ui.set_filter_created_from(text(filter.created.from));
+    ui.set_filter_created_to(text(filter.created.to));
+    ui.set_filter_updated_from(text(filter.updated.from));
+    ui.set_filter_updated_to(text(filter.updated.to));
+    ui.set_filter_due_from(text(filter.due.from));
+    ui.set_filter_due_to(text(filter.due.to));
+}
+
+/// Show the note editor filled with the given values (an empty ID creates a new note)
+fn open_editor(ui: &MainWindow, note_id: &str, edit: &NoteEdit, backlinks: &[&AppNote]) {
+    ui.set_editor_title(edit.title.clone().into());
+    ui.set_editor_title_error("".into());
+    ui.set_editor_content(edit.content.clone().into());
+    ui.set_editor_color(edit.color.into());
+    ui.set_editor_text_color(match edit.text_color {
+        Some(text_color) => text_color.into(),
+        None => slint::Color::from_argb_u8(0, 0, 0, 0), // Transparent for auto-contrast
+    });
+    ui.set_editor_tags(edit.tags_text().into());
+    ui.set_editor_status(edit.workflow_status.label().into());
+    ui.set_editor_priority(edit.priority.label().into());
+    ui.set_editor_due_date(edit.due_date.map(|due| due.to_string()).unwrap_or_default().into());
+    ui.set_editor_due_date_error("".into());
+    ui.set_editor_estimated_time(edit.estimated_time.unwrap_or(0) as i32);
+    ui.set_editor_checklist(edit.checklist_text().into());
+    ui.set_editor_backlinks(ModelRc::new(VecModel::from(
+        backlinks.iter().map(|note| NoteLink {
+            id: note.id.clone().into(),
+            title: note.title.clone().into(),
+        }).collect::<Vec<_>>(),
+    )));
+    ui.set_editing_note_id(note_id.into());
+    ui.set_show_editor(true);
+}
+
+/// Show the editor on a live note, starting from the full stored note rather than its card
+fn edit_existing_note(ui: &MainWindow, controller: &AppController, note_id: &str) -> bool {
+    let Some(note) = controller.note(note_id) else {
+        return false;
+    };
+    open_editor(ui, &note.id, &NoteEdit::from_note(note), &controller.backlinks(&note.id));
+    true
+}
+
+/// Show why a relation change was rejected, or reset the relation form once it went through
+fn finish_relation_edit(ui: &MainWindow, result: Result<(), RelationError>) {
+    match result {
+        Ok(()) => {
+            ui.set_relation_error("".into());
+            ui.set_relation_editing_id("".into());
+            ui.set_relation_description("".into());
+        }
+        Err(error) => ui.set_relation_error(error.to_string().into()),
+    }
+}
+
 // Conversion functions between AppNote and Slint-generated StickyNote
-fn app_note_to_slint_note(note: &AppNote) -> StickyNote {
+fn app_note_to_slint_note(note: &AppNote, blocked_by: &str, content_blocks: ModelRc<MarkdownBlock>) -> StickyNote {
     StickyNote {
         id: note.id.clone().into(),
         title: note.title.clone().into(),
         content: note.content.clone().into(),
+        content_blocks,
         color: slint::Color::from_rgb_u8(
             note.color.red,
             note.color.green,
@@ -28,206 +719,308 @@ fn app_note_to_slint_note(note: &AppNote) -> StickyNote {
             ),
             None => slint::Color::from_argb_u8(0, 0, 0, 0), // Transparent for auto-contrast
         },
-        x: note.x.into(),
-        y: note.y.into(),
-        width: note.width.into(),
-        height: note.height.into(),
+        x: note.x,
+        y: note.y,
+        width: note.width,
+        height: note.height,
         tags: slint::ModelRc::new(slint::VecModel::from(note.tags.iter().map(|tag| tag.clone().into()).collect::<Vec<slint::SharedString>>())),
-        workflow_status: match note.workflow_status {
-            AppWorkflowStatus::Idea => "Idea".into(),
-            AppWorkflowStatus::Todo => "Todo".into(),
-            AppWorkflowStatus::InProgress => "In Progress".into(),
-            AppWorkflowStatus::Review => "Review".into(),
-            AppWorkflowStatus::Done => "Done".into(),
-            AppWorkflowStatus::Archived => "Archived".into(),
-        },
-        priority: match note.priority {
-            Priority::Low => "Low".into(),
-            Priority::Medium => "Medium".into(),
-            Priority::High => "High".into(),
-            Priority::Urgent => "Urgent".into(),
-        },
-        due_date: note.due_date.clone().unwrap_or_default().into(),
+        workflow_status: note.workflow_status.label().into(),
+        priority: note.priority.label().into(),
+        due_date: note.due_date.map(|due| due.to_string()).unwrap_or_default().into(),
         estimated_time: note.estimated_time.unwrap_or(0) as i32,
-        completion_percentage: note.completion_percentage as f32,
-        updated_at: note.updated_at.clone().into(),
+        completion_percentage: note.completion_percentage,
+        checklist: ModelRc::new(VecModel::from(
+            note.checklist.iter().take(CARD_CHECKLIST_ROWS).map(|item| ChecklistEntry {
+                id: item.id.clone().into(),
+                text: item.text.clone().into(),
+                done: item.done,
+            }).collect::<Vec<_>>(),
+        )),
+        checklist_hidden: note.checklist.len().saturating_sub(CARD_CHECKLIST_ROWS) as i32,
+        checklist_done: note.checklist_progress().0 as i32,
+        checklist_total: note.checklist.len() as i32,
+        updated_at: timestamp::format_local(&note.updated_at).into(),
+        blocked_by: blocked_by.into(),
+        title_segments: ModelRc::default(),
+        snippet: ModelRc::default(),
     }
 }
 
-fn slint_note_to_app_note(note: &StickyNote) -> AppNote {
-    let mut new_note = AppNote {
-        id: note.id.to_string(),
-        title: note.title.to_string(),
-        content: note.content.to_string(),
-        color: SerializableColor::from(note.color),
-        text_color: if note.text_color.alpha() > 0 { Some(SerializableColor::from(note.text_color)) } else { None },
-        x: note.x.into(),
-        y: note.y.into(),
-        width: note.width.into(),
-        height: note.height.into(),
-        created_at: chrono::Utc::now().to_rfc3339(),
-        updated_at: note.updated_at.to_string(),
-        
-        // Extended fields
-        tags: HashSet::new(),
-        keywords: HashSet::new(),
-        workflow_status: AppWorkflowStatus::Idea,
-        sentiment: None,
-        priority: Priority::Medium,
-        due_date: None,
-        estimated_time: None,
-        completion_percentage: 0.0,
-        actual_time: None,
-    };
+/// Render note content as Markdown lines for the UI
+fn markdown_blocks(content: &str, line_chars: usize, max_lines: usize) -> ModelRc<MarkdownBlock> {
+    let lines = markdown::wrap(&markdown::parse(content), line_chars);
+    ModelRc::new(VecModel::from(
+        lines.iter().take(max_lines).map(|block| {
+            let (kind, level) = match block.kind {
+                BlockKind::Paragraph => ("paragraph", 0),
+                BlockKind::Heading(level) => ("heading", level as i32),
+                BlockKind::Bullet => ("bullet", 0),
+                BlockKind::Numbered(_) => ("numbered", 0),
+                BlockKind::Task(_) => ("task", 0),
+                BlockKind::Quote => ("quote", 0),
+                BlockKind::Code => ("code", 0),
+                BlockKind::Rule => ("rule", 0),
+            };
+            MarkdownBlock {
+                kind: kind.into(),
+                level,
+                depth: block.depth as i32,
+                marker: block.marker().into(),
+                continued: block.continued,
+                spans: ModelRc::new(VecModel::from(
+                    block.spans.iter().map(|span| MarkdownSpan {
+                        text: span.text.clone().into(),
+                        bold: span.bold,
+                        italic: span.italic,
+                        code: span.code,
+                        link: span.link.clone().unwrap_or_default().into(),
+                        note_link: span.note_link.clone().unwrap_or_default().into(),
+                    }).collect::<Vec<_>>(),
+                )),
+            }
+        }).collect::<Vec<_>>(),
+    ))
+}
+
+fn text_segments(text: &HighlightedText) -> ModelRc<TextSegment> {
+    let segments: Vec<TextSegment> = text
+        .segments()
+        .into_iter()
+        .map(|(text, highlighted)| TextSegment { text: text.into(), highlighted })
+        .collect();
+    ModelRc::new(VecModel::from(segments))
+}
+
+/// Smart sample notes shown the first time the app starts with an empty store
+fn sample_notes() -> Vec<AppNote> {
+    // Sample note 1: Project planning
+    let mut sample_note1 = AppNote::new(
+        "AI Project Launch".to_string(), 
+        "Need to develop an intelligent sticky notes system\n- Implement knowledge graph\n- Add workflow automation\n- Support drag functionality\nDeadline: End of this month".to_string()
+    );
+    sample_note1.add_tag("AI".to_string());
+    sample_note1.add_tag("project".to_string());
+    sample_note1.add_tag("important".to_string());
+    sample_note1.set_workflow_status(WorkflowStatus::InProgress);
+    sample_note1.priority = Priority::High;
+    sample_note1.set_position(100.0, 150.0);
+    sample_note1.estimated_time = Some(240); // 4 hours
+    
+    // Sample note 2: Technical research
+    let mut sample_note2 = AppNote::new(
+        "Rust & Slint Research".to_string(),
+        "Deep dive into Rust programming language and Slint UI framework\nThis tech stack is very promising!".to_string()
+    );
+    sample_note2.add_checklist_item("Master ownership concepts".to_string());
+    sample_note2.add_checklist_item("Understand UI component design".to_string());
+    sample_note2.add_checklist_item("Practice cross-platform development".to_string());
+    sample_note2.add_tag("Rust".to_string());
+    sample_note2.add_tag("learning".to_string());
+    sample_note2.add_tag("technology".to_string());
+    sample_note2.set_workflow_status(WorkflowStatus::Todo);
+    sample_note2.priority = Priority::Medium;
+    sample_note2.set_position(350.0, 200.0);
+    sample_note2.set_color(SerializableColor { red: 76, green: 175, blue: 80 });
+    sample_note2.estimated_time = Some(180); // 3 hours
+    
+    // Sample note 3: UI design
+    let mut sample_note3 = AppNote::new(
+        "UI/UX Design Thoughts".to_string(),
+        "User interface should be intuitive and easy to use\nUser experience is key!".to_string()
+    );
+    sample_note3.add_checklist_item("Support drag operations".to_string());
+    sample_note3.add_checklist_item("Display relationships".to_string());
+    sample_note3.add_checklist_item("Smart recommendation features".to_string());
+    sample_note3.add_tag("UI".to_string());
+    sample_note3.add_tag("design".to_string());
+    sample_note3.add_tag("UX".to_string());
+    sample_note3.set_workflow_status(WorkflowStatus::Review);
+    sample_note3.priority = Priority::Medium;
+    sample_note3.set_position(150.0, 350.0);
+    sample_note3.set_color(SerializableColor { red: 33, green: 150, blue: 243 });
+    sample_note3.estimated_time = Some(120); // 2 hours
     
-    // Auto analyze content
-    new_note.extract_keywords();
-    new_note.analyze_sentiment();
+    // Sample note 4: Completed task
+    let mut sample_note4 = AppNote::new(
+        "Data Structure Design".to_string(),
+        "Completed the design of note data structure\nNext step: Frontend integration, see [[UI/UX Design Thoughts]]".to_string()
+    );
+    for item in ["Added tag system", "Implemented relationships", "Support workflow status"] {
+        let item_id = sample_note4.add_checklist_item(item.to_string());
+        sample_note4.toggle_checklist_item(&item_id);
+    }
+    sample_note4.add_tag("completed".to_string());
+    sample_note4.add_tag("data-structure".to_string());
+    sample_note4.set_workflow_status(WorkflowStatus::Done);
+    sample_note4.priority = Priority::Low;
+    sample_note4.set_position(400.0, 100.0);
+    sample_note4.set_color(SerializableColor { red: 156, green: 39, blue: 176 });
+    sample_note4.actual_time = Some(150); // Actually took 2.5 hours
     
-    new_note
+    vec![sample_note1, sample_note2, sample_note3, sample_note4]
 }
 
+
 #[tokio::main]
 async fn main() -> Result<(), slint::PlatformError> {
     let ui = MainWindow::new()?;
     
-    // Create sticky notes and relations data models
-    let notes_model = ModelRc::new(VecModel::<StickyNote>::default());
-    let relations_model = ModelRc::new(VecModel::<NoteRelation>::default());
+    // Create the notes, relations and trash data models
+    let models = UiModels::new();
+    models.attach(&ui);
     
-    ui.set_notes(notes_model.clone());
-    ui.set_relations(relations_model.clone());
+    // Load persisted notes, seeding the sample notes only on a truly empty store
+    let (storage, storage_warning) = open_note_store().await;
+    ui.set_storage_warning(storage_warning.unwrap_or_default().into());
+    let retention_days = trash_retention_days();
+    let loaded = match storage.load().await {
+        Ok(()) => {
+            // Drop notes that have outstayed their time in the trash before anything is shown
+            match storage.purge_trash(retention_days).await {
+                Ok(purged) if !purged.is_empty() => println!("🧹 Purged {} notes from the trash", purged.len()),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️ Failed to purge the trash: {}", e),
+            }
+            storage.list().await
+        }
+        Err(e) => Err(e),
+    };
+    let trashed_notes = match storage.list_trash().await {
+        Ok(notes) => notes,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load the trash: {}", e);
+            Vec::new()
+        }
+    };
+    let (mut loaded_notes, store_was_empty) = match loaded {
+        Ok(notes) => {
+            let is_empty = notes.is_empty() && trashed_notes.is_empty();
+            (notes, is_empty)
+        }
+        Err(e) => {
+            // Don't seed over a store we failed to read
+            eprintln!("⚠️ Failed to load notes: {}", e);
+            (Vec::new(), false)
+        }
+    };
+    if store_was_empty {
+        loaded_notes = sample_notes();
+    }
+    
+    // Knowledge graph manager, restored from storage so manual relations survive restarts
+    let knowledge_graph = match storage.load_graph().await {
+        Ok(graph) => graph,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load knowledge graph: {}", e);
+            KnowledgeGraph::new()
+        }
+    };
+    
+    let saved_searches = match storage.load_saved_searches().await {
+        Ok(saved_searches) => saved_searches,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load saved searches: {}", e);
+            Vec::new()
+        }
+    };
     
-    // Knowledge graph manager
-    let mut knowledge_graph = KnowledgeGraph::new();
-    let mut app_notes: Vec<AppNote> = Vec::new();
+    // Each store remembers its own view settings, such as the sort order
+    let settings = match storage.load_settings().await {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load settings: {}", e);
+            Default::default()
+        }
+    };
+    
+    let (storage_tx, storage_writer) = spawn_storage_writer(storage.clone());
+    if store_was_empty {
+        for note in &loaded_notes {
+            let _ = storage_tx.send(StorageOp::Save(Box::new(note.clone())));
+        }
+    }
+    
+    // All note logic lives in the controller; the callbacks below only forward to it and re-render
+    loaded_notes.extend(trashed_notes);
+    let mut app_controller = AppController::new(loaded_notes, knowledge_graph, storage_tx)
+        .with_saved_searches(saved_searches)
+        .with_settings(settings)
+        .with_trash_retention_days(retention_days)
+        .with_typo_tolerance(typo_tolerance());
+    if store_was_empty {
+        // Give the sample notes an initial set of relations
+        app_controller.auto_discover_relations();
+    }
+    let notes_count = app_controller.notes().len();
+    let relations_count = app_controller.graph().relations.len();
+    let controller = Rc::new(RefCell::new(app_controller));
+    models.sync(&ui, &controller.borrow());
     
     // Setup quick add note callback
     let ui_weak = ui.as_weak();
-    let notes_model_clone = notes_model.clone();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_quick_add_note(move |text| {
         let ui = ui_weak.unwrap();
-        let notes = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
-        let mut new_note = AppNote::new(
-            if text.len() > 30 { format!("{}...", &text[..27]) } else { text.to_string() },
-            text.to_string()
-        );
-        
-        // Set position to avoid overlap
-        new_note.set_position(
-            50.0 + (notes.row_count() as f32 * 20.0),
-            50.0 + (notes.row_count() as f32 * 20.0)
-        );
-        
-        // Auto-extract keywords and analyze sentiment
-        new_note.extract_keywords();
-        new_note.analyze_sentiment();
-        
-        notes.push(app_note_to_slint_note(&new_note));
+        controller_clone.borrow_mut().quick_add(text.as_str());
+        models_clone.sync(&ui, &controller_clone.borrow());
         println!("✨ Quick added note: {}", text);
-        
-        // Sync update filtered_notes display
-        let current_filter = ui.get_filter_status();
-        let current_search = ui.get_search_text();
-        
-        if !current_search.is_empty() {
-            // If there's a current search condition, re-execute search
-            ui.invoke_search_notes(current_search);
-        } else if current_filter != "All" {
-            // If there's a current filter condition, re-execute filter
-            ui.invoke_filter_notes_by_status(current_filter);
-        } else {
-            // If no filter/search conditions, display all notes
-            ui.set_filtered_notes(notes_model_clone.clone().into());
-        }
     });
-
+    
     // Setup add note callback
     let ui_weak = ui.as_weak();
     ui.on_add_note(move || {
         let ui = ui_weak.unwrap();
-        ui.set_show_editor(true);
-        ui.set_editor_title("".into());
-        ui.set_editor_content("".into());
-        ui.set_editor_color(slint::Color::from_rgb_u8(255, 235, 59)); // Yellow
-        ui.set_editing_note_id("".into());
+        let defaults = AppNote::new(String::new(), String::new());
+        open_editor(&ui, "", &NoteEdit::from_note(&defaults), &[]);
     });
     
     // Setup save note callback (enhanced version)
     let ui_weak = ui.as_weak();
-    let notes_model_clone = notes_model.clone();
-    ui.on_save_note(move |title, content, color, text_color, tags_text, workflow_status, priority, due_date, estimated_time| {
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_save_note(move |title, content, color, text_color, tags_text, workflow_status, priority, due_date, estimated_time, checklist_text| {
         let ui = ui_weak.unwrap();
         let editing_id = ui.get_editing_note_id();
-        
-        // Create new note
-        let mut note = AppNote::new(title.to_string(), content.to_string());
-        let serializable_color = SerializableColor {
-            red: color.red(),
-            green: color.green(),
-            blue: color.blue(),
+        let due_date = match NoteEdit::parse_due_date(due_date.as_str()) {
+            Ok(due_date) => due_date,
+            Err(error) => {
+                // Keep the editor open so the date can be fixed
+                ui.set_editor_due_date_error(error.into());
+                return;
+            }
         };
-        note.set_color(serializable_color);
-        
-        // Set text color (None if transparent/auto-contrast)
-        if text_color.alpha() > 0 {
-            note.text_color = Some(SerializableColor {
-                red: text_color.red(),
-                green: text_color.green(),
-                blue: text_color.blue(),
-            });
-        } else {
-            note.text_color = None; // Auto-contrast
-        }
         
-        // Parse and set tags
-        let tags: Vec<&str> = tags_text.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
-        for tag in tags {
-            note.add_tag(tag.to_string());
+        let mut controller = controller_clone.borrow_mut();
+        // Keep the editor open for a title that would break the links to the note
+        if let Err(error) = controller.check_title(editing_id.as_str(), title.as_str()) {
+            ui.set_editor_title_error(error.into());
+            return;
         }
         
-        // Set workflow status
-        let status = match workflow_status.as_str() {
-            "Todo" => AppWorkflowStatus::Todo,
-            "Progress" => AppWorkflowStatus::InProgress,
-            "Review" => AppWorkflowStatus::Review,
-            "Done" => AppWorkflowStatus::Done,
-            "Archived" => AppWorkflowStatus::Archived,
-            _ => AppWorkflowStatus::Idea,
+        // Unchanged items keep their ids, so undo and history can tell them apart
+        let previous_checklist = controller.note(editing_id.as_str()).map(|note| note.checklist.clone()).unwrap_or_default();
+        let edit = NoteEdit {
+            title: title.to_string(),
+            content: content.to_string(),
+            color: color.into(),
+            // Transparent means auto-contrast
+            text_color: (text_color.alpha() > 0).then(|| text_color.into()),
+            tags: NoteEdit::parse_tags(tags_text.as_str()),
+            workflow_status: WorkflowStatus::from_label(workflow_status.as_str()),
+            priority: Priority::from_label(priority.as_str()),
+            due_date,
+            estimated_time: (estimated_time > 0).then_some(estimated_time as u32),
+            checklist: NoteEdit::parse_checklist(checklist_text.as_str(), &previous_checklist),
         };
-        note.set_workflow_status(status);
         
-        // Set priority
-        note.priority = match priority.as_str() {
-            "Low" => Priority::Low,
-            "High" => Priority::High,
-            "Urgent" => Priority::Urgent,
-            _ => Priority::Medium,
-        };
-        
-        // Set due date and estimated time
-        if !due_date.is_empty() {
-            note.due_date = Some(due_date.to_string());
-        }
-        if estimated_time > 0 {
-            note.estimated_time = Some(estimated_time as u32);
-        }
-        
-        // If editing existing note, find appropriate position
-        if !editing_id.is_empty() {
-            // Maintain original position when editing existing note
-            let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-            for i in 0..vec_model.row_count() {
-                if let Some(existing_note) = vec_model.row_data(i) {
-                    if existing_note.id == editing_id {
-                        note.set_position(existing_note.x.into(), existing_note.y.into());
-                        note.set_size(existing_note.width.into(), existing_note.height.into());
-                        break;
-                    }
-                }
-            }
+        if controller.note(editing_id.as_str()).is_some() {
+            // Only the changed fields are applied, so identity, place and history are kept
+            controller.edit_note(editing_id.as_str(), &edit);
         } else {
+            let mut note = AppNote::new(edit.title.clone(), edit.content.clone());
+            note.apply_edit(&edit);
+            
             // Set random position for new note to avoid overlap
             // Use note ID to generate pseudo-random position
             let mut hasher = DefaultHasher::new();
@@ -237,366 +1030,440 @@ async fn main() -> Result<(), slint::PlatformError> {
             let random_x = ((hash_value % 500) as f32) + 50.0;
             let random_y = (((hash_value >> 16) % 300) as f32) + 100.0;
             note.set_position(random_x, random_y);
+            controller.create_note(note);
         }
-        
-        let slint_note = app_note_to_slint_note(&note);
-        
-        // Update model
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        if editing_id.is_empty() {
-            // Add new note
-            vec_model.push(slint_note);
-        } else {
-            // Update existing note
-            for i in 0..vec_model.row_count() {
-                if let Some(existing_note) = vec_model.row_data(i) {
-                    if existing_note.id == editing_id {
-                        let mut updated_note = slint_note;
-                        updated_note.id = editing_id.clone();
-                        vec_model.set_row_data(i, updated_note);
-                        break;
-                    }
-                }
-            }
-        }
+        models_clone.sync(&ui, &controller);
         
         // Close editor
         ui.set_show_editor(false);
-        ui.set_editor_title("".into());
-        ui.set_editor_content("".into());
         ui.set_editing_note_id("".into());
-        
-        // Sync update filtered_notes display
-        let current_filter = ui.get_filter_status();
-        let current_search = ui.get_search_text();
-        
-        if !current_search.is_empty() {
-            // If there's a current search condition, re-execute search
-            ui.invoke_search_notes(current_search);
-        } else if current_filter != "All" {
-            // If there's a current filter condition, re-execute filter
-            ui.invoke_filter_notes_by_status(current_filter);
-        } else {
-            // If no filter/search conditions, display all notes
-            ui.set_filtered_notes(notes_model_clone.clone().into());
-        }
     });
     
     // Setup edit note callback
     let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
     ui.on_edit_note(move |note| {
         let ui = ui_weak.unwrap();
-        ui.set_show_editor(true);
-        ui.set_editor_title(note.title.clone());
-        ui.set_editor_content(note.content.clone());
-        ui.set_editor_color(note.color);
-        ui.set_editing_note_id(note.id.clone());
+        edit_existing_note(&ui, &controller_clone.borrow(), note.id.as_str());
     });
     
-    // Setup delete note callback
-    let notes_model_clone = notes_model.clone();
+    // Backlinks in the editor open the linking note
     let ui_weak = ui.as_weak();
-    ui.on_delete_note(move |note_id| {
+    let controller_clone = controller.clone();
+    ui.on_open_note(move |note_id| {
         let ui = ui_weak.unwrap();
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
-        // Find and remove note
-        let mut index_to_remove = None;
-        for i in 0..vec_model.row_count() {
-            if let Some(note) = vec_model.row_data(i) {
-                if note.id == note_id {
-                    index_to_remove = Some(i);
-                    break;
-                }
+        edit_existing_note(&ui, &controller_clone.borrow(), note_id.as_str());
+    });
+    
+    // A [[link]] on a card opens the note it points to
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    ui.on_open_note_link(move |note_id, title| {
+        let ui = ui_weak.unwrap();
+        let controller = controller_clone.borrow();
+        match controller.linked_note(note_id.as_str(), title.as_str()) {
+            Some(target) => {
+                edit_existing_note(&ui, &controller, &target.id);
             }
+            None => println!("🔗 No note titled \"{}\"", title),
         }
-        
-        if let Some(index) = index_to_remove {
-            vec_model.remove(index);
-            println!("🗑️ Deleted note: {}", note_id);
-            
-            // Sync update filtered_notes display
-            let current_filter = ui.get_filter_status();
-            let current_search = ui.get_search_text();
-            
-            if !current_search.is_empty() {
-                // If there's a current search condition, re-execute search
-                ui.invoke_search_notes(current_search);
-            } else if current_filter != "All" {
-                // If there's a current filter condition, re-execute filter
-                ui.invoke_filter_notes_by_status(current_filter);
-            } else {
-                // If no filter/search conditions, display all remaining notes
-                ui.set_filtered_notes(notes_model_clone.clone().into());
-            }
+    });
+    
+    // Setup delete note callback: notes go to the trash and can be restored from there
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_delete_note(move |note_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.trash_note(note_id.as_str()) {
+            models_clone.sync(&ui, &controller);
+            println!("🗑️ Moved note to trash: {}", note_id);
+        }
+    });
+    
+    // Setup restore note callback
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_restore_note(move |note_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.restore_note(note_id.as_str()) {
+            models_clone.sync(&ui, &controller);
+            println!("♻️ Restored note: {}", note_id);
+        }
+    });
+    
+    // Setup delete forever callback
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_delete_note_forever(move |note_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.delete_forever(note_id.as_str()) {
+            models_clone.sync(&ui, &controller);
+            println!("🔥 Permanently deleted note: {}", note_id);
+        }
+    });
+    
+    // Setup empty trash callback
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_empty_trash(move || {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        let deleted = controller.empty_trash();
+        models_clone.sync(&ui, &controller);
+        println!("🔥 Emptied the trash ({} notes)", deleted);
+    });
+    
+    // Setup undo/redo callbacks (Ctrl+Z / Ctrl+Shift+Z)
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_undo(move || {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if let Some(kind) = controller.undo() {
+            models_clone.sync(&ui, &controller);
+            println!("↶ Undid {}", kind.label());
+        }
+    });
+    
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_redo(move || {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if let Some(kind) = controller.redo() {
+            models_clone.sync(&ui, &controller);
+            println!("↷ Redid {}", kind.label());
         }
     });
     
     // New feature: drag position update callback
-    let notes_model_clone = notes_model.clone();
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_position_changed(move |note_id, x, y| {
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
-        // Update note position
-        for i in 0..vec_model.row_count() {
-            if let Some(mut note) = vec_model.row_data(i) {
-                if note.id == note_id {
-                    note.x = x;
-                    note.y = y;
-                    vec_model.set_row_data(i, note);
-                    break;
-                }
-            }
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.move_note(note_id.as_str(), x, y) {
+            models_clone.sync(&ui, &controller);
         }
     });
     
     // 🔥 Fixed workflow status change callback - actually update data model
-    let notes_model_clone = notes_model.clone();
     let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_workflow_status_changed(move |note_id, status| {
         let ui = ui_weak.unwrap();
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
-        // Update note's workflow_status in the data model
-        for i in 0..vec_model.row_count() {
-            if let Some(mut note) = vec_model.row_data(i) {
-                if note.id == note_id {
-                    // Update status
-                    note.workflow_status = status.clone();
-                    // Update data in model
-                    vec_model.set_row_data(i, note);
-                    println!("✅ Note {} status UPDATED to: {}", note_id, status);
-                    
-                    // Update filtered_notes to maintain consistency
-                    // If currently showing all notes, update filtered_notes as well
-                    if ui.get_filter_status() == "All" {
-                        ui.set_filtered_notes(notes_model_clone.clone().into());
-                    } else {
-                        // If currently filtered, reapply the filter
-                        let current_filter = ui.get_filter_status();
-                        ui.invoke_filter_notes_by_status(current_filter);
-                    }
-                    break;
-                }
-            }
+        let mut controller = controller_clone.borrow_mut();
+        if controller.set_workflow_status(note_id.as_str(), WorkflowStatus::from_label(status.as_str())) {
+            models_clone.sync(&ui, &controller);
+            println!("✅ Note {} status UPDATED to: {}", note_id, status);
+        }
+    });
+    
+    // The editor's Markdown preview
+    ui.on_render_markdown(|content| markdown_blocks(content.as_str(), PREVIEW_LINE_CHARS, usize::MAX));
+    
+    // Checking an item on a card updates the note's completion
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_toggle_checklist_item(move |note_id, item_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.toggle_checklist_item(note_id.as_str(), item_id.as_str()) {
+            models_clone.sync(&ui, &controller);
         }
     });
     
     // New feature: knowledge graph toggle callback
     let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_toggle_knowledge_graph(move || {
         let ui = ui_weak.unwrap();
         println!("Knowledge graph display status: {}", ui.get_show_knowledge_graph());
+        models_clone.sync(&ui, &controller_clone.borrow());
+    });
+    
+    // The graph view is laid out for its size
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_graph_resized(move || {
+        let ui = ui_weak.unwrap();
+        models_clone.sync(&ui, &controller_clone.borrow());
+    });
+    
+    // A layout finished in the background is drawn like any other change
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_graph_laid_out(move || {
+        let ui = ui_weak.unwrap();
+        models_clone.sync(&ui, &controller_clone.borrow());
+    });
+    
+    // Clicking a note in the graph view focuses it, clicking it again clears the focus;
+    // a new relation starts from the focused note
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_focus_graph_note(move |note_id| {
+        let ui = ui_weak.unwrap();
+        let controller = controller_clone.borrow();
+        if ui.get_graph_focus_id() == note_id {
+            ui.set_graph_focus_id(Default::default());
+        } else {
+            ui.set_graph_focus_id(note_id.clone());
+            if let Some(index) = controller.notes().iter().position(|note| note.id == note_id.as_str()) {
+                ui.set_relation_from_index(index as i32);
+            }
+        }
+        models_clone.sync(&ui, &controller);
     });
     
     // New feature: auto discover relations callback
-    let notes_model_clone = notes_model.clone();
-    let relations_model_clone = relations_model.clone();
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_auto_discover_relations(move || {
+        let ui = ui_weak.unwrap();
         println!("Starting auto discovery of note relations...");
         
-        // Get all notes from UI model
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        let mut app_notes_temp: Vec<AppNote> = Vec::new();
-        
-        for i in 0..vec_model.row_count() {
-            if let Some(slint_note) = vec_model.row_data(i) {
-                let app_note = slint_note_to_app_note(&slint_note);
-                app_notes_temp.push(app_note);
-            }
-        }
-        
-        // Create temporary knowledge graph and discover relations
-        let mut temp_graph = KnowledgeGraph::new();
-        temp_graph.auto_discover_relations(&app_notes_temp);
-        
-        // Update relation model
-        let relations_vec_model = relations_model_clone.as_any().downcast_ref::<VecModel<NoteRelation>>().unwrap();
-        // Clear existing relations
-        while relations_vec_model.row_count() > 0 {
-            relations_vec_model.remove(0);
-        }
+        // Discover on top of the persisted graph so existing (and manual) relations are kept
+        let mut controller = controller_clone.borrow_mut();
+        let discovered = controller.auto_discover_relations();
+        models_clone.sync(&ui, &controller);
         
-        let relations_count = temp_graph.relations.len();
-        for relation in temp_graph.relations {
-            let slint_relation = NoteRelation {
-                from_note_id: relation.from_note_id.into(),
-                to_note_id: relation.to_note_id.into(),
-                relation_type: format!("{:?}", relation.relation_type).into(),
-                strength: relation.strength,
-            };
-            relations_vec_model.push(slint_relation);
-        }
-        
-        println!("Discovered {} relations", relations_count);
+        println!("Discovered {} new relations ({} total)", discovered, controller.graph().relations.len());
     });
     
-    // Search notes functionality will be implemented after filter functionality
-    
-    // Add some smart sample notes
-    let vec_model = notes_model.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-    
-    // Sample note 1: Project planning
-    let mut sample_note1 = AppNote::new(
-        "AI Project Launch".to_string(), 
-        "Need to develop an intelligent sticky notes system\n- Implement knowledge graph\n- Add workflow automation\n- Support drag functionality\nDeadline: End of this month".to_string()
-    );
-    sample_note1.add_tag("AI".to_string());
-    sample_note1.add_tag("project".to_string());
-    sample_note1.add_tag("important".to_string());
-    sample_note1.set_workflow_status(AppWorkflowStatus::InProgress);
-    sample_note1.priority = Priority::High;
-    sample_note1.set_position(100.0, 150.0);
-    sample_note1.estimated_time = Some(240); // 4 hours
-    let slint_note1 = app_note_to_slint_note(&sample_note1);
-    vec_model.push(slint_note1);
+    // Manual relations from the knowledge graph sidebar; the note pickers list the live notes in order
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_create_relation(move |from_index, to_index, relation_type, strength, description| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        let note_id = |index: i32| controller.notes().get(index as usize).map(|note| note.id.clone());
+        let (Some(from_id), Some(to_id)) = (note_id(from_index), note_id(to_index)) else {
+            return;
+        };
+        let result = controller.create_relation(
+            &from_id,
+            &to_id,
+            RelationType::from_label(relation_type.as_str()),
+            strength as f32 / 100.0,
+            Some(description.to_string()),
+        );
+        finish_relation_edit(&ui, result.map(|_| ()));
+        models_clone.sync(&ui, &controller);
+    });
     
-    // Sample note 2: Technical research
-    let mut sample_note2 = AppNote::new(
-        "Rust & Slint Research".to_string(),
-        "Deep dive into Rust programming language and Slint UI framework\n- Master ownership concepts\n- Understand UI component design\n- Practice cross-platform development\nThis tech stack is very promising!".to_string()
-    );
-    sample_note2.add_tag("Rust".to_string());
-    sample_note2.add_tag("learning".to_string());
-    sample_note2.add_tag("technology".to_string());
-    sample_note2.set_workflow_status(AppWorkflowStatus::Todo);
-    sample_note2.priority = Priority::Medium;
-    sample_note2.set_position(350.0, 200.0);
-    sample_note2.set_color(SerializableColor { red: 76, green: 175, blue: 80 });
-    sample_note2.estimated_time = Some(180); // 3 hours
-    let slint_note2 = app_note_to_slint_note(&sample_note2);
-    vec_model.push(slint_note2);
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_update_relation(move |relation_id, relation_type, strength, description| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        let result = controller.update_relation(
+            relation_id.as_str(),
+            RelationType::from_label(relation_type.as_str()),
+            strength as f32 / 100.0,
+            Some(description.to_string()),
+        );
+        finish_relation_edit(&ui, result);
+        models_clone.sync(&ui, &controller);
+    });
     
-    // Sample note 3: UI design
-    let mut sample_note3 = AppNote::new(
-        "UI/UX Design Thoughts".to_string(),
-        "User interface should be intuitive and easy to use\n- Support drag operations\n- Display relationships\n- Smart recommendation features\nUser experience is key!".to_string()
-    );
-    sample_note3.add_tag("UI".to_string());
-    sample_note3.add_tag("design".to_string());
-    sample_note3.add_tag("UX".to_string());
-    sample_note3.set_workflow_status(AppWorkflowStatus::Review);
-    sample_note3.priority = Priority::Medium;
-    sample_note3.set_position(150.0, 350.0);
-    sample_note3.set_color(SerializableColor { red: 33, green: 150, blue: 243 });
-    sample_note3.estimated_time = Some(120); // 2 hours
-    let slint_note3 = app_note_to_slint_note(&sample_note3);
-    vec_model.push(slint_note3);
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_delete_relation(move |relation_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        match controller.delete_relation(relation_id.as_str()) {
+            Ok(()) if ui.get_relation_editing_id() == relation_id => finish_relation_edit(&ui, Ok(())),
+            Ok(()) => {}
+            Err(error) => ui.set_relation_error(error.to_string().into()),
+        }
+        models_clone.sync(&ui, &controller);
+    });
     
-    // Sample note 4: Completed task
-    let mut sample_note4 = AppNote::new(
-        "Data Structure Design".to_string(),
-        "Completed the design of note data structure\n✓ Added tag system\n✓ Implemented relationships\n✓ Support workflow status\nNext step: Frontend integration".to_string()
-    );
-    sample_note4.add_tag("completed".to_string());
-    sample_note4.add_tag("data-structure".to_string());
-    sample_note4.set_workflow_status(AppWorkflowStatus::Done);
-    sample_note4.priority = Priority::Low;
-    sample_note4.set_position(400.0, 100.0);
-    sample_note4.set_color(SerializableColor { red: 156, green: 39, blue: 176 });
-    sample_note4.completion_percentage = 100.0;
-    sample_note4.actual_time = Some(150); // Actually took 2.5 hours
-    let slint_note4 = app_note_to_slint_note(&sample_note4);
-    vec_model.push(slint_note4);
-    
-    // Save all sample notes to app_notes vector
-    app_notes.push(sample_note1);
-    app_notes.push(sample_note2);
-    app_notes.push(sample_note3);
-    app_notes.push(sample_note4);
-    
-    // Auto discover initial relations
-    knowledge_graph.auto_discover_relations(&app_notes);
-    
-    // Add discovered relations to UI model
-    let relations_vec_model = relations_model.as_any().downcast_ref::<VecModel<NoteRelation>>().unwrap();
-    for relation in &knowledge_graph.relations {
-        let slint_relation = NoteRelation {
-            from_note_id: relation.from_note_id.clone().into(),
-            to_note_id: relation.to_note_id.clone().into(),
-            relation_type: format!("{:?}", relation.relation_type).into(),
-            strength: relation.strength,
-        };
-        relations_vec_model.push(slint_relation);
-    }
+    // Sort order picker; the choice is remembered by the store
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_sort_notes(move |sort_order| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        controller.set_sort_order(SortOrder::from_label(sort_order.as_str()));
+        models_clone.sync(&ui, &controller);
+        println!("↕️ Sorting notes by {}", controller.sort_order().label());
+    });
     
-    // Implement filtering functionality
-    let notes_model_clone = notes_model.clone();
+    // Filter panel: multi-select toggles, date ranges and removable chips
     let ui_weak = ui.as_weak();
-    ui.on_filter_notes_by_status(move |status| {
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_toggle_filter(move |kind, value| {
         let ui = ui_weak.unwrap();
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
-        println!("Filtering notes by status: {}", status);
-        
-        if status == "All" {
-            // Show all notes
-            ui.set_filtered_notes(notes_model_clone.clone().into());
-            println!("Showing all {} notes", vec_model.row_count());
-        } else {
-            // Filter notes matching the status
-            let mut filtered_notes = Vec::new();
-            let mut count = 0;
-            
-            for i in 0..vec_model.row_count() {
-                if let Some(note) = vec_model.row_data(i) {
-                    if note.workflow_status == status {
-                        filtered_notes.push(note);
-                        count += 1;
-                    }
+        let mut controller = controller_clone.borrow_mut();
+        let filter = controller.filter_mut();
+        match kind.as_str() {
+            "status" => {
+                filter.toggle_status(WorkflowStatus::from_label(value.as_str()));
+            }
+            "priority" => {
+                filter.toggle_priority(Priority::from_label(value.as_str()));
+            }
+            "tag" => {
+                filter.toggle_tag(value.as_str());
+            }
+            "color" => {
+                if let Some(color) = SerializableColor::from_hex(value.as_str()) {
+                    filter.toggle_color(color);
                 }
             }
-            
-            let filtered_model = std::rc::Rc::new(VecModel::from(filtered_notes));
-            ui.set_filtered_notes(filtered_model.into());
-            println!("Found {} notes with status: {}", count, status);
+            _ => return,
         }
+        models_clone.sync(&ui, &controller);
+        println!("Showing {} notes", models_clone.filtered_notes.model.row_count());
+    });
+    
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_apply_filter_dates(move || {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        let inputs = [
+            (DateField::Created, ui.get_filter_created_from(), ui.get_filter_created_to()),
+            (DateField::Updated, ui.get_filter_updated_from(), ui.get_filter_updated_to()),
+            (DateField::Due, ui.get_filter_due_from(), ui.get_filter_due_to()),
+        ];
+        // Apply every valid range; invalid ones keep their text so they can be corrected
+        let mut errors = Vec::new();
+        for (field, from, to) in inputs {
+            match DateRange::parse(from.as_str(), to.as_str()) {
+                Ok(range) => controller.filter_mut().set_date_range(field, range),
+                Err(e) => errors.push(format!("{}: {}", field.label(), e)),
+            }
+        }
+        if errors.is_empty() {
+            show_filter_dates(&ui, controller.filter());
+        }
+        ui.set_filter_error(errors.join("; ").into());
+        models_clone.sync(&ui, &controller);
+    });
+    
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_remove_filter_chip(move |index| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        let condition = controller.filter().conditions().get(index as usize).cloned();
+        if let Some(condition) = condition {
+            controller.filter_mut().remove(&condition);
+            show_filter_dates(&ui, controller.filter());
+            models_clone.sync(&ui, &controller);
+        }
+    });
+    
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_clear_filters(move || {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        controller.set_filter(NoteFilter::new());
+        show_filter_dates(&ui, controller.filter());
+        ui.set_filter_error("".into());
+        models_clone.sync(&ui, &controller);
     });
     
     // Implement search notes functionality
-    let notes_model_clone = notes_model.clone();
     let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
     ui.on_search_notes(move |search_text| {
         let ui = ui_weak.unwrap();
-        let vec_model = notes_model_clone.as_any().downcast_ref::<VecModel<StickyNote>>().unwrap();
-        
         println!("🔍 Searching notes: '{}'", search_text);
         
-        if search_text.is_empty() {
-            // If search is empty, show all notes
-            ui.set_filtered_notes(notes_model_clone.clone().into());
-            println!("✅ Showing all {} notes", vec_model.row_count());
-        } else {
-            // Search for matching notes
-            let mut filtered_notes = Vec::new();
-            let search_lower = search_text.to_lowercase();
-            
-            for i in 0..vec_model.row_count() {
-                if let Some(note) = vec_model.row_data(i) {
-                    let title_match = note.title.to_lowercase().contains(&search_lower);
-                    let content_match = note.content.to_lowercase().contains(&search_lower);
-                    let tags_match = note.tags.iter().any(|tag| tag.to_lowercase().contains(&search_lower));
-                    
-                    if title_match || content_match || tags_match {
-                        filtered_notes.push(note);
-                    }
-                }
-            }
-            
-            let count = filtered_notes.len();
-            let filtered_model = std::rc::Rc::new(VecModel::from(filtered_notes));
-            ui.set_filtered_notes(filtered_model.into());
-            println!("🎯 Found {} notes matching '{}'", count, search_text);
+        let mut controller = controller_clone.borrow_mut();
+        controller.set_search_text(search_text.as_str());
+        models_clone.sync(&ui, &controller);
+        println!("🎯 Found {} notes matching '{}'", models_clone.filtered_notes.model.row_count(), search_text);
+    });
+    
+    // Saved searches: picking one puts its query in the search box
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_apply_saved_search(move |saved_search_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.apply_saved_search(saved_search_id.as_str()) {
+            ui.set_search_text(controller.search_text().into());
+            models_clone.sync(&ui, &controller);
+            println!("⭐ Applied saved search: {}", controller.search_text());
         }
     });
     
-    // Set initial filter to show all notes
-    ui.set_filtered_notes(notes_model.clone().into());
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_save_search(move |name| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.save_search(name.as_str()).is_some() {
+            models_clone.sync(&ui, &controller);
+            ui.set_save_search_mode(false);
+            println!("⭐ Saved search '{}': {}", name.trim(), controller.search_text());
+        }
+    });
+    
+    let ui_weak = ui.as_weak();
+    let controller_clone = controller.clone();
+    let models_clone = models.clone();
+    ui.on_delete_saved_search(move |saved_search_id| {
+        let ui = ui_weak.unwrap();
+        let mut controller = controller_clone.borrow_mut();
+        if controller.delete_saved_search(saved_search_id.as_str()) {
+            models_clone.sync(&ui, &controller);
+            println!("🗑️ Deleted saved search: {}", saved_search_id);
+        }
+    });
     
     println!("🚀 Smart sticky notes system launched successfully!");
-    println!("📊 Loaded {} sample notes", app_notes.len());
-    println!("🔗 Discovered {} relations", knowledge_graph.relations.len());
+    if store_was_empty {
+        println!("📊 Seeded {} sample notes", notes_count);
+    } else {
+        println!("📊 Loaded {} notes from storage", notes_count);
+    }
+    println!("🔗 Loaded {} relations", relations_count);
     println!("✨ Supports drag & drop, knowledge graph, and workflow automation features");
     
-    ui.run()
-} 
\ No newline at end of file
+    let result = ui.run();
+    
+    // Write every queued change before exiting; the writer is stopped explicitly because
+    // the window's callbacks still hold storage senders through the controller
+    drop(ui);
+    drop(controller);
+    if let Err(e) = storage_writer.shutdown().await {
+        eprintln!("⚠️ Failed to save notes on shutdown: {}", e);
+    }
+    
+    result
+}
@@ -0,0 +1,332 @@
+/// A run of text with one style
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    /// Target of a link
+    pub link: Option<String>,
+    /// Title of the note a `[[wiki link]]` points to
+    pub note_link: Option<String>,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// What a line of Markdown is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Paragraph,
+    /// Level 1 to 6
+    Heading(u8),
+    Bullet,
+    /// The number the item was written with
+    Numbered(u32),
+    /// Whether the task is checked
+    Task(bool),
+    Quote,
+    /// A line inside a fenced code block, kept verbatim
+    Code,
+    Rule,
+}
+
+/// One rendered line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub kind: BlockKind,
+    /// List nesting, from the indentation (two spaces or a tab per level)
+    pub depth: usize,
+    /// Set on the lines [`wrap`] adds after the first one of a block
+    pub continued: bool,
+    pub spans: Vec<Span>,
+}
+
+impl Block {
+    fn new(kind: BlockKind, depth: usize, spans: Vec<Span>) -> Self {
+        Self {
+            kind,
+            depth,
+            continued: false,
+            spans,
+        }
+    }
+    
+    /// The marker drawn in front of the line, e.g. "•", "2." or "☑"
+    pub fn marker(&self) -> String {
+        if self.continued {
+            return String::new();
+        }
+        match self.kind {
+            BlockKind::Bullet => "•".to_string(),
+            BlockKind::Numbered(number) => format!("{}.", number),
+            BlockKind::Task(true) => "☑".to_string(),
+            BlockKind::Task(false) => "☐".to_string(),
+            _ => String::new(),
+        }
+    }
+    
+    /// The text of the line without any markup
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// Parse note content as Markdown. Notes are line based, so every line is its own block:
+/// a line break in the content stays a line break and paragraphs aren't reflowed. Blank
+/// lines are dropped.
+///
+/// Supported are ATX headings (`# Title`), bullet (`-`, `*`, `+`), numbered (`1.`) and
+/// task (`- [ ]`, `- [x]`) list items nested by indentation, quotes (`>`), fenced code
+/// blocks, rules (`---`) and inline `**bold**`, `*italic*`, `` `code` ``,
+/// `[links](https://…)` and `[[Note Title]]` links to other notes.
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            blocks.push(Block::new(BlockKind::Code, 0, vec![Span { code: true, ..Span::plain(line) }]));
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        blocks.push(parse_line(line));
+    }
+    blocks
+}
+
+fn parse_line(line: &str) -> Block {
+    let indent: usize = line
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 2 } else { 1 })
+        .sum();
+    let depth = indent / 2;
+    let line = line.trim();
+    
+    if is_rule(line) {
+        return Block::new(BlockKind::Rule, 0, Vec::new());
+    }
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) {
+        if let Some(title) = line[hashes..].strip_prefix(' ') {
+            return Block::new(BlockKind::Heading(hashes as u8), 0, parse_inline(title.trim()));
+        }
+    }
+    if let Some(quote) = line.strip_prefix('>') {
+        return Block::new(BlockKind::Quote, 0, parse_inline(quote.trim()));
+    }
+    if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|bullet| line.strip_prefix(bullet)) {
+        let item = item.trim_start();
+        for (marker, done) in [("[ ]", false), ("[x]", true), ("[X]", true)] {
+            if let Some(task) = item.strip_prefix(marker) {
+                return Block::new(BlockKind::Task(done), depth, parse_inline(task.trim()));
+            }
+        }
+        return Block::new(BlockKind::Bullet, depth, parse_inline(item));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let (Ok(number), Some(item)) = (line[..digits].parse(), line[digits..].strip_prefix(". ")) {
+            return Block::new(BlockKind::Numbered(number), depth, parse_inline(item.trim()));
+        }
+    }
+    Block::new(BlockKind::Paragraph, depth, parse_inline(line))
+}
+
+/// `---`, `***` or `___`, optionally spaced out
+fn is_rule(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && ['-', '*', '_'].iter().any(|mark| marks.chars().all(|c| c == *mark))
+}
+
+/// Parse inline markup into styled spans. Markers without a partner are kept as text.
+pub fn parse_inline(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut previous: Option<char> = None;
+    let mut rest = text;
+    
+    let flush = |current: &mut String, spans: &mut Vec<Span>, bold: bool, italic: bool| {
+        if !current.is_empty() {
+            spans.push(Span {
+                text: std::mem::take(current),
+                bold,
+                italic,
+                ..Span::default()
+            });
+        }
+    };
+    
+    'scan: while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        match c {
+            '\\' => {
+                if let Some(escaped) = after.chars().next().filter(char::is_ascii_punctuation) {
+                    current.push(escaped);
+                    previous = Some(escaped);
+                    rest = &after[escaped.len_utf8()..];
+                    continue;
+                }
+            }
+            '`' => {
+                if let Some(end) = after.find('`') {
+                    flush(&mut current, &mut spans, bold, italic);
+                    spans.push(Span {
+                        text: after[..end].to_string(),
+                        code: true,
+                        ..Span::default()
+                    });
+                    previous = Some('`');
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some((title, label, length)) = parse_wiki_link(rest) {
+                    flush(&mut current, &mut spans, bold, italic);
+                    spans.push(Span {
+                        text: label.to_string(),
+                        bold,
+                        italic,
+                        note_link: Some(title.to_string()),
+                        ..Span::default()
+                    });
+                    previous = Some(']');
+                    rest = &rest[length..];
+                    continue;
+                }
+                if let Some((label, target, length)) = parse_link(rest) {
+                    flush(&mut current, &mut spans, bold, italic);
+                    spans.push(Span {
+                        text: label.to_string(),
+                        bold,
+                        italic,
+                        link: Some(target.to_string()),
+                        ..Span::default()
+                    });
+                    previous = Some(')');
+                    rest = &rest[length..];
+                    continue;
+                }
+            }
+            '*' | '_' => {
+                for (marker, is_bold) in [("**", true), ("__", true), ("*", false), ("_", false)] {
+                    let Some(remainder) = rest.strip_prefix(marker) else {
+                        continue;
+                    };
+                    let open = if is_bold { bold } else { italic };
+                    // Opening markers need a partner and text right after them; `_` also
+                    // has to start a word, so snake_case stays as it is
+                    let opens = !open
+                        && remainder.contains(marker)
+                        && remainder.chars().next().is_some_and(|next| !next.is_whitespace())
+                        && (marker.starts_with('*') || !previous.is_some_and(char::is_alphanumeric));
+                    if open || opens {
+                        flush(&mut current, &mut spans, bold, italic);
+                        if is_bold {
+                            bold = !bold;
+                        } else {
+                            italic = !italic;
+                        }
+                        previous = Some(c);
+                        rest = remainder;
+                        continue 'scan;
+                    }
+                }
+            }
+            _ => {}
+        }
+        current.push(c);
+        previous = Some(c);
+        rest = after;
+    }
+    flush(&mut current, &mut spans, bold, italic);
+    spans
+}
+
+/// `[[Title]]` or `[[Title|label]]` at the start of the text: the title, the label
+/// shown for it (the title when there is none) and the length
+pub fn parse_wiki_link(text: &str) -> Option<(&str, &str, usize)> {
+    let inner = text.strip_prefix("[[")?;
+    let end = inner.find("]]")?;
+    let link = &inner[..end];
+    if link.contains(['[', ']', '\n']) {
+        return None;
+    }
+    let (title, label) = match link.split_once('|') {
+        Some((title, label)) => (title.trim(), label.trim()),
+        None => (link.trim(), ""),
+    };
+    if title.is_empty() {
+        return None;
+    }
+    Some((title, if label.is_empty() { title } else { label }, end + 4))
+}
+
+/// `[label](target)` at the start of the text: the label, the target and the length
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let label = &text[1..label_end];
+    if label.is_empty() || label.contains(['[', ']']) {
+        return None;
+    }
+    let target_start = label_end + 2;
+    let target_end = target_start + text[target_start..].find(')')?;
+    let target = text[target_start..target_end].trim();
+    if target.is_empty() || target.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, target, target_end + 1))
+}
+
+/// Break blocks into lines of at most `max_chars` characters, at spaces where possible.
+/// Lines after the first one of a block are marked `continued`. Headings, code and
+/// rules stay on one line.
+pub fn wrap(blocks: &[Block], max_chars: usize) -> Vec<Block> {
+    let mut lines = Vec::new();
+    for block in blocks {
+        if matches!(block.kind, BlockKind::Heading(_) | BlockKind::Code | BlockKind::Rule) {
+            lines.push(block.clone());
+            continue;
+        }
+        let width = max_chars.saturating_sub(2 * block.depth).max(8);
+        let mut line = Block { spans: Vec::new(), ..block.clone() };
+        let mut length = 0;
+        for span in &block.spans {
+            // Keep the space with the word before it, so spans join up again as written
+            for word in span.text.split_inclusive(' ') {
+                let word_length = word.trim_end().chars().count();
+                if length > 0 && length + word_length > width {
+                    let next = Block { spans: Vec::new(), continued: true, ..block.clone() };
+                    lines.push(std::mem::replace(&mut line, next));
+                    length = 0;
+                }
+                match line.spans.last_mut() {
+                    Some(last) if same_style(last, span) => last.text.push_str(word),
+                    _ => line.spans.push(Span { text: word.to_string(), ..span.clone() }),
+                }
+                length += word.chars().count();
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn same_style(a: &Span, b: &Span) -> bool {
+    a.bold == b.bold && a.italic == b.italic && a.code == b.code && a.link == b.link && a.note_link == b.note_link
+}
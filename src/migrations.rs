@@ -0,0 +1,293 @@
+use crate::timestamp;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// Schema version written by this build of the app
+pub const CURRENT_SCHEMA_VERSION: u32 = 10;
+
+/// Version assumed for documents written before the envelope had a `schema_version`
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// A single upgrade step from `from_version` to `from_version + 1`
+pub struct Migration {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&mut Value) -> Result<(), MigrationError>,
+}
+
+/// Registry of every migration, ordered by the version it upgrades from
+static MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 1,
+        description: "Add schema_version and fill in extended note fields",
+        migrate: migrate_v1_to_v2,
+    },
+    Migration {
+        from_version: 2,
+        description: "Store the knowledge graph next to the notes",
+        migrate: migrate_v2_to_v3,
+    },
+    Migration {
+        from_version: 3,
+        description: "Keep a revision history per note",
+        migrate: migrate_v3_to_v4,
+    },
+    Migration {
+        from_version: 4,
+        description: "Soft-delete notes into a trash",
+        migrate: migrate_v4_to_v5,
+    },
+    Migration {
+        from_version: 5,
+        description: "Store saved searches next to the notes",
+        migrate: migrate_v5_to_v6,
+    },
+    Migration {
+        from_version: 6,
+        description: "Remember view settings per workspace",
+        migrate: migrate_v6_to_v7,
+    },
+    Migration {
+        from_version: 7,
+        description: "Store timestamps as RFC 3339 and due dates as plain dates",
+        migrate: migrate_v7_to_v8,
+    },
+    Migration {
+        from_version: 8,
+        description: "Give notes a checklist",
+        migrate: migrate_v8_to_v9,
+    },
+    Migration {
+        from_version: 9,
+        description: "Record where each relation came from",
+        migrate: migrate_v9_to_v10,
+    },
+];
+
+/// Errors raised while upgrading a stored document
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The document was written by a newer version of the app
+    UnsupportedVersion(u32),
+    /// No migration is registered for this version
+    MissingMigration(u32),
+    /// The document doesn't have the expected shape
+    InvalidDocument(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::UnsupportedVersion(version) => write!(
+                f,
+                "notes file has schema version {} but this app only understands up to {}",
+                version, CURRENT_SCHEMA_VERSION
+            ),
+            MigrationError::MissingMigration(version) => {
+                write!(f, "no migration registered from schema version {}", version)
+            }
+            MigrationError::InvalidDocument(reason) => write!(f, "invalid notes document: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// All registered migrations
+pub fn migrations() -> &'static [Migration] {
+    MIGRATIONS
+}
+
+/// Read the schema version of a stored document
+pub fn schema_version(document: &Value) -> u32 {
+    document
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|version| version as u32)
+        .unwrap_or(LEGACY_SCHEMA_VERSION)
+}
+
+/// Upgrade a document step by step to [`CURRENT_SCHEMA_VERSION`].
+/// Returns the version the document had before migrating.
+pub fn migrate_document(document: &mut Value) -> Result<u32, MigrationError> {
+    let original_version = schema_version(document);
+    if original_version > CURRENT_SCHEMA_VERSION {
+        return Err(MigrationError::UnsupportedVersion(original_version));
+    }
+    
+    let mut version = original_version;
+    while version < CURRENT_SCHEMA_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|migration| migration.from_version == version)
+            .ok_or(MigrationError::MissingMigration(version))?;
+        (migration.migrate)(document)?;
+        version += 1;
+        set_schema_version(document, version)?;
+    }
+    
+    Ok(original_version)
+}
+
+fn set_schema_version(document: &mut Value, version: u32) -> Result<(), MigrationError> {
+    envelope_mut(document)?.insert("schema_version".to_string(), json!(version));
+    Ok(())
+}
+
+fn envelope_mut(document: &mut Value) -> Result<&mut Map<String, Value>, MigrationError> {
+    document
+        .as_object_mut()
+        .ok_or_else(|| MigrationError::InvalidDocument("top level is not an object".to_string()))
+}
+
+/// Every note object in the document
+fn notes_mut(document: &mut Value) -> Result<impl Iterator<Item = &mut Map<String, Value>>, MigrationError> {
+    let notes = envelope_mut(document)?
+        .entry("notes")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| MigrationError::InvalidDocument("`notes` is not an object".to_string()))?;
+    Ok(notes.values_mut().filter_map(Value::as_object_mut))
+}
+
+/// Insert `value` for `key` unless the note already has it
+fn default_field(note: &mut Map<String, Value>, key: &str, value: Value) {
+    note.entry(key.to_string()).or_insert(value);
+}
+
+/// v1 files predate the envelope version and may come from builds without the extended fields
+fn migrate_v1_to_v2(document: &mut Value) -> Result<(), MigrationError> {
+    for note in notes_mut(document)? {
+        default_field(note, "text_color", Value::Null);
+        default_field(note, "tags", json!([]));
+        default_field(note, "keywords", json!([]));
+        default_field(note, "workflow_status", json!("Idea"));
+        default_field(note, "sentiment", Value::Null);
+        default_field(note, "priority", json!("Medium"));
+        default_field(note, "due_date", Value::Null);
+        default_field(note, "estimated_time", Value::Null);
+        default_field(note, "completion_percentage", json!(0.0));
+        default_field(note, "actual_time", Value::Null);
+    }
+    Ok(())
+}
+
+/// v3 persists the knowledge graph; older files start with an empty one
+fn migrate_v2_to_v3(document: &mut Value) -> Result<(), MigrationError> {
+    envelope_mut(document)?
+        .entry("graph")
+        .or_insert_with(|| json!({ "relations": [], "auto_relation_threshold": 0.3 }));
+    Ok(())
+}
+
+/// v4 records revisions; history starts empty for notes saved before it
+fn migrate_v3_to_v4(document: &mut Value) -> Result<(), MigrationError> {
+    envelope_mut(document)?
+        .entry("revisions")
+        .or_insert_with(|| json!({}));
+    Ok(())
+}
+
+/// v5 soft-deletes notes; nothing stored before it is in the trash
+fn migrate_v4_to_v5(document: &mut Value) -> Result<(), MigrationError> {
+    for note in notes_mut(document)? {
+        default_field(note, "deleted_at", Value::Null);
+    }
+    Ok(())
+}
+
+/// v6 stores saved searches; there are none before it
+fn migrate_v5_to_v6(document: &mut Value) -> Result<(), MigrationError> {
+    envelope_mut(document)?
+        .entry("saved_searches")
+        .or_insert_with(|| json!([]));
+    Ok(())
+}
+
+/// v7 remembers the sort order; earlier stores used the default one
+fn migrate_v6_to_v7(document: &mut Value) -> Result<(), MigrationError> {
+    envelope_mut(document)?
+        .entry("settings")
+        .or_insert_with(|| json!({ "sort_order": "Relevance" }));
+    Ok(())
+}
+
+/// v8 stores timestamps as RFC 3339 in UTC instead of minute-precision `YYYY-MM-DD HH:MM`
+/// strings (which were written from UTC too), and due dates as `YYYY-MM-DD`. Due dates
+/// that aren't dates at all are dropped; a timestamp that can't be read is an error.
+fn migrate_v7_to_v8(document: &mut Value) -> Result<(), MigrationError> {
+    for note in notes_mut(document)? {
+        for key in ["created_at", "updated_at"] {
+            rewrite_timestamp(note, key, true)?;
+        }
+        rewrite_timestamp(note, "deleted_at", false)?;
+        if let Some(due) = note.get_mut("due_date") {
+            *due = due
+                .as_str()
+                .and_then(timestamp::parse_date)
+                .map_or(Value::Null, |date| json!(date.format(timestamp::DATE_FORMAT).to_string()));
+        }
+    }
+    
+    let envelope = envelope_mut(document)?;
+    if let Some(revisions) = envelope.get_mut("revisions").and_then(Value::as_object_mut) {
+        for revision in revisions.values_mut().filter_map(Value::as_array_mut).flatten() {
+            if let Some(revision) = revision.as_object_mut() {
+                rewrite_timestamp(revision, "timestamp", true)?;
+            }
+        }
+    }
+    if let Some(relations) = envelope
+        .get_mut("graph")
+        .and_then(|graph| graph.get_mut("relations"))
+        .and_then(Value::as_array_mut)
+    {
+        for relation in relations.iter_mut().filter_map(Value::as_object_mut) {
+            rewrite_timestamp(relation, "created_at", true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite a legacy timestamp field as RFC 3339. Missing or null values are only
+/// accepted when the field is optional.
+fn rewrite_timestamp(object: &mut Map<String, Value>, key: &str, required: bool) -> Result<(), MigrationError> {
+    let value = match object.get(key) {
+        Some(Value::String(value)) => value,
+        None | Some(Value::Null) if !required => return Ok(()),
+        other => {
+            return Err(MigrationError::InvalidDocument(format!("`{}` is not a timestamp: {:?}", key, other)));
+        }
+    };
+    let parsed = timestamp::parse_timestamp(value)
+        .ok_or_else(|| MigrationError::InvalidDocument(format!("`{}` is not a timestamp: `{}`", key, value)))?;
+    object.insert(key.to_string(), json!(timestamp::to_rfc3339(&parsed)));
+    Ok(())
+}
+
+/// v9 adds checklists; existing notes start without one and keep their completion
+fn migrate_v8_to_v9(document: &mut Value) -> Result<(), MigrationError> {
+    for note in notes_mut(document)? {
+        default_field(note, "checklist", json!([]));
+    }
+    Ok(())
+}
+
+/// v10 records each relation's origin so `[[links]]` can be told apart. Auto-discovery
+/// only ever added `RelatedTo` relations; everything else was added by hand.
+fn migrate_v9_to_v10(document: &mut Value) -> Result<(), MigrationError> {
+    if let Some(relations) = envelope_mut(document)?
+        .get_mut("graph")
+        .and_then(|graph| graph.get_mut("relations"))
+        .and_then(Value::as_array_mut)
+    {
+        for relation in relations.iter_mut().filter_map(Value::as_object_mut) {
+            let origin = match relation.get("relation_type").and_then(Value::as_str) {
+                Some("RelatedTo") => "Discovered",
+                _ => "Manual",
+            };
+            default_field(relation, "origin", json!(origin));
+        }
+    }
+    Ok(())
+}
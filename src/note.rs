@@ -1,16 +1,41 @@
-use chrono::Utc;
+use crate::graph_layout::{ForceLayout, GraphLayout};
+use crate::plan::TaskPlan;
+use crate::{links, timestamp};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 /// RGB color representation that can be serialized
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct SerializableColor {
     pub red: u8,
     pub green: u8,
     pub blue: u8,
 }
 
+impl SerializableColor {
+    /// `#rrggbb`
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+    
+    /// Parse `#rrggbb` (the `#` is optional)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+        Some(Self {
+            red: channel(0..2)?,
+            green: channel(2..4)?,
+            blue: channel(4..6)?,
+        })
+    }
+}
+
 impl From<slint::Color> for SerializableColor {
     fn from(color: slint::Color) -> Self {
         Self {
@@ -27,8 +52,8 @@ impl From<SerializableColor> for slint::Color {
     }
 }
 
-/// Note priority levels (for future use)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Note priority levels, ordered from least to most important
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Priority {
     Low,
     Medium,
@@ -36,6 +61,30 @@ pub enum Priority {
     Urgent,
 }
 
+impl Priority {
+    pub const ALL: [Priority; 4] = [Priority::Low, Priority::Medium, Priority::High, Priority::Urgent];
+    
+    /// Label shown in the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+            Priority::Urgent => "Urgent",
+        }
+    }
+    
+    /// Parse a UI label, falling back to `Medium`
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "Low" => Priority::Low,
+            "High" => Priority::High,
+            "Urgent" => Priority::Urgent,
+            _ => Priority::Medium,
+        }
+    }
+}
+
 /// Workflow status for task automation (for future use)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WorkflowStatus {
@@ -47,8 +96,43 @@ pub enum WorkflowStatus {
     Archived,      // Archived
 }
 
+impl WorkflowStatus {
+    pub const ALL: [WorkflowStatus; 6] = [
+        WorkflowStatus::Idea,
+        WorkflowStatus::Todo,
+        WorkflowStatus::InProgress,
+        WorkflowStatus::Review,
+        WorkflowStatus::Done,
+        WorkflowStatus::Archived,
+    ];
+    
+    /// Label shown in the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorkflowStatus::Idea => "Idea",
+            WorkflowStatus::Todo => "Todo",
+            WorkflowStatus::InProgress => "In Progress",
+            WorkflowStatus::Review => "Review",
+            WorkflowStatus::Done => "Done",
+            WorkflowStatus::Archived => "Archived",
+        }
+    }
+    
+    /// Parse a UI label (also accepts "Progress"), falling back to `Idea`
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "Todo" => WorkflowStatus::Todo,
+            "In Progress" | "Progress" => WorkflowStatus::InProgress,
+            "Review" => WorkflowStatus::Review,
+            "Done" => WorkflowStatus::Done,
+            "Archived" => WorkflowStatus::Archived,
+            _ => WorkflowStatus::Idea,
+        }
+    }
+}
+
 /// Relationship types between notes (for knowledge graph)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum RelationType {
     RelatedTo,     // Related
     DependsOn,     // Depends on
@@ -60,6 +144,69 @@ pub enum RelationType {
     Extends,       // Extends
 }
 
+impl RelationType {
+    pub const ALL: [RelationType; 8] = [
+        RelationType::RelatedTo,
+        RelationType::DependsOn,
+        RelationType::Blocks,
+        RelationType::PartOf,
+        RelationType::SubtaskOf,
+        RelationType::References,
+        RelationType::Conflicts,
+        RelationType::Extends,
+    ];
+    
+    /// Label shown in the UI, read as "<from> <label> <to>"
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelationType::RelatedTo => "Related to",
+            RelationType::DependsOn => "Depends on",
+            RelationType::Blocks => "Blocks",
+            RelationType::PartOf => "Part of",
+            RelationType::SubtaskOf => "Subtask of",
+            RelationType::References => "References",
+            RelationType::Conflicts => "Conflicts with",
+            RelationType::Extends => "Extends",
+        }
+    }
+    
+    /// Parse a UI label, falling back to `RelatedTo`
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|relation_type| relation_type.label() == label)
+            .unwrap_or(RelationType::RelatedTo)
+    }
+    
+    /// Whether the relation reads the same both ways, so A→B and B→A are the same relation
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, RelationType::RelatedTo | RelationType::Conflicts)
+    }
+    
+    /// For the types that order work, which of the two notes waits for the other:
+    /// `(dependent, prerequisite)`. A note waits for what it depends on, for what blocks
+    /// it and, as the parent, for its subtasks.
+    pub fn dependency<'a>(&self, from_id: &'a str, to_id: &'a str) -> Option<(&'a str, &'a str)> {
+        match self {
+            RelationType::DependsOn => Some((from_id, to_id)),
+            RelationType::Blocks | RelationType::SubtaskOf => Some((to_id, from_id)),
+            _ => None,
+        }
+    }
+}
+
+/// Where a relation came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RelationOrigin {
+    /// Added on purpose
+    #[default]
+    Manual,
+    /// Found by keyword overlap in [`KnowledgeGraph::auto_discover_relations`]
+    Discovered,
+    /// A `[[Title]]` link in the source note's content, kept in sync with it
+    Link,
+}
+
 /// A relationship between two notes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteRelation {
@@ -68,12 +215,41 @@ pub struct NoteRelation {
     pub to_note_id: String,
     pub relation_type: RelationType,
     pub strength: f32,  // Relation strength 0.0-1.0
-    pub created_at: String,
+    #[serde(with = "timestamp::serde_timestamp")]
+    pub created_at: DateTime<Utc>,
     pub description: Option<String>,
+    // Relation rows in SQLite skip the schema migrations, so older ones have no origin
+    #[serde(default)]
+    pub origin: RelationOrigin,
+}
+
+impl NoteRelation {
+    /// `(dependent, prerequisite)` when the relation orders work, see [`RelationType::dependency`]
+    pub fn dependency(&self) -> Option<(&str, &str)> {
+        self.relation_type.dependency(&self.from_note_id, &self.to_note_id)
+    }
+}
+
+/// One checkbox of a note's checklist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub text: String,
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(text: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            text,
+            done: false,
+        }
+    }
 }
 
 /// Internal note representation with enhanced features
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppNote {
     pub id: String,
     pub title: String,
@@ -84,8 +260,10 @@ pub struct AppNote {
     pub y: f32,
     pub width: f32,
     pub height: f32,
-    pub created_at: String,
-    pub updated_at: String,
+    #[serde(with = "timestamp::serde_timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamp::serde_timestamp")]
+    pub updated_at: DateTime<Utc>,
     
     // Extended fields
     pub tags: HashSet<String>,
@@ -93,16 +271,24 @@ pub struct AppNote {
     pub workflow_status: WorkflowStatus,
     pub sentiment: Option<f32>,
     pub priority: Priority,
-    pub due_date: Option<String>,
+    #[serde(default, with = "timestamp::serde_optional_date")]
+    pub due_date: Option<NaiveDate>,
     pub estimated_time: Option<u32>,
+    // Items in display order; while there are any, they determine completion_percentage
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
     pub completion_percentage: f32,
     pub actual_time: Option<u32>,
+    
+    // Set while the note sits in the trash
+    #[serde(default, with = "timestamp::serde_optional_timestamp")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl AppNote {
     /// Create a new sticky note with default values
     pub fn new(title: String, content: String) -> Self {
-        let now = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        let now = Utc::now();
         
         let mut note = Self {
             id: Uuid::new_v4().to_string(),
@@ -114,7 +300,7 @@ impl AppNote {
             y: 10.0,
             width: 220.0,
             height: 200.0,
-            created_at: now.clone(),
+            created_at: now,
             updated_at: now,
             
             // Extended fields
@@ -125,8 +311,10 @@ impl AppNote {
             priority: Priority::Medium,
             due_date: None,
             estimated_time: None,
+            checklist: Vec::new(),
             completion_percentage: 0.0,
             actual_time: None,
+            deleted_at: None,
         };
         
         // Auto analyze content
@@ -140,7 +328,7 @@ impl AppNote {
     pub fn update_content(&mut self, title: String, content: String) {
         self.title = title;
         self.content = content;
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
         
         // Re-analyze content
         self.extract_keywords();
@@ -151,38 +339,122 @@ impl AppNote {
     pub fn set_position(&mut self, x: f32, y: f32) {
         self.x = x;
         self.y = y;
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
     }
     
     /// Set the note's size
     pub fn set_size(&mut self, width: f32, height: f32) {
         self.width = width;
         self.height = height;
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
     }
     
     /// Set the note's color
     pub fn set_color(&mut self, color: SerializableColor) {
         self.color = color;
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
     }
     
     /// Add a tag
     pub fn add_tag(&mut self, tag: String) {
         self.tags.insert(tag.to_lowercase());
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
     }
     
     /// Remove a tag
     pub fn remove_tag(&mut self, tag: &str) {
         self.tags.remove(&tag.to_lowercase());
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
     }
     
     /// Set workflow status
     pub fn set_workflow_status(&mut self, status: WorkflowStatus) {
         self.workflow_status = status;
-        self.updated_at = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+        self.updated_at = Utc::now();
+    }
+    
+    /// Append an item to the checklist, returning its id
+    pub fn add_checklist_item(&mut self, text: String) -> String {
+        let item = ChecklistItem::new(text);
+        let id = item.id.clone();
+        self.checklist.push(item);
+        self.checklist_changed();
+        id
+    }
+    
+    /// Check or uncheck an item. Returns false if the note has no such item.
+    pub fn toggle_checklist_item(&mut self, item_id: &str) -> bool {
+        let Some(item) = self.checklist.iter_mut().find(|item| item.id == item_id) else {
+            return false;
+        };
+        item.done = !item.done;
+        self.checklist_changed();
+        true
+    }
+    
+    /// Remove an item. Returns false if the note has no such item.
+    pub fn remove_checklist_item(&mut self, item_id: &str) -> bool {
+        let count = self.checklist.len();
+        self.checklist.retain(|item| item.id != item_id);
+        if self.checklist.len() == count {
+            return false;
+        }
+        self.checklist_changed();
+        true
+    }
+    
+    /// Move an item to a new position (clamped to the end of the list).
+    /// Returns false if the note has no such item.
+    pub fn move_checklist_item(&mut self, item_id: &str, index: usize) -> bool {
+        let Some(position) = self.checklist.iter().position(|item| item.id == item_id) else {
+            return false;
+        };
+        let item = self.checklist.remove(position);
+        self.checklist.insert(index.min(self.checklist.len()), item);
+        self.checklist_changed();
+        true
+    }
+    
+    /// Replace the whole checklist
+    pub fn set_checklist(&mut self, checklist: Vec<ChecklistItem>) {
+        self.checklist = checklist;
+        self.checklist_changed();
+    }
+    
+    /// Checked and total number of checklist items
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (done, self.checklist.len())
+    }
+    
+    /// Derive the completion from the checked items. A note without a checklist keeps
+    /// the completion it was given.
+    fn checklist_changed(&mut self) {
+        let (done, total) = self.checklist_progress();
+        if total > 0 {
+            self.completion_percentage = done as f32 * 100.0 / total as f32;
+        }
+        self.updated_at = Utc::now();
+    }
+    
+    /// Move the note to the trash; it can be restored until the trash is purged
+    pub fn move_to_trash(&mut self) {
+        self.deleted_at = Some(Utc::now());
+    }
+    
+    /// Take the note back out of the trash
+    pub fn restore_from_trash(&mut self) {
+        self.deleted_at = None;
+    }
+    
+    /// Whether the note is in the trash
+    pub fn is_in_trash(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+    
+    /// Whole days since the note was moved to the trash
+    pub fn days_in_trash(&self) -> Option<i64> {
+        Some((Utc::now() - self.deleted_at?).num_days())
     }
     
     /// Extract keywords from content (simplified implementation)
@@ -253,6 +525,161 @@ impl AppNote {
     pub fn slint_color(&self) -> slint::Color {
         self.color.into()
     }
+    
+    /// Apply the values submitted by the note editor, touching only the fields that
+    /// differ so identity, position and creation time stay as they were.
+    /// Returns whether anything changed.
+    pub fn apply_edit(&mut self, edit: &NoteEdit) -> bool {
+        let mut changed = false;
+        
+        if self.title != edit.title || self.content != edit.content {
+            self.update_content(edit.title.clone(), edit.content.clone());
+            changed = true;
+        }
+        if self.color != edit.color {
+            self.color = edit.color;
+            changed = true;
+        }
+        if self.text_color != edit.text_color {
+            self.text_color = edit.text_color;
+            changed = true;
+        }
+        if self.tags != edit.tags {
+            self.tags = edit.tags.clone();
+            changed = true;
+        }
+        if self.workflow_status != edit.workflow_status {
+            self.workflow_status = edit.workflow_status.clone();
+            changed = true;
+        }
+        if self.priority != edit.priority {
+            self.priority = edit.priority.clone();
+            changed = true;
+        }
+        if self.due_date != edit.due_date {
+            self.due_date = edit.due_date;
+            changed = true;
+        }
+        if self.estimated_time != edit.estimated_time {
+            self.estimated_time = edit.estimated_time;
+            changed = true;
+        }
+        if self.checklist != edit.checklist {
+            self.set_checklist(edit.checklist.clone());
+            changed = true;
+        }
+        
+        if changed {
+            self.updated_at = Utc::now();
+        }
+        changed
+    }
+}
+
+/// The fields a user can change in the note editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteEdit {
+    pub title: String,
+    pub content: String,
+    pub color: SerializableColor,
+    pub text_color: Option<SerializableColor>,  // None means auto-contrast
+    pub tags: HashSet<String>,
+    pub workflow_status: WorkflowStatus,
+    pub priority: Priority,
+    pub due_date: Option<NaiveDate>,
+    pub estimated_time: Option<u32>,  // Minutes
+    pub checklist: Vec<ChecklistItem>,
+}
+
+impl NoteEdit {
+    /// The editor's starting values for an existing note
+    pub fn from_note(note: &AppNote) -> Self {
+        Self {
+            title: note.title.clone(),
+            content: note.content.clone(),
+            color: note.color,
+            text_color: note.text_color,
+            tags: note.tags.clone(),
+            workflow_status: note.workflow_status.clone(),
+            priority: note.priority.clone(),
+            due_date: note.due_date,
+            estimated_time: note.estimated_time,
+            checklist: note.checklist.clone(),
+        }
+    }
+    
+    /// Parse the editor's comma separated tag list (tags are stored lowercase)
+    pub fn parse_tags(text: &str) -> HashSet<String> {
+        text.split(',')
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+    
+    /// Check a new title from the editor; other notes link to it as `[[title]]`, so it
+    /// can't hold the characters that end a link
+    pub fn check_title(title: &str) -> Result<(), String> {
+        if crate::links::is_linkable_title(title) {
+            Ok(())
+        } else {
+            Err("Titles can't contain [, ], | or line breaks, as links to the note would break".to_string())
+        }
+    }
+    
+    /// Parse the editor's due date: empty for none, `YYYY-MM-DD`, `today`, or days
+    /// (`+3d`) or weeks (`+2w`) from today
+    pub fn parse_due_date(text: &str) -> Result<Option<NaiveDate>, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        crate::query::parse_date(text, Local::now().date_naive())
+            .map(Some)
+            .ok_or_else(|| format!("`{}` is not a date (use YYYY-MM-DD, today or +3d)", text))
+    }
+    
+    /// Parse the editor's checklist, one item per line: `[x] text` for a checked item and
+    /// `[ ] text` or just `text` for an open one (a leading `- ` is ignored, so Markdown
+    /// task lists can be pasted). Items whose text is unchanged keep their id.
+    pub fn parse_checklist(text: &str, previous: &[ChecklistItem]) -> Vec<ChecklistItem> {
+        let mut unused: Vec<&ChecklistItem> = previous.iter().collect();
+        text.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix("- ").unwrap_or(line).trim_start();
+                let (done, text) = if let Some(text) = line.strip_prefix("[x]").or_else(|| line.strip_prefix("[X]")) {
+                    (true, text)
+                } else {
+                    (false, line.strip_prefix("[ ]").or_else(|| line.strip_prefix("[]")).unwrap_or(line))
+                };
+                let text = text.trim();
+                if text.is_empty() {
+                    return None;
+                }
+                let id = match unused.iter().position(|item| item.text == text) {
+                    Some(index) => unused.remove(index).id.clone(),
+                    None => Uuid::new_v4().to_string(),
+                };
+                Some(ChecklistItem { id, text: text.to_string(), done })
+            })
+            .collect()
+    }
+    
+    /// The checklist as the editor shows it, one `[ ]`/`[x]` line per item
+    pub fn checklist_text(&self) -> String {
+        self.checklist
+            .iter()
+            .map(|item| format!("[{}] {}", if item.done { "x" } else { " " }, item.text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    
+    /// The tags as the editor shows them, sorted so the text is stable
+    pub fn tags_text(&self) -> String {
+        let mut tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+        tags.sort_unstable();
+        tags.join(", ")
+    }
 }
 
 impl Default for AppNote {
@@ -263,6 +690,46 @@ impl Default for AppNote {
 
 // Conversion methods moved to main.rs to access Slint-generated structures
 
+/// Why a relation can't be created, changed or deleted
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelationError {
+    /// A note can't be related to itself
+    SelfRelation,
+    /// The two notes already have a relation of this type
+    Duplicate(RelationType),
+    /// Strength has to be between 0 and 1
+    InvalidStrength(f32),
+    /// No live note has this ID
+    UnknownNote(String),
+    /// No relation has this ID
+    UnknownRelation(String),
+    /// The relation comes from a `[[link]]` and changes with the note's content
+    LinkRelation,
+    /// The relation would make notes wait for each other. Holds the loop's note IDs,
+    /// starting and ending with the same note.
+    DependencyCycle(Vec<String>),
+}
+
+impl fmt::Display for RelationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelationError::SelfRelation => write!(f, "a note can't be related to itself"),
+            RelationError::Duplicate(relation_type) => {
+                write!(f, "these notes already have a \"{}\" relation", relation_type.label())
+            }
+            RelationError::InvalidStrength(strength) => write!(f, "strength {} is not between 0 and 1", strength),
+            RelationError::UnknownNote(note_id) => write!(f, "no note with ID {}", note_id),
+            RelationError::UnknownRelation(relation_id) => write!(f, "no relation with ID {}", relation_id),
+            RelationError::LinkRelation => write!(f, "this relation is a [[link]] in the note; edit the note to change it"),
+            RelationError::DependencyCycle(cycle) => {
+                write!(f, "this would make {} notes wait for each other in a loop", cycle.len() - 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RelationError {}
+
 /// Knowledge graph manager for handling note relationships
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeGraph {
@@ -280,18 +747,197 @@ impl KnowledgeGraph {
     
     /// Add a relation between two notes
     pub fn add_relation(&mut self, from_id: String, to_id: String, relation_type: RelationType, strength: f32) {
+        self.push_relation(from_id, to_id, relation_type, strength, RelationOrigin::Manual);
+    }
+    
+    fn push_relation(&mut self, from_id: String, to_id: String, relation_type: RelationType, strength: f32, origin: RelationOrigin) {
         let relation = NoteRelation {
             id: Uuid::new_v4().to_string(),
             from_note_id: from_id,
             to_note_id: to_id,
             relation_type,
             strength,
-            created_at: Utc::now().format("%Y-%m-%d %H:%M").to_string(),
+            created_at: Utc::now(),
             description: None,
+            origin,
         };
         self.relations.push(relation);
     }
     
+    /// Add a relation chosen by hand, returning its ID. Rejects relating a note to itself,
+    /// a strength outside 0–1 and a second relation of the same type between the notes.
+    pub fn create_relation(
+        &mut self,
+        from_id: &str,
+        to_id: &str,
+        relation_type: RelationType,
+        strength: f32,
+        description: Option<String>,
+    ) -> Result<String, RelationError> {
+        self.validate_relation(None, from_id, to_id, relation_type, strength)?;
+        self.push_relation(from_id.to_string(), to_id.to_string(), relation_type, strength, RelationOrigin::Manual);
+        let relation = self.relations.last_mut().expect("relation was just added");
+        relation.description = clean_description(description);
+        Ok(relation.id.clone())
+    }
+    
+    /// Change the type, strength and description of a relation, validated like a new one.
+    /// An edited discovered relation becomes a manual one, so it is kept as edited.
+    pub fn update_relation(
+        &mut self,
+        relation_id: &str,
+        relation_type: RelationType,
+        strength: f32,
+        description: Option<String>,
+    ) -> Result<(), RelationError> {
+        let relation = self.editable_relation(relation_id)?;
+        let (from_id, to_id) = (relation.from_note_id.clone(), relation.to_note_id.clone());
+        self.validate_relation(Some(relation_id), &from_id, &to_id, relation_type, strength)?;
+        
+        let relation = self.relations.iter_mut().find(|r| r.id == relation_id).expect("relation was just found");
+        relation.relation_type = relation_type;
+        relation.strength = strength;
+        relation.description = clean_description(description);
+        relation.origin = RelationOrigin::Manual;
+        Ok(())
+    }
+    
+    /// Delete a relation, returning it
+    pub fn delete_relation(&mut self, relation_id: &str) -> Result<NoteRelation, RelationError> {
+        self.editable_relation(relation_id)?;
+        let index = self.relations.iter().position(|r| r.id == relation_id).expect("relation was just found");
+        Ok(self.relations.remove(index))
+    }
+    
+    /// A relation that can be changed by hand; link relations follow the note content
+    fn editable_relation(&self, relation_id: &str) -> Result<&NoteRelation, RelationError> {
+        let relation = self
+            .relations
+            .iter()
+            .find(|r| r.id == relation_id)
+            .ok_or_else(|| RelationError::UnknownRelation(relation_id.to_string()))?;
+        if relation.origin == RelationOrigin::Link {
+            return Err(RelationError::LinkRelation);
+        }
+        Ok(relation)
+    }
+    
+    /// Check a relation before it is added, or before `relation_id` is changed into it
+    fn validate_relation(
+        &self,
+        relation_id: Option<&str>,
+        from_id: &str,
+        to_id: &str,
+        relation_type: RelationType,
+        strength: f32,
+    ) -> Result<(), RelationError> {
+        if from_id == to_id {
+            return Err(RelationError::SelfRelation);
+        }
+        // Also rejects NaN
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(RelationError::InvalidStrength(strength));
+        }
+        let duplicate = self.relations.iter().any(|r| {
+            let same_direction = r.from_note_id == from_id && r.to_note_id == to_id;
+            let reversed = r.from_note_id == to_id && r.to_note_id == from_id;
+            Some(r.id.as_str()) != relation_id
+                && r.relation_type == relation_type
+                && (same_direction || (reversed && relation_type.is_symmetric()))
+        });
+        if duplicate {
+            return Err(RelationError::Duplicate(relation_type));
+        }
+        // The new dependency closes a loop if its prerequisite already waits for its dependent
+        if let Some((dependent, prerequisite)) = relation_type.dependency(from_id, to_id) {
+            if let Some(mut cycle) = self.dependency_path_skipping(prerequisite, dependent, relation_id) {
+                cycle.insert(0, dependent.to_string());
+                return Err(RelationError::DependencyCycle(cycle));
+            }
+        }
+        Ok(())
+    }
+    
+    /// IDs of the notes a note directly waits for
+    pub fn prerequisites(&self, note_id: &str) -> Vec<&str> {
+        let mut prerequisites: Vec<&str> = Vec::new();
+        for (dependent, prerequisite) in self.relations.iter().filter_map(NoteRelation::dependency) {
+            if dependent == note_id && !prerequisites.contains(&prerequisite) {
+                prerequisites.push(prerequisite);
+            }
+        }
+        prerequisites
+    }
+    
+    /// IDs of the notes that directly wait for a note
+    pub fn dependents(&self, note_id: &str) -> Vec<&str> {
+        let mut dependents: Vec<&str> = Vec::new();
+        for (dependent, prerequisite) in self.relations.iter().filter_map(NoteRelation::dependency) {
+            if prerequisite == note_id && !dependents.contains(&dependent) {
+                dependents.push(dependent);
+            }
+        }
+        dependents
+    }
+    
+    /// Order the open notes among `notes` by their dependencies and work out how long
+    /// they take, see [`TaskPlan`]
+    pub fn plan(&self, notes: &[AppNote]) -> TaskPlan {
+        TaskPlan::new(self, notes)
+    }
+    
+    /// Where to draw `notes` and the relations between them, see [`ForceLayout`]
+    pub fn layout(&self, notes: &[AppNote], settings: &ForceLayout) -> GraphLayout {
+        settings.layout(self, notes)
+    }
+    
+    /// A shortest chain of dependencies from `from_id` to `to_id`: the note IDs from
+    /// `from_id`, which waits for the next one, and so on up to `to_id`. `None` when
+    /// `from_id` doesn't wait for `to_id`, directly or indirectly.
+    pub fn dependency_path(&self, from_id: &str, to_id: &str) -> Option<Vec<String>> {
+        self.dependency_path_skipping(from_id, to_id, None)
+    }
+    
+    /// [`Self::dependency_path`] as if the relation `skipped` wasn't there
+    fn dependency_path_skipping(&self, from_id: &str, to_id: &str, skipped: Option<&str>) -> Option<Vec<String>> {
+        let edges: Vec<(&str, &str)> = self
+            .relations
+            .iter()
+            .filter(|r| Some(r.id.as_str()) != skipped)
+            .filter_map(NoteRelation::dependency)
+            .collect();
+        
+        // Breadth-first, remembering where each note was reached from
+        let mut reached_from: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from_id]);
+        while let Some(note_id) = queue.pop_front() {
+            if note_id == to_id {
+                let mut path = vec![to_id.to_string()];
+                let mut current = to_id;
+                while current != from_id {
+                    current = reached_from[current];
+                    path.push(current.to_string());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &(_, prerequisite) in edges.iter().filter(|(dependent, _)| *dependent == note_id) {
+                if prerequisite != from_id && !reached_from.contains_key(prerequisite) {
+                    reached_from.insert(prerequisite, note_id);
+                    queue.push_back(prerequisite);
+                }
+            }
+        }
+        None
+    }
+    
+    /// Remove every relation that involves the given note, returning how many were removed
+    pub fn remove_note_relations(&mut self, note_id: &str) -> usize {
+        let before = self.relations.len();
+        self.relations.retain(|r| r.from_note_id != note_id && r.to_note_id != note_id);
+        before - self.relations.len()
+    }
+    
     /// Find related notes for a given note
     pub fn find_related_notes(&self, note_id: &str) -> Vec<&NoteRelation> {
         self.relations.iter()
@@ -313,17 +959,71 @@ impl KnowledgeGraph {
                     );
                     
                     if !exists {
-                        self.add_relation(
+                        self.push_relation(
                             notes[i].id.clone(),
                             notes[j].id.clone(),
                             RelationType::RelatedTo,
-                            similarity
+                            similarity,
+                            RelationOrigin::Discovered
                         );
                     }
                 }
             }
         }
     }
+    
+    /// Make the `[[Title]]` links in the notes' content the graph's link relations: a
+    /// `References` relation for every link that resolves to another of the notes, and
+    /// none for links that don't (any more). Links that keep pointing at the same note
+    /// keep their relation. Returns whether anything changed.
+    pub fn sync_links(&mut self, notes: &[AppNote]) -> bool {
+        let mut wanted: Vec<(&str, &str)> = Vec::new();
+        for note in notes {
+            for title in links::linked_titles(&note.content) {
+                if let Some(target) = links::resolve(notes, &note.id, &title) {
+                    if !wanted.contains(&(note.id.as_str(), target.id.as_str())) {
+                        wanted.push((note.id.as_str(), target.id.as_str()));
+                    }
+                }
+            }
+        }
+        
+        let before = self.relations.len();
+        self.relations.retain(|r| {
+            r.origin != RelationOrigin::Link || wanted.contains(&(r.from_note_id.as_str(), r.to_note_id.as_str()))
+        });
+        let mut changed = self.relations.len() != before;
+        for (from_id, to_id) in wanted {
+            let exists = self.relations.iter().any(|r| {
+                r.origin == RelationOrigin::Link && r.from_note_id == from_id && r.to_note_id == to_id
+            });
+            if !exists {
+                self.push_relation(from_id.to_string(), to_id.to_string(), RelationType::References, 1.0, RelationOrigin::Link);
+                changed = true;
+            }
+        }
+        changed
+    }
+    
+    /// IDs of the notes whose content links to the given note
+    pub fn backlinks(&self, note_id: &str) -> Vec<&str> {
+        self.relations
+            .iter()
+            .filter(|r| r.origin == RelationOrigin::Link && r.to_note_id == note_id)
+            .map(|r| r.from_note_id.as_str())
+            .collect()
+    }
+}
+
+impl Default for KnowledgeGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A trimmed description, or none when it is blank
+fn clean_description(description: Option<String>) -> Option<String> {
+    description.map(|description| description.trim().to_string()).filter(|description| !description.is_empty())
 }
 
 /// Helper function to check if a word is a stop word
@@ -0,0 +1,119 @@
+use crate::note::{AppNote, KnowledgeGraph, WorkflowStatus};
+use crate::sort::SortOrder;
+use std::collections::HashMap;
+
+/// An open note's place in a [`TaskPlan`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedTask {
+    pub note_id: String,
+    /// Minutes from now the note can be started at the earliest: once everything it
+    /// waits for is finished
+    pub earliest_start: u32,
+    /// `earliest_start` plus the note's estimated time (none counts as zero)
+    pub earliest_finish: u32,
+    /// Whether it waits for none of the other open notes, so work on it can start now
+    pub ready: bool,
+}
+
+/// The order to work through the open notes in, from their dependency relations and
+/// estimated times
+///
+/// Open notes are the ones that are neither done nor archived. Timing assumes notes that
+/// don't wait for each other can be worked on side by side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskPlan {
+    /// Every note comes after the notes it waits for. Among the notes that could come
+    /// next, the most important goes first, then the one due soonest, then display order.
+    pub tasks: Vec<PlannedTask>,
+    /// IDs of the chain of waiting notes that takes longest, in the order they are
+    /// worked on. Any delay along it delays finishing everything.
+    pub critical_path: Vec<String>,
+    /// Minutes until every open note can be finished
+    pub earliest_finish: u32,
+}
+
+impl TaskPlan {
+    /// Plan the open notes among `notes` using the graph's dependencies
+    pub fn new(graph: &KnowledgeGraph, notes: &[AppNote]) -> Self {
+        let open: Vec<&AppNote> = notes.iter().filter(|note| is_open(note)).collect();
+        let index: HashMap<&str, usize> = open.iter().enumerate().map(|(i, note)| (note.id.as_str(), i)).collect();
+        
+        // Dependencies on notes that aren't open don't hold anything up
+        let prerequisites: Vec<Vec<usize>> = open
+            .iter()
+            .map(|note| graph.prerequisites(&note.id).into_iter().filter_map(|id| index.get(id).copied()).collect())
+            .collect();
+        let mut dependents = vec![Vec::new(); open.len()];
+        for (task, task_prerequisites) in prerequisites.iter().enumerate() {
+            for &prerequisite in task_prerequisites {
+                dependents[prerequisite].push(task);
+            }
+        }
+        
+        // Kahn's algorithm, always taking the best of the notes that are ready
+        let next_first = |a: usize, b: usize| {
+            SortOrder::Priority
+                .compare(open[a], open[b])
+                .then_with(|| SortOrder::DueDate.compare(open[a], open[b]))
+                .then(a.cmp(&b))
+        };
+        let mut waiting_for: Vec<usize> = prerequisites.iter().map(Vec::len).collect();
+        let mut candidates: Vec<usize> = (0..open.len()).filter(|&task| waiting_for[task] == 0).collect();
+        let mut order = Vec::with_capacity(open.len());
+        while let Some(position) = (0..candidates.len()).min_by(|&a, &b| next_first(candidates[a], candidates[b])) {
+            let task = candidates.swap_remove(position);
+            order.push(task);
+            for &dependent in &dependents[task] {
+                waiting_for[dependent] -= 1;
+                if waiting_for[dependent] == 0 {
+                    candidates.push(dependent);
+                }
+            }
+        }
+        // Notes caught in a dependency loop (only possible in data from before loops were
+        // refused) never become ready; they go last
+        let mut placed = vec![false; open.len()];
+        order.iter().for_each(|&task| placed[task] = true);
+        order.extend((0..open.len()).filter(|&task| !placed[task]));
+        
+        let mut start = vec![0u32; open.len()];
+        let mut finish = vec![0u32; open.len()];
+        for &task in &order {
+            start[task] = prerequisites[task].iter().map(|&prerequisite| finish[prerequisite]).max().unwrap_or(0);
+            finish[task] = start[task].saturating_add(open[task].estimated_time.unwrap_or(0));
+        }
+        
+        // Walk back from the note that finishes last through what made each note start when it does
+        let mut critical_path = Vec::new();
+        let mut current = order.iter().copied().reduce(|last, task| if finish[task] > finish[last] { task } else { last });
+        while let Some(task) = current.filter(|task| !critical_path.contains(task)) {
+            critical_path.push(task);
+            current = prerequisites[task].iter().copied().find(|&prerequisite| finish[prerequisite] == start[task]);
+        }
+        critical_path.reverse();
+        
+        Self {
+            tasks: order
+                .iter()
+                .map(|&task| PlannedTask {
+                    note_id: open[task].id.clone(),
+                    earliest_start: start[task],
+                    earliest_finish: finish[task],
+                    ready: prerequisites[task].is_empty(),
+                })
+                .collect(),
+            critical_path: critical_path.into_iter().map(|task| open[task].id.clone()).collect(),
+            earliest_finish: finish.iter().copied().max().unwrap_or(0),
+        }
+    }
+    
+    /// Whether a note is on the critical path
+    pub fn is_critical(&self, note_id: &str) -> bool {
+        self.critical_path.iter().any(|id| id == note_id)
+    }
+}
+
+/// Notes still to be worked on
+fn is_open(note: &AppNote) -> bool {
+    !matches!(note.workflow_status, WorkflowStatus::Done | WorkflowStatus::Archived)
+}
@@ -0,0 +1,513 @@
+use crate::note::{AppNote, Priority, WorkflowStatus};
+use crate::search::{match_weight, tokenize, TypoTolerance};
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// How a field value is compared, e.g. the `>=` in `priority:>=high`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Split a leading operator off a field value (no operator means `Equal`)
+    fn split(value: &str) -> (Self, &str) {
+        for (operator, comparison) in [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+            ("=", Comparison::Equal),
+        ] {
+            if let Some(rest) = value.strip_prefix(operator) {
+                return (comparison, rest);
+            }
+        }
+        (Comparison::Equal, value)
+    }
+    
+    /// Whether a value that compares to the query value as `ordering` matches
+    pub fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+/// A parsed search query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    /// A word in the title, content, tags or keywords (also matches longer words it starts
+    /// and misspellings within the typo tolerance)
+    Term(String),
+    /// Consecutive words in the title or content
+    Phrase(Vec<String>),
+    Tag(String),
+    Status(WorkflowStatus),
+    Priority(Comparison, Priority),
+    Due(Comparison, NaiveDate),
+    Not(Box<QueryExpr>),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+}
+
+impl QueryExpr {
+    fn matches(&self, note: &AppNote, has_term: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            QueryExpr::Term(term) => has_term(term),
+            QueryExpr::Phrase(words) => {
+                contains_phrase(&tokenize(&note.title), words) || contains_phrase(&tokenize(&note.content), words)
+            }
+            QueryExpr::Tag(tag) => note.tags.contains(tag),
+            QueryExpr::Status(status) => &note.workflow_status == status,
+            QueryExpr::Priority(comparison, priority) => comparison.holds(note.priority.cmp(priority)),
+            QueryExpr::Due(comparison, date) => note.due_date.is_some_and(|due| comparison.holds(due.cmp(date))),
+            QueryExpr::Not(expr) => !expr.matches(note, has_term),
+            QueryExpr::And(exprs) => exprs.iter().all(|expr| expr.matches(note, has_term)),
+            QueryExpr::Or(exprs) => exprs.iter().any(|expr| expr.matches(note, has_term)),
+        }
+    }
+    
+    fn collect_terms<'a>(&'a self, terms: &mut Vec<&'a str>, include_excluded: bool) {
+        match self {
+            QueryExpr::Term(term) => terms.push(term),
+            QueryExpr::Phrase(words) => terms.extend(words.iter().map(String::as_str)),
+            QueryExpr::Not(expr) if include_excluded => expr.collect_terms(terms, include_excluded),
+            QueryExpr::And(exprs) | QueryExpr::Or(exprs) => {
+                for expr in exprs {
+                    expr.collect_terms(terms, include_excluded);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn contains_phrase(words: &[String], phrase: &[String]) -> bool {
+    words.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// A syntax error in a search query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    /// Character offset in the query where the problem is
+    pub position: usize,
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A search box query
+///
+/// Words must all match, `"quoted phrases"` match consecutive words, `-` excludes
+/// and `OR` (upper case) matches either side, with parentheses for grouping.
+/// Fields filter on note metadata: `tag:rust`, `status:todo`, `priority:>=high`
+/// and `due:<2026-11-01` (`<`, `<=`, `>`, `>=` and `=` compare). Due dates can also be
+/// relative to today: `due:today`, `due:<=+7d` or `due:>-2w`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    expr: Option<QueryExpr>,
+}
+
+impl Query {
+    /// Parse a query, resolving relative due dates against the local date
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        Self::parse_relative_to(text, Local::now().date_naive())
+    }
+    
+    /// Parse a query, resolving relative due dates against `today`
+    pub fn parse_relative_to(text: &str, today: NaiveDate) -> Result<Self, QueryError> {
+        let tokens = lex(text)?;
+        let mut parser = Parser { tokens, next: 0, end: text.chars().count(), today };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            // The only thing parse_or stops at is a closing parenthesis
+            return Err(QueryError::new("unmatched `)`", token.position));
+        }
+        Ok(Self { expr })
+    }
+    
+    /// An empty query matches every note
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+    
+    pub fn expr(&self) -> Option<&QueryExpr> {
+        self.expr.as_ref()
+    }
+    
+    /// Whether the note matches the query, with the default typo tolerance
+    pub fn matches(&self, note: &AppNote) -> bool {
+        let words: Vec<String> = [note.title.as_str(), note.content.as_str()]
+            .into_iter()
+            .chain(note.tags.iter().map(String::as_str))
+            .chain(note.keywords.iter().map(String::as_str))
+            .flat_map(tokenize)
+            .collect();
+        self.matches_with(note, |term| {
+            words.iter().any(|word| match_weight(term, word, TypoTolerance::default()).is_some())
+        })
+    }
+    
+    /// Whether the note matches, asking `has_term` whether it contains a word (e.g. from
+    /// a search index) instead of scanning its text
+    pub fn matches_with(&self, note: &AppNote, has_term: impl Fn(&str) -> bool) -> bool {
+        match &self.expr {
+            Some(expr) => expr.matches(note, &has_term),
+            None => true,
+        }
+    }
+    
+    /// Every free-text word in the query, including excluded ones
+    pub fn terms(&self) -> Vec<&str> {
+        let mut terms = Vec::new();
+        if let Some(expr) = &self.expr {
+            expr.collect_terms(&mut terms, true);
+        }
+        terms
+    }
+    
+    /// The free-text words results are ranked by (excluded words don't count)
+    pub fn ranking_terms(&self) -> Vec<&str> {
+        let mut terms = Vec::new();
+        if let Some(expr) = &self.expr {
+            expr.collect_terms(&mut terms, false);
+        }
+        terms
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Phrase(String),
+    Field(String, String),
+    Not,
+    Or,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn lex(text: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i;
+        match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                tokens.push(Token { kind: TokenKind::Open, position });
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token { kind: TokenKind::Close, position });
+                i += 1;
+            }
+            '"' => {
+                let (phrase, next) = read_quoted(&chars, i)?;
+                tokens.push(Token { kind: TokenKind::Phrase(phrase), position });
+                i = next;
+            }
+            '-' => {
+                let excludes_something = matches!(chars.get(i + 1), Some(next) if !next.is_whitespace() && *next != ')');
+                if !excludes_something {
+                    return Err(QueryError::new("nothing to exclude after `-`", position));
+                }
+                tokens.push(Token { kind: TokenKind::Not, position });
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')' | '"' | ':') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                
+                if chars.get(i) == Some(&':') {
+                    if word.is_empty() {
+                        return Err(QueryError::new("missing field name before `:`", position));
+                    }
+                    i += 1;
+                    let value = if chars.get(i) == Some(&'"') {
+                        let (value, next) = read_quoted(&chars, i)?;
+                        i = next;
+                        value
+                    } else {
+                        let value_start = i;
+                        while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')' | '"') {
+                            i += 1;
+                        }
+                        chars[value_start..i].iter().collect()
+                    };
+                    tokens.push(Token { kind: TokenKind::Field(word.to_lowercase(), value), position });
+                } else if word == "OR" {
+                    tokens.push(Token { kind: TokenKind::Or, position });
+                } else {
+                    tokens.push(Token { kind: TokenKind::Word(word), position });
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Read a double-quoted string starting at `start`, returning it and the index after it
+fn read_quoted(chars: &[char], start: usize) -> Result<(String, usize), QueryError> {
+    let end = chars[start + 1..]
+        .iter()
+        .position(|c| *c == '"')
+        .map(|offset| start + 1 + offset)
+        .ok_or_else(|| QueryError::new("missing closing `\"`", start))?;
+    Ok((chars[start + 1..end].iter().collect(), end + 1))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    // Position reported for errors at the end of the query
+    end: usize,
+    // Relative due dates count from here
+    today: NaiveDate,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+    
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |token| token.position)
+    }
+    
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+    
+    /// alternatives := sequence ("OR" sequence)*
+    fn parse_or(&mut self) -> Result<Option<QueryExpr>, QueryError> {
+        let mut alternatives = Vec::new();
+        loop {
+            let position = self.position();
+            let sequence = self.parse_and()?;
+            let or_follows = matches!(self.peek(), Some(Token { kind: TokenKind::Or, .. }));
+            match sequence {
+                Some(expr) => alternatives.push(expr),
+                None if or_follows || !alternatives.is_empty() => {
+                    return Err(QueryError::new("`OR` needs a search term on both sides", position));
+                }
+                None => {}
+            }
+            if !or_follows {
+                break;
+            }
+            self.advance();
+        }
+        
+        Ok(match alternatives.len() {
+            0 => None,
+            1 => alternatives.pop(),
+            _ => Some(QueryExpr::Or(alternatives)),
+        })
+    }
+    
+    /// sequence := unary*
+    fn parse_and(&mut self) -> Result<Option<QueryExpr>, QueryError> {
+        let mut parts = Vec::new();
+        while let Some(token) = self.peek() {
+            if matches!(token.kind, TokenKind::Or | TokenKind::Close) {
+                break;
+            }
+            if let Some(expr) = self.parse_unary()? {
+                parts.push(expr);
+            }
+        }
+        Ok(match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(QueryExpr::And(parts)),
+        })
+    }
+    
+    /// unary := "-" unary | atom
+    fn parse_unary(&mut self) -> Result<Option<QueryExpr>, QueryError> {
+        let Some(token) = self.advance() else {
+            return Ok(None);
+        };
+        match token.kind {
+            TokenKind::Not => match self.peek() {
+                Some(Token { kind: TokenKind::Or | TokenKind::Close | TokenKind::Not, .. }) | None => {
+                    Err(QueryError::new("nothing to exclude after `-`", token.position))
+                }
+                _ => Ok(self.parse_unary()?.map(|expr| QueryExpr::Not(Box::new(expr)))),
+            },
+            TokenKind::Open => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token { kind: TokenKind::Close, .. }) => {}
+                    _ => return Err(QueryError::new("missing `)`", token.position)),
+                }
+                match expr {
+                    Some(expr) => Ok(Some(expr)),
+                    None => Err(QueryError::new("empty parentheses", token.position)),
+                }
+            }
+            TokenKind::Close => Err(QueryError::new("unmatched `)`", token.position)),
+            TokenKind::Or => Err(QueryError::new("`OR` needs a search term on both sides", token.position)),
+            TokenKind::Word(word) => {
+                let mut terms: Vec<QueryExpr> = tokenize(&word).into_iter().map(QueryExpr::Term).collect();
+                Ok(match terms.len() {
+                    // Punctuation on its own doesn't search for anything
+                    0 => None,
+                    1 => terms.pop(),
+                    _ => Some(QueryExpr::And(terms)),
+                })
+            }
+            TokenKind::Phrase(phrase) => {
+                let words = tokenize(&phrase);
+                Ok((!words.is_empty()).then_some(QueryExpr::Phrase(words)))
+            }
+            TokenKind::Field(field, value) => parse_field(&field, &value, token.position, self.today).map(Some),
+        }
+    }
+}
+
+fn parse_field(field: &str, value: &str, position: usize, today: NaiveDate) -> Result<QueryExpr, QueryError> {
+    if value.is_empty() {
+        return Err(QueryError::new(format!("missing value after `{}:`", field), position));
+    }
+    match field {
+        "tag" => Ok(QueryExpr::Tag(value.to_lowercase())),
+        "status" => {
+            let status = match value.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+                "idea" => WorkflowStatus::Idea,
+                "todo" => WorkflowStatus::Todo,
+                "inprogress" | "progress" => WorkflowStatus::InProgress,
+                "review" => WorkflowStatus::Review,
+                "done" => WorkflowStatus::Done,
+                "archived" => WorkflowStatus::Archived,
+                _ => {
+                    return Err(QueryError::new(
+                        format!("unknown status `{}` (try idea, todo, in-progress, review, done or archived)", value),
+                        position,
+                    ))
+                }
+            };
+            Ok(QueryExpr::Status(status))
+        }
+        "priority" => {
+            let (comparison, level) = Comparison::split(value);
+            let priority = match level.to_lowercase().as_str() {
+                "low" => Priority::Low,
+                "medium" => Priority::Medium,
+                "high" => Priority::High,
+                "urgent" => Priority::Urgent,
+                _ => {
+                    return Err(QueryError::new(
+                        format!("unknown priority `{}` (try low, medium, high or urgent)", level),
+                        position,
+                    ))
+                }
+            };
+            Ok(QueryExpr::Priority(comparison, priority))
+        }
+        "due" => {
+            let (comparison, date) = Comparison::split(value);
+            let date = parse_date(date, today).ok_or_else(|| {
+                QueryError::new(format!("`{}` is not a date (use YYYY-MM-DD, today or +7d)", date), position)
+            })?;
+            Ok(QueryExpr::Due(comparison, date))
+        }
+        _ => Err(QueryError::new(
+            format!("unknown field `{}:` (try tag:, status:, priority: or due:)", field),
+            position,
+        )),
+    }
+}
+
+/// A `YYYY-MM-DD` date, `today`, or a number of days (`+3d`) or weeks (`-1w`) from today
+pub(crate) fn parse_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if value.eq_ignore_ascii_case("today") {
+        return Some(today);
+    }
+    if let Some(offset) = value.strip_prefix(['+', '-']) {
+        let mut chars = offset.chars();
+        let unit = chars.next_back()?;
+        // The sign in front is the only one; `+-3d` isn't a date
+        let count = chars.as_str();
+        if !count.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let count: i64 = count.parse().ok()?;
+        let days = match unit.to_ascii_lowercase() {
+            'd' => count,
+            'w' => count.checked_mul(7)?,
+            _ => return None,
+        };
+        let days = if value.starts_with('-') { -days } else { days };
+        return today.checked_add_signed(Duration::try_days(days)?);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// A named query the user can come back to, e.g. "My urgent this week" for
+/// `priority:urgent due:<=+7d`. The query is kept as text and parsed each time it is
+/// used, so relative dates follow the calendar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+}
+
+impl SavedSearch {
+    pub fn new(name: impl Into<String>, query: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.into(),
+            query: query.into(),
+        }
+    }
+    
+    pub fn parse(&self) -> Result<Query, QueryError> {
+        Query::parse(&self.query)
+    }
+}
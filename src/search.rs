@@ -0,0 +1,362 @@
+use crate::note::AppNote;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// BM25 term frequency saturation
+pub const DEFAULT_K1: f32 = 1.2;
+
+/// BM25 document length normalization
+pub const DEFAULT_B: f32 = 0.75;
+
+// Words in the title and tags say more about a note than words in its body
+const TITLE_WEIGHT: f32 = 2.0;
+const TAG_WEIGHT: f32 = 2.0;
+const CONTENT_WEIGHT: f32 = 1.0;
+const KEYWORD_WEIGHT: f32 = 0.5;
+
+// Each typo multiplies a word's score by this, so exact matches outrank fuzzy ones
+const TYPO_WEIGHT: f32 = 0.4;
+
+/// How many typos a search word may contain and still match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypoTolerance {
+    /// Only the word itself and longer words it starts
+    Off,
+    /// One typo in words of 4 or more characters, two in words of 8 or more
+    #[default]
+    Auto,
+    /// Up to this many typos in words longer than that
+    Max(usize),
+}
+
+impl TypoTolerance {
+    /// Typos allowed in the given search word
+    pub fn max_typos(&self, term: &str) -> usize {
+        let length = term.chars().count();
+        match *self {
+            TypoTolerance::Off => 0,
+            TypoTolerance::Auto if length >= 8 => 2,
+            TypoTolerance::Auto if length >= 4 => 1,
+            TypoTolerance::Auto => 0,
+            TypoTolerance::Max(typos) if length > typos => typos,
+            TypoTolerance::Max(_) => 0,
+        }
+    }
+}
+
+impl FromStr for TypoTolerance {
+    type Err = String;
+    
+    /// Parse "off", "auto" or a maximum number of typos
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Ok(TypoTolerance::Off),
+            "auto" => Ok(TypoTolerance::Auto),
+            number => number
+                .parse()
+                .map(TypoTolerance::Max)
+                .map_err(|_| format!("invalid typo tolerance `{}` (use off, auto or a number)", value)),
+        }
+    }
+}
+
+/// A note matching a search, with its relevance score (higher is better)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub note_id: String,
+    pub score: f32,
+}
+
+/// Split text into lowercase search terms
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Number of single-character insertions, deletions, substitutions or swaps of two
+/// neighbouring characters that turn `a` into `b`, or `None` if it is more than `max`
+pub fn typo_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    
+    // Optimal string alignment distance, keeping the last two rows
+    let mut before_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        if current.iter().all(|distance| *distance > max) {
+            return None;
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    
+    Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+/// How well an indexed word matches a search word: 1.0 for the word itself, less for a
+/// longer word it starts and less again for each typo. `None` if it doesn't match.
+pub fn match_weight(term: &str, word: &str, tolerance: TypoTolerance) -> Option<f32> {
+    if word == term {
+        return Some(1.0);
+    }
+    if word.starts_with(term) {
+        // A completed word is a weaker match than the word itself
+        return Some(term.chars().count() as f32 / word.chars().count() as f32);
+    }
+    let typos = typo_distance(term, word, tolerance.max_typos(term))?;
+    Some(TYPO_WEIGHT.powi(typos as i32))
+}
+
+#[derive(Debug, Clone, Default)]
+struct IndexedNote {
+    // Weighted term frequencies
+    terms: HashMap<String, f32>,
+    length: f32,
+}
+
+/// Inverted index over note titles, content, checklists, tags and keywords, ranked with BM25
+///
+/// The index is kept up to date one note at a time with [`SearchIndex::upsert`] and
+/// [`SearchIndex::remove`], so a save never rebuilds it. Every query term has to
+/// match for a note to be found. A term also matches words it is a prefix of, so
+/// results keep narrowing while the user types, and words within the
+/// [`TypoTolerance`], so "slnit" still finds "Slint" (ranked below exact matches).
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    notes: HashMap<String, IndexedNote>,
+    // Term -> note ID -> weighted term frequency, sorted so prefixes are a range scan
+    postings: BTreeMap<String, HashMap<String, f32>>,
+    total_length: f32,
+    k1: f32,
+    b: f32,
+    typo_tolerance: TypoTolerance,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            notes: HashMap::new(),
+            postings: BTreeMap::new(),
+            total_length: 0.0,
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+            typo_tolerance: TypoTolerance::default(),
+        }
+    }
+    
+    /// Build an index over the given notes
+    pub fn from_notes<'a>(notes: impl IntoIterator<Item = &'a AppNote>) -> Self {
+        let mut index = Self::new();
+        for note in notes {
+            index.upsert(note);
+        }
+        index
+    }
+    
+    /// Set the BM25 parameters
+    pub fn with_bm25_parameters(mut self, k1: f32, b: f32) -> Self {
+        self.k1 = k1;
+        self.b = b;
+        self
+    }
+    
+    /// Set how many typos a search word may contain
+    pub fn with_typo_tolerance(mut self, typo_tolerance: TypoTolerance) -> Self {
+        self.typo_tolerance = typo_tolerance;
+        self
+    }
+    
+    pub fn typo_tolerance(&self) -> TypoTolerance {
+        self.typo_tolerance
+    }
+    
+    /// Number of indexed notes
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+    
+    pub fn contains(&self, note_id: &str) -> bool {
+        self.notes.contains_key(note_id)
+    }
+    
+    /// Add a note to the index, replacing what was indexed for it before
+    pub fn upsert(&mut self, note: &AppNote) {
+        self.remove(&note.id);
+        
+        let mut indexed = IndexedNote::default();
+        let mut add = |text: &str, weight: f32| {
+            for term in tokenize(text) {
+                *indexed.terms.entry(term).or_insert(0.0) += weight;
+                indexed.length += weight;
+            }
+        };
+        add(&note.title, TITLE_WEIGHT);
+        add(&note.content, CONTENT_WEIGHT);
+        for item in &note.checklist {
+            add(&item.text, CONTENT_WEIGHT);
+        }
+        for tag in &note.tags {
+            add(tag, TAG_WEIGHT);
+        }
+        for keyword in &note.keywords {
+            add(keyword, KEYWORD_WEIGHT);
+        }
+        
+        for (term, frequency) in &indexed.terms {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(note.id.clone(), *frequency);
+        }
+        self.total_length += indexed.length;
+        self.notes.insert(note.id.clone(), indexed);
+    }
+    
+    /// Drop a note from the index. Returns false if it wasn't indexed.
+    pub fn remove(&mut self, note_id: &str) -> bool {
+        let Some(indexed) = self.notes.remove(note_id) else {
+            return false;
+        };
+        for term in indexed.terms.keys() {
+            if let Some(postings) = self.postings.get_mut(term) {
+                postings.remove(note_id);
+                if postings.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_length -= indexed.length;
+        if self.notes.is_empty() {
+            // Don't let float rounding accumulate across rebuilds
+            self.total_length = 0.0;
+        }
+        true
+    }
+    
+    /// Notes matching every term of the query, best match first
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms = tokenize(query);
+        if terms.is_empty() || self.notes.is_empty() {
+            return Vec::new();
+        }
+        
+        let mut scores: Option<HashMap<&str, f32>> = None;
+        for term in &terms {
+            let term_scores = self.term_scores(term);
+            scores = Some(match scores {
+                None => term_scores,
+                Some(mut scores) => {
+                    scores.retain(|note_id, _| term_scores.contains_key(note_id));
+                    for (note_id, score) in scores.iter_mut() {
+                        *score += term_scores[note_id];
+                    }
+                    scores
+                }
+            });
+        }
+        
+        let mut hits: Vec<SearchHit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(note_id, score)| SearchHit { note_id: note_id.to_string(), score })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.note_id.cmp(&b.note_id)));
+        hits
+    }
+    
+    /// IDs of the notes containing a word that matches the term
+    pub fn notes_matching(&self, term: &str) -> HashSet<&str> {
+        self.matching_words(&term.to_lowercase())
+            .into_iter()
+            .flat_map(|(postings, _)| postings.keys().map(String::as_str))
+            .collect()
+    }
+    
+    /// Relevance of every note matching at least one of the terms, for ranking results
+    /// that were selected some other way
+    pub fn scores<S: AsRef<str>>(&self, terms: &[S]) -> HashMap<&str, f32> {
+        let mut scores: HashMap<&str, f32> = HashMap::new();
+        if self.notes.is_empty() {
+            return scores;
+        }
+        for term in terms {
+            for (note_id, score) in self.term_scores(&term.as_ref().to_lowercase()) {
+                *scores.entry(note_id).or_insert(0.0) += score;
+            }
+        }
+        scores
+    }
+    
+    // Private helper methods
+    
+    /// The postings of every indexed word matching the term, with the weight of the match
+    fn matching_words(&self, term: &str) -> Vec<(&HashMap<String, f32>, f32)> {
+        let mut words: Vec<(&HashMap<String, f32>, f32)> = Vec::new();
+        
+        // The word itself and the words it starts are a range of the sorted vocabulary
+        for (word, postings) in self.postings.range(term.to_string()..) {
+            match match_weight(term, word, TypoTolerance::Off) {
+                Some(weight) => words.push((postings, weight)),
+                None => break,
+            }
+        }
+        
+        // Misspellings can be anywhere, but most words are skipped on length alone
+        if self.typo_tolerance.max_typos(term) > 0 {
+            for (word, postings) in &self.postings {
+                if !word.starts_with(term) {
+                    if let Some(weight) = match_weight(term, word, self.typo_tolerance) {
+                        words.push((postings, weight));
+                    }
+                }
+            }
+        }
+        words
+    }
+    
+    /// BM25 score of one query term for every note it matches, each note scoring its
+    /// best matching word
+    fn term_scores(&self, term: &str) -> HashMap<&str, f32> {
+        let note_count = self.notes.len() as f32;
+        let average_length = (self.total_length / note_count).max(f32::EPSILON);
+        
+        let mut scores: HashMap<&str, f32> = HashMap::new();
+        for (postings, weight) in self.matching_words(term) {
+            let matching = postings.len() as f32;
+            let idf = (1.0 + (note_count - matching + 0.5) / (matching + 0.5)).ln();
+            for (note_id, frequency) in postings {
+                let length = self.notes[note_id].length;
+                let normalization = self.k1 * (1.0 - self.b + self.b * length / average_length);
+                let score = weight * idf * frequency * (self.k1 + 1.0) / (frequency + normalization);
+                
+                let best = scores.entry(note_id.as_str()).or_insert(0.0);
+                *best = best.max(score);
+            }
+        }
+        scores
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::note::AppNote;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// How the visible notes are ordered
+///
+/// Every order except [`SortOrder::Relevance`] applies while searching too; notes that
+/// tie keep their relevance order, or their display order when there is no search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Best search matches first; display order when not searching
+    #[default]
+    Relevance,
+    /// Most recently updated first
+    Updated,
+    /// Newest first
+    Created,
+    /// Soonest due first; notes without a due date last
+    DueDate,
+    /// Most important first
+    Priority,
+    /// Alphabetical, ignoring case
+    Title,
+    /// Most complete first
+    Completion,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 7] = [
+        SortOrder::Relevance,
+        SortOrder::Updated,
+        SortOrder::Created,
+        SortOrder::DueDate,
+        SortOrder::Priority,
+        SortOrder::Title,
+        SortOrder::Completion,
+    ];
+    
+    /// Label shown in the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Relevance => "Relevance",
+            SortOrder::Updated => "Last updated",
+            SortOrder::Created => "Newest",
+            SortOrder::DueDate => "Due date",
+            SortOrder::Priority => "Priority",
+            SortOrder::Title => "Title",
+            SortOrder::Completion => "Completion",
+        }
+    }
+    
+    /// Parse a UI label, falling back to `Relevance`
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|order| order.label() == label)
+            .unwrap_or_default()
+    }
+    
+    /// Compare two notes for this order. `Relevance` leaves every note equal, so a stable
+    /// sort keeps the order they came in.
+    pub fn compare(&self, a: &AppNote, b: &AppNote) -> Ordering {
+        match self {
+            SortOrder::Relevance => Ordering::Equal,
+            SortOrder::Updated => b.updated_at.cmp(&a.updated_at),
+            SortOrder::Created => b.created_at.cmp(&a.created_at),
+            SortOrder::DueDate => match (&a.due_date, &b.due_date) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortOrder::Priority => b.priority.cmp(&a.priority),
+            SortOrder::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortOrder::Completion => b.completion_percentage.total_cmp(&a.completion_percentage),
+        }
+    }
+    
+    /// Stable-sort notes into this order
+    pub fn sort(&self, notes: &mut [&AppNote]) {
+        if *self != SortOrder::Relevance {
+            notes.sort_by(|a, b| self.compare(a, b));
+        }
+    }
+}
@@ -0,0 +1,552 @@
+use crate::history::{self, NoteRevision, DEFAULT_REVISION_LIMIT};
+use crate::migrations::{self, CURRENT_SCHEMA_VERSION};
+use crate::note::{AppNote, KnowledgeGraph, NoteRelation};
+use crate::query::SavedSearch;
+use crate::storage::{NoteQuery, NoteStorage, NoteStore, StorageResult, WorkspaceSettings};
+use crate::timestamp::{self, DATE_FORMAT};
+use async_trait::async_trait;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Schema version of databases created before `user_version` was tracked whose notes
+/// table has a `status` column; without it the notes are older still
+const SQLITE_BASE_SCHEMA_VERSION: u32 = 2;
+
+/// Indexed columns of the notes table that a database from an older build may lack. They
+/// are added empty, and the migration fills them in from each note's JSON.
+const NOTE_COLUMNS: &[(&str, &str)] = &[
+    ("status", "TEXT NOT NULL DEFAULT ''"),
+    ("priority", "TEXT NOT NULL DEFAULT ''"),
+    ("due_date", "TEXT"),
+    ("created_at", "TEXT NOT NULL DEFAULT ''"),
+    ("deleted_at", "TEXT"),
+];
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS notes (
+        id TEXT PRIMARY KEY,
+        status TEXT NOT NULL,
+        priority TEXT NOT NULL,
+        due_date TEXT,
+        created_at TEXT NOT NULL,
+        deleted_at TEXT,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_notes_status ON notes(status);
+    CREATE INDEX IF NOT EXISTS idx_notes_priority ON notes(priority);
+    CREATE INDEX IF NOT EXISTS idx_notes_due_date ON notes(due_date);
+    CREATE INDEX IF NOT EXISTS idx_notes_deleted_at ON notes(deleted_at);
+    
+    CREATE TABLE IF NOT EXISTS note_tags (
+        note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (note_id, tag)
+    );
+    CREATE INDEX IF NOT EXISTS idx_note_tags_tag ON note_tags(tag);
+    
+    CREATE TABLE IF NOT EXISTS relations (
+        id TEXT PRIMARY KEY,
+        from_note_id TEXT NOT NULL,
+        to_note_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_relations_from ON relations(from_note_id);
+    CREATE INDEX IF NOT EXISTS idx_relations_to ON relations(to_note_id);
+    
+    CREATE TABLE IF NOT EXISTS revisions (
+        id TEXT PRIMARY KEY,
+        note_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_revisions_note ON revisions(note_id);
+    
+    CREATE TABLE IF NOT EXISTS saved_searches (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        query TEXT NOT NULL
+    );
+    
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+/// SQLite-backed note storage
+///
+/// The full note is stored as JSON next to indexed columns for status, priority,
+/// due date and tags, so queries over large collections don't need to parse every note.
+#[derive(Debug, Clone)]
+pub struct SqliteNoteStorage {
+    db_path: Option<PathBuf>,
+    revision_limit: usize,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteNoteStorage {
+    /// Open (or create) the database in the same data directory as the JSON store
+    pub async fn new() -> StorageResult<Self> {
+        let data_dir = NoteStorage::get_data_directory();
+        tokio::fs::create_dir_all(&data_dir).await?;
+        Self::open(data_dir.join("notes.db")).await
+    }
+    
+    /// Open (or create) a database file
+    pub async fn open(path: impl AsRef<Path>) -> StorageResult<Self> {
+        let db_path = path.as_ref().to_path_buf();
+        let open_path = db_path.clone();
+        let conn = tokio::task::spawn_blocking(move || Connection::open(open_path)).await??;
+        Self::from_connection(conn, Some(db_path))
+    }
+    
+    /// Open a private in-memory database (mainly for tests)
+    pub fn open_in_memory() -> StorageResult<Self> {
+        Self::from_connection(Connection::open_in_memory()?, None)
+    }
+    
+    /// Set how many revisions are kept per note
+    pub fn with_revision_limit(mut self, revision_limit: usize) -> Self {
+        self.revision_limit = revision_limit;
+        self
+    }
+    
+    /// Path of the database file, if it lives on disk
+    pub fn db_path(&self) -> Option<&Path> {
+        self.db_path.as_deref()
+    }
+    
+    fn from_connection(mut conn: Connection, db_path: Option<PathBuf>) -> StorageResult<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")?;
+        // Tell the version before the schema below adds what older databases lack
+        let version = stored_schema_version(&conn)?;
+        add_missing_note_columns(&conn)?;
+        conn.execute_batch(SCHEMA)?;
+        migrate_database(&mut conn, version)?;
+        Ok(Self {
+            db_path,
+            revision_limit: DEFAULT_REVISION_LIMIT,
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+    
+    /// Run a blocking database operation off the async runtime
+    async fn with_conn<T, F>(&self, operation: F) -> StorageResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> StorageResult<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| "SQLite connection lock poisoned")?;
+            operation(&mut conn)
+        })
+        .await?
+    }
+}
+
+/// Names of the notes table's columns; none when there is no notes table yet
+fn note_columns(conn: &Connection) -> StorageResult<Vec<String>> {
+    let mut statement = conn.prepare("SELECT name FROM pragma_table_info('notes')")?;
+    let columns = statement.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+    Ok(columns)
+}
+
+/// The schema version of the stored notes. Databases from before `user_version` was
+/// tracked are told apart by their notes table, and one without a notes table is new.
+fn stored_schema_version(conn: &Connection) -> StorageResult<u32> {
+    let user_version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if user_version > 0 {
+        return Ok(user_version);
+    }
+    let columns = note_columns(conn)?;
+    Ok(if columns.is_empty() {
+        CURRENT_SCHEMA_VERSION
+    } else if columns.iter().any(|column| column == "status") {
+        SQLITE_BASE_SCHEMA_VERSION
+    } else {
+        migrations::LEGACY_SCHEMA_VERSION
+    })
+}
+
+/// Add the [`NOTE_COLUMNS`] an existing notes table doesn't have yet
+fn add_missing_note_columns(conn: &Connection) -> StorageResult<()> {
+    let columns = note_columns(conn)?;
+    if columns.is_empty() {
+        return Ok(());
+    }
+    for (name, definition) in NOTE_COLUMNS {
+        if !columns.iter().any(|column| column == name) {
+            conn.execute_batch(&format!("ALTER TABLE notes ADD COLUMN {} {}", name, definition))?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring the stored notes, relations and revisions from `version` up to
+/// [`CURRENT_SCHEMA_VERSION`] using the shared migration registry
+fn migrate_database(conn: &mut Connection, version: u32) -> StorageResult<()> {
+    if version < CURRENT_SCHEMA_VERSION {
+        // Run the rows through the same document migrations as the JSON store
+        let mut notes = Map::new();
+        let mut relations = Vec::new();
+        let mut revisions: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        {
+            let mut statement = conn.prepare("SELECT id, data FROM notes")?;
+            let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+            for row in rows {
+                let (id, data) = row?;
+                notes.insert(id, serde_json::from_str(&data)?);
+            }
+            let mut statement = conn.prepare("SELECT data FROM relations ORDER BY rowid")?;
+            for data in statement.query_map([], |row| row.get::<_, String>(0))? {
+                relations.push(serde_json::from_str::<Value>(&data?)?);
+            }
+            let mut statement = conn.prepare("SELECT note_id, data FROM revisions ORDER BY rowid")?;
+            let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+            for row in rows {
+                let (note_id, data) = row?;
+                revisions.entry(note_id).or_default().push(serde_json::from_str(&data)?);
+            }
+        }
+        let mut document = serde_json::json!({
+            "schema_version": version,
+            "notes": notes,
+            "graph": { "relations": relations },
+            "revisions": revisions,
+        });
+        migrations::migrate_document(&mut document)?;
+        
+        let tx = conn.transaction()?;
+        if let Some(notes) = document.get("notes").and_then(Value::as_object) {
+            for data in notes.values() {
+                let note: AppNote = serde_json::from_value(data.clone())?;
+                write_note(&tx, &note)?;
+            }
+        }
+        if let Some(relations) = document.pointer("/graph/relations").and_then(Value::as_array) {
+            for data in relations {
+                let relation: NoteRelation = serde_json::from_value(data.clone())?;
+                tx.execute(
+                    "UPDATE relations SET data = ?2 WHERE id = ?1",
+                    params![relation.id, serde_json::to_string(&relation)?],
+                )?;
+            }
+        }
+        if let Some(revisions) = document.get("revisions").and_then(Value::as_object) {
+            for data in revisions.values().filter_map(Value::as_array).flatten() {
+                let revision: NoteRevision = serde_json::from_value(data.clone())?;
+                tx.execute(
+                    "UPDATE revisions SET data = ?2 WHERE id = ?1",
+                    params![revision.id, serde_json::to_string(&revision)?],
+                )?;
+            }
+        }
+        tx.commit()?;
+    } else if version > CURRENT_SCHEMA_VERSION {
+        return Err(Box::new(migrations::MigrationError::UnsupportedVersion(version)));
+    }
+    
+    conn.pragma_update(None, "user_version", CURRENT_SCHEMA_VERSION)?;
+    Ok(())
+}
+
+/// Insert or replace a note row together with its tags
+fn write_note(conn: &Connection, note: &AppNote) -> StorageResult<()> {
+    let data = serde_json::to_string(note)?;
+    conn.execute(
+        "INSERT INTO notes (id, status, priority, due_date, created_at, deleted_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            status = excluded.status,
+            priority = excluded.priority,
+            due_date = excluded.due_date,
+            created_at = excluded.created_at,
+            deleted_at = excluded.deleted_at,
+            data = excluded.data",
+        params![
+            note.id,
+            format!("{:?}", note.workflow_status),
+            format!("{:?}", note.priority),
+            note.due_date.map(|due| due.format(DATE_FORMAT).to_string()),
+            timestamp::to_sortable(&note.created_at),
+            note.deleted_at.as_ref().map(timestamp::to_sortable),
+            data,
+        ],
+    )?;
+    conn.execute("DELETE FROM note_tags WHERE note_id = ?1", params![note.id])?;
+    let mut insert_tag = conn.prepare_cached("INSERT INTO note_tags (note_id, tag) VALUES (?1, ?2)")?;
+    for tag in &note.tags {
+        insert_tag.execute(params![note.id, tag])?;
+    }
+    Ok(())
+}
+
+/// Parse the JSON column of a notes row
+fn note_from_json(data: &str) -> StorageResult<AppNote> {
+    Ok(serde_json::from_str(data)?)
+}
+
+#[async_trait]
+impl NoteStore for SqliteNoteStorage {
+    async fn load(&self) -> StorageResult<()> {
+        // Tables are created when the connection is opened
+        self.with_conn(|conn| {
+            conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get::<_, i64>(0))?;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn get(&self, note_id: &str) -> StorageResult<Option<AppNote>> {
+        let note_id = note_id.to_string();
+        self.with_conn(move |conn| {
+            let data: Option<String> = conn
+                .query_row("SELECT data FROM notes WHERE id = ?1", params![note_id], |row| row.get(0))
+                .optional()?;
+            data.as_deref().map(note_from_json).transpose()
+        })
+        .await
+    }
+    
+    async fn upsert(&self, note: &AppNote) -> StorageResult<()> {
+        let note = note.clone();
+        let revision_limit = self.revision_limit;
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let previous: Option<String> = tx
+                .query_row("SELECT data FROM notes WHERE id = ?1", params![note.id], |row| row.get(0))
+                .optional()?;
+            let previous = previous.as_deref().map(note_from_json).transpose()?;
+            
+            let changed_fields = history::changed_fields(previous.as_ref(), &note);
+            if !changed_fields.is_empty() {
+                let revision = NoteRevision::from_note(&note, changed_fields);
+                tx.execute(
+                    "INSERT INTO revisions (id, note_id, data) VALUES (?1, ?2, ?3)",
+                    params![revision.id, revision.note_id, serde_json::to_string(&revision)?],
+                )?;
+                tx.execute(
+                    "DELETE FROM revisions WHERE note_id = ?1 AND rowid NOT IN (
+                        SELECT rowid FROM revisions WHERE note_id = ?1 ORDER BY rowid DESC LIMIT ?2
+                    )",
+                    params![note.id, revision_limit as i64],
+                )?;
+            }
+            
+            write_note(&tx, &note)?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn delete(&self, note_id: &str) -> StorageResult<bool> {
+        let note_id = note_id.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let deleted = tx.execute("DELETE FROM notes WHERE id = ?1", params![note_id])?;
+            tx.execute(
+                "DELETE FROM relations WHERE from_note_id = ?1 OR to_note_id = ?1",
+                params![note_id],
+            )?;
+            tx.execute("DELETE FROM revisions WHERE note_id = ?1", params![note_id])?;
+            tx.commit()?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+    
+    async fn list(&self) -> StorageResult<Vec<AppNote>> {
+        self.query(&NoteQuery::default()).await
+    }
+    
+    async fn query(&self, query: &NoteQuery) -> StorageResult<Vec<AppNote>> {
+        let query = query.clone();
+        self.with_conn(move |conn| {
+            let mut sql = String::from("SELECT data FROM notes");
+            let mut conditions: Vec<&str> = vec!["deleted_at IS NULL"];
+            let mut values: Vec<String> = Vec::new();
+            
+            if let Some(status) = &query.status {
+                conditions.push("status = ?");
+                values.push(format!("{:?}", status));
+            }
+            if let Some(priority) = &query.priority {
+                conditions.push("priority = ?");
+                values.push(format!("{:?}", priority));
+            }
+            if let Some(tag) = &query.tag {
+                conditions.push("id IN (SELECT note_id FROM note_tags WHERE tag = ?)");
+                values.push(tag.to_lowercase());
+            }
+            if let Some(before) = &query.due_before {
+                conditions.push("due_date <= ?");
+                values.push(before.format(DATE_FORMAT).to_string());
+            }
+            if let Some(after) = &query.due_after {
+                conditions.push("due_date >= ?");
+                values.push(after.format(DATE_FORMAT).to_string());
+            }
+            
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+            sql.push_str(" ORDER BY created_at, id");
+            if let Some(limit) = query.limit {
+                sql.push_str(&format!(" LIMIT {}", limit));
+            }
+            
+            let mut statement = conn.prepare(&sql)?;
+            let rows = statement.query_map(params_from_iter(values.iter()), |row| row.get::<_, String>(0))?;
+            let mut notes = Vec::new();
+            for data in rows {
+                notes.push(note_from_json(&data?)?);
+            }
+            Ok(notes)
+        })
+        .await
+    }
+    
+    async fn list_trash(&self) -> StorageResult<Vec<AppNote>> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT data FROM notes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id",
+            )?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            let mut notes = Vec::new();
+            for data in rows {
+                notes.push(note_from_json(&data?)?);
+            }
+            Ok(notes)
+        })
+        .await
+    }
+    
+    async fn load_graph(&self) -> StorageResult<KnowledgeGraph> {
+        self.with_conn(|conn| {
+            let mut graph = KnowledgeGraph::new();
+            let threshold: Option<String> = conn
+                .query_row("SELECT value FROM meta WHERE key = 'auto_relation_threshold'", [], |row| row.get(0))
+                .optional()?;
+            if let Some(threshold) = threshold {
+                graph.auto_relation_threshold = threshold.parse()?;
+            }
+            
+            let mut statement = conn.prepare("SELECT data FROM relations ORDER BY rowid")?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            for data in rows {
+                let relation: NoteRelation = serde_json::from_str(&data?)?;
+                graph.relations.push(relation);
+            }
+            Ok(graph)
+        })
+        .await
+    }
+    
+    async fn save_graph(&self, graph: &KnowledgeGraph) -> StorageResult<()> {
+        let graph = graph.clone();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM relations", [])?;
+            {
+                let mut insert = tx.prepare(
+                    "INSERT INTO relations (id, from_note_id, to_note_id, data) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for relation in &graph.relations {
+                    insert.execute(params![
+                        relation.id,
+                        relation.from_note_id,
+                        relation.to_note_id,
+                        serde_json::to_string(relation)?,
+                    ])?;
+                }
+            }
+            tx.execute(
+                "INSERT INTO meta (key, value) VALUES ('auto_relation_threshold', ?1)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![graph.auto_relation_threshold.to_string()],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn load_saved_searches(&self) -> StorageResult<Vec<SavedSearch>> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare("SELECT id, name, query FROM saved_searches ORDER BY rowid")?;
+            let rows = statement.query_map([], |row| {
+                Ok(SavedSearch {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    query: row.get(2)?,
+                })
+            })?;
+            let mut saved_searches = Vec::new();
+            for saved_search in rows {
+                saved_searches.push(saved_search?);
+            }
+            Ok(saved_searches)
+        })
+        .await
+    }
+    
+    async fn save_saved_searches(&self, saved_searches: &[SavedSearch]) -> StorageResult<()> {
+        let saved_searches = saved_searches.to_vec();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM saved_searches", [])?;
+            {
+                let mut insert = tx.prepare("INSERT INTO saved_searches (id, name, query) VALUES (?1, ?2, ?3)")?;
+                for saved_search in &saved_searches {
+                    insert.execute(params![saved_search.id, saved_search.name, saved_search.query])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn load_settings(&self) -> StorageResult<WorkspaceSettings> {
+        self.with_conn(|conn| {
+            let settings: Option<String> = conn
+                .query_row("SELECT value FROM meta WHERE key = 'settings'", [], |row| row.get(0))
+                .optional()?;
+            match settings {
+                Some(settings) => Ok(serde_json::from_str(&settings)?),
+                None => Ok(WorkspaceSettings::default()),
+            }
+        })
+        .await
+    }
+    
+    async fn save_settings(&self, settings: &WorkspaceSettings) -> StorageResult<()> {
+        let settings = serde_json::to_string(settings)?;
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO meta (key, value) VALUES ('settings', ?1)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![settings],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn list_revisions(&self, note_id: &str) -> StorageResult<Vec<NoteRevision>> {
+        let note_id = note_id.to_string();
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare("SELECT data FROM revisions WHERE note_id = ?1 ORDER BY rowid")?;
+            let rows = statement.query_map(params![note_id], |row| row.get::<_, String>(0))?;
+            let mut revisions = Vec::new();
+            for data in rows {
+                revisions.push(serde_json::from_str(&data?)?);
+            }
+            Ok(revisions)
+        })
+        .await
+    }
+}
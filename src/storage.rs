@@ -1,26 +1,224 @@
-use crate::note::AppNote;
+use crate::history::{self, NoteRevision, DEFAULT_REVISION_LIMIT};
+use crate::migrations::{self, CURRENT_SCHEMA_VERSION};
+use crate::note::{AppNote, KnowledgeGraph, Priority, WorkflowStatus};
+use crate::query::SavedSearch;
+use crate::sort::SortOrder;
+use async_trait::async_trait;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// Default quiet period before pending changes are written to disk
+const DEFAULT_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on how long a continuous stream of changes can postpone a flush
+const MAX_FLUSH_DELAY_FACTOR: u32 = 10;
+
+/// Days a note stays in the trash before it is purged for good
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Result type shared by the storage backends
+pub type StorageResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A storage backend for notes
+///
+/// Implemented by the JSON file store ([`NoteStorage`]) and the SQLite store
+/// ([`crate::SqliteNoteStorage`]), so the app can run on either.
+#[async_trait]
+pub trait NoteStore: Send + Sync {
+    /// Prepare the backend for use (read the file, create tables, ...)
+    async fn load(&self) -> StorageResult<()>;
+    
+    /// Get a specific note by ID
+    async fn get(&self, note_id: &str) -> StorageResult<Option<AppNote>>;
+    
+    /// Insert a note or replace the stored note with the same ID.
+    /// Records a revision whenever a tracked field changed.
+    async fn upsert(&self, note: &AppNote) -> StorageResult<()>;
+    
+    /// Permanently delete a note with its relations and history, returning whether the note existed.
+    /// Moving a note to the trash is an ordinary upsert with `deleted_at` set.
+    async fn delete(&self, note_id: &str) -> StorageResult<bool>;
+    
+    /// List every note that isn't in the trash
+    async fn list(&self) -> StorageResult<Vec<AppNote>>;
+    
+    /// List the notes matching a query (never includes the trash)
+    async fn query(&self, query: &NoteQuery) -> StorageResult<Vec<AppNote>>;
+    
+    /// List the notes in the trash, most recently deleted first
+    async fn list_trash(&self) -> StorageResult<Vec<AppNote>>;
+    
+    /// Permanently delete notes that have been in the trash for at least `retention_days`.
+    /// Returns the IDs of the purged notes.
+    async fn purge_trash(&self, retention_days: u32) -> StorageResult<Vec<String>> {
+        let mut purged = Vec::new();
+        for note in self.list_trash().await? {
+            if note.days_in_trash().is_some_and(|days| days >= i64::from(retention_days)) {
+                self.delete(&note.id).await?;
+                purged.push(note.id);
+            }
+        }
+        Ok(purged)
+    }
+    
+    /// Load the persisted knowledge graph
+    async fn load_graph(&self) -> StorageResult<KnowledgeGraph>;
+    
+    /// Replace the persisted knowledge graph
+    async fn save_graph(&self, graph: &KnowledgeGraph) -> StorageResult<()>;
+    
+    /// Load the saved searches in the order the user sees them
+    async fn load_saved_searches(&self) -> StorageResult<Vec<SavedSearch>>;
+    
+    /// Replace the persisted saved searches
+    async fn save_saved_searches(&self, saved_searches: &[SavedSearch]) -> StorageResult<()>;
+    
+    /// Load the view settings remembered for this store
+    async fn load_settings(&self) -> StorageResult<WorkspaceSettings>;
+    
+    /// Replace the view settings remembered for this store
+    async fn save_settings(&self, settings: &WorkspaceSettings) -> StorageResult<()>;
+    
+    /// List the revisions recorded for a note, oldest first
+    async fn list_revisions(&self, note_id: &str) -> StorageResult<Vec<NoteRevision>>;
+    
+    /// Roll a note's title and content back to one of its revisions, saving it like any
+    /// other edit. While the app runs, restore through
+    /// [`crate::AppController::restore_revision`] instead, so the controller's notes, search
+    /// index and undo history follow; writing here behind its back gets overwritten.
+    async fn restore_revision(&self, note_id: &str, revision_id: &str) -> StorageResult<AppNote> {
+        let mut note = self
+            .get(note_id)
+            .await?
+            .ok_or_else(|| format!("note {} not found", note_id))?;
+        let revision = self
+            .list_revisions(note_id)
+            .await?
+            .into_iter()
+            .find(|revision| revision.id == revision_id)
+            .ok_or_else(|| format!("revision {} not found for note {}", revision_id, note_id))?;
+        
+        note.update_content(revision.title, revision.content);
+        self.upsert(&note).await?;
+        Ok(note)
+    }
+    
+    /// Make sure every change so far is durably stored
+    async fn flush(&self) -> StorageResult<()> {
+        Ok(())
+    }
+}
+
+/// View settings remembered by each note store, so every workspace keeps its own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceSettings {
+    pub sort_order: SortOrder,
+}
+
+/// Filter criteria understood by every storage backend
+#[derive(Debug, Clone, Default)]
+pub struct NoteQuery {
+    pub status: Option<WorkflowStatus>,
+    pub priority: Option<Priority>,
+    pub tag: Option<String>,
+    /// Only notes due on or before this date
+    pub due_before: Option<NaiveDate>,
+    /// Only notes due on or after this date
+    pub due_after: Option<NaiveDate>,
+    pub limit: Option<usize>,
+}
+
+impl NoteQuery {
+    /// Check whether a note satisfies every criterion of the query (ignores `limit`)
+    pub fn matches(&self, note: &AppNote) -> bool {
+        if note.is_in_trash() {
+            return false;
+        }
+        if let Some(status) = &self.status {
+            if &note.workflow_status != status {
+                return false;
+            }
+        }
+        if let Some(priority) = &self.priority {
+            if &note.priority != priority {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !note.tags.contains(&tag.to_lowercase()) {
+                return false;
+            }
+        }
+        if self.due_before.is_some() || self.due_after.is_some() {
+            let Some(due) = note.due_date else {
+                return false;
+            };
+            if self.due_before.is_some_and(|before| due > before) {
+                return false;
+            }
+            if self.due_after.is_some_and(|after| due < after) {
+                return false;
+            }
+        }
+        true
+    }
+}
 
 /// Storage manager for sticky notes
+///
+/// Notes are kept in an in-memory cache after the first access. Mutations only touch
+/// the cache and schedule a debounced flush, so bursts of changes (e.g. dragging a note)
+/// are coalesced into a single write. Call [`NoteStorage::flush`] before shutting down.
 #[derive(Debug, Clone)]
 pub struct NoteStorage {
     data_dir: PathBuf,
     notes_file: PathBuf,
+    flush_delay: Duration,
+    revision_limit: usize,
+    inner: Arc<StorageInner>,
+}
+
+#[derive(Debug, Default)]
+struct StorageInner {
+    // Lazily loaded copy of the notes file
+    cache: Mutex<Option<StorageData>>,
+    // Serializes writers of the notes file
+    write_lock: Mutex<()>,
+    // Bumped on every mutation of the cache
+    generation: AtomicU64,
+    // Generation that was last written to disk
+    flushed_generation: AtomicU64,
+    flush_scheduled: AtomicBool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct StorageData {
+    schema_version: u32,
     notes: HashMap<String, AppNote>,
+    graph: KnowledgeGraph,
+    revisions: HashMap<String, Vec<NoteRevision>>,
+    saved_searches: Vec<SavedSearch>,
+    settings: WorkspaceSettings,
 }
 
 impl Default for StorageData {
     fn default() -> Self {
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             notes: HashMap::new(),
+            graph: KnowledgeGraph::new(),
+            revisions: HashMap::new(),
+            saved_searches: Vec::new(),
+            settings: WorkspaceSettings::default(),
         }
     }
 }
@@ -28,7 +226,11 @@ impl Default for StorageData {
 impl NoteStorage {
     /// Create a new note storage instance
     pub async fn new() -> Self {
-        let data_dir = Self::get_data_directory();
+        Self::with_data_dir(Self::get_data_directory()).await
+    }
+    
+    /// Create a note storage instance rooted at a specific directory
+    pub async fn with_data_dir(data_dir: PathBuf) -> Self {
         let notes_file = data_dir.join("notes.json");
         
         // Create data directory if it doesn't exist
@@ -39,11 +241,26 @@ impl NoteStorage {
         Self {
             data_dir,
             notes_file,
+            flush_delay: DEFAULT_FLUSH_DELAY,
+            revision_limit: DEFAULT_REVISION_LIMIT,
+            inner: Arc::new(StorageInner::default()),
         }
     }
     
-    /// Get the appropriate data directory for the current platform
-    fn get_data_directory() -> PathBuf {
+    /// Set how long the storage waits for changes to settle before writing them
+    pub fn with_flush_delay(mut self, flush_delay: Duration) -> Self {
+        self.flush_delay = flush_delay;
+        self
+    }
+    
+    /// Set how many revisions are kept per note
+    pub fn with_revision_limit(mut self, revision_limit: usize) -> Self {
+        self.revision_limit = revision_limit;
+        self
+    }
+    
+    /// Get the appropriate data directory for the current platform, shared by every backend
+    pub(crate) fn get_data_directory() -> PathBuf {
         if let Some(data_dir) = dirs::data_dir() {
             data_dir.join("rust_slint_sticky")
         } else {
@@ -54,25 +271,13 @@ impl NoteStorage {
         }
     }
     
-    /// Load all notes from storage
+    /// Load all notes from storage, including the ones in the trash
     pub async fn load_notes(&self) -> Result<Vec<crate::AppNote>, Box<dyn std::error::Error + Send + Sync>> {
-        if !self.notes_file.exists() {
-            return Ok(Vec::new());
-        }
-        
-        let mut file = fs::File::open(&self.notes_file).await?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).await?;
-        
-        if contents.trim().is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let storage_data: StorageData = serde_json::from_str(&contents)?;
-        
-        let notes: Vec<crate::AppNote> = storage_data
+        let data = self.cached_data().await?;
+        let notes: Vec<crate::AppNote> = data
             .notes
-            .into_values()
+            .values()
+            .cloned()
             .collect();
         
         Ok(notes)
@@ -80,17 +285,22 @@ impl NoteStorage {
     
     /// Save a note to storage
     pub async fn save_note(&self, note: &AppNote) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut storage_data = self.load_storage_data().await?;
-        storage_data.notes.insert(note.id.clone(), note.clone());
-        self.save_storage_data(&storage_data).await?;
+        let mut data = self.cached_data().await?;
+        let changed_fields = history::changed_fields(data.notes.get(&note.id), note);
+        if !changed_fields.is_empty() {
+            let revisions = data.revisions.entry(note.id.clone()).or_default();
+            revisions.push(NoteRevision::from_note(note, changed_fields));
+            history::enforce_retention(revisions, self.revision_limit);
+        }
+        data.notes.insert(note.id.clone(), note.clone());
+        self.mark_dirty();
+        
         Ok(())
     }
     
-    /// Delete a note from storage
+    /// Delete a note from storage, along with its knowledge graph relations
     pub async fn delete_note(&self, note_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut storage_data = self.load_storage_data().await?;
-        storage_data.notes.remove(note_id);
-        self.save_storage_data(&storage_data).await?;
+        NoteStore::delete(self, note_id).await?;
         Ok(())
     }
     
@@ -102,21 +312,24 @@ impl NoteStorage {
     
     /// Get a specific note by ID
     pub async fn get_note(&self, note_id: &str) -> Result<Option<AppNote>, Box<dyn std::error::Error + Send + Sync>> {
-        let storage_data = self.load_storage_data().await?;
-        Ok(storage_data.notes.get(note_id).cloned())
+        let data = self.cached_data().await?;
+        Ok(data.notes.get(note_id).cloned())
     }
     
     /// Clear all notes from storage
     pub async fn clear_all_notes(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let storage_data = StorageData::default();
-        self.save_storage_data(&storage_data).await?;
+        let mut data = self.cached_data().await?;
+        *data = StorageData::default();
+        self.mark_dirty();
+        
         Ok(())
     }
     
     /// Export notes to a backup file
     pub async fn export_notes(&self, backup_path: &PathBuf) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let storage_data = self.load_storage_data().await?;
-        let json_data = serde_json::to_string_pretty(&storage_data)?;
+        let data = self.cached_data().await?;
+        let json_data = serde_json::to_string_pretty(&*data)?;
+        drop(data);
         
         let mut file = fs::File::create(backup_path).await?;
         file.write_all(json_data.as_bytes()).await?;
@@ -131,22 +344,31 @@ impl NoteStorage {
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
         
-        let imported_data: StorageData = serde_json::from_str(&contents)?;
+        // Backups may come from an older version of the app
+        let mut document: serde_json::Value = serde_json::from_str(&contents)?;
+        migrations::migrate_document(&mut document)?;
+        let imported_data: StorageData = serde_json::from_value(document)?;
         let count = imported_data.notes.len();
         
         // Merge with existing notes
-        let mut storage_data = self.load_storage_data().await?;
-        for (id, note) in imported_data.notes {
-            storage_data.notes.insert(id, note);
+        let mut data = self.cached_data().await?;
+        data.notes.extend(imported_data.notes);
+        for relation in imported_data.graph.relations {
+            if !data.graph.relations.iter().any(|r| r.id == relation.id) {
+                data.graph.relations.push(relation);
+            }
         }
+        self.mark_dirty();
         
-        self.save_storage_data(&storage_data).await?;
         Ok(count)
     }
     
     /// Get storage statistics
     pub async fn get_stats(&self) -> Result<StorageStats, Box<dyn std::error::Error + Send + Sync>> {
-        let storage_data = self.load_storage_data().await?;
+        let data = self.cached_data().await?;
+        let total_notes = data.notes.len();
+        drop(data);
+        
         let file_size = if self.notes_file.exists() {
             fs::metadata(&self.notes_file).await?.len()
         } else {
@@ -154,14 +376,104 @@ impl NoteStorage {
         };
         
         Ok(StorageStats {
-            total_notes: storage_data.notes.len(),
+            total_notes,
             file_size_bytes: file_size,
             data_directory: self.data_dir.clone(),
         })
     }
     
+    /// Where the pre-migration copy of a notes file with the given version is kept
+    pub fn backup_file_for_version(&self, schema_version: u32) -> PathBuf {
+        self.data_dir.join(format!("notes.v{}.backup.json", schema_version))
+    }
+    
+    /// Whether the cache holds changes that have not been written to disk yet
+    pub fn has_pending_changes(&self) -> bool {
+        self.inner.generation.load(Ordering::SeqCst) != self.inner.flushed_generation.load(Ordering::SeqCst)
+    }
+    
+    /// Write any pending changes to disk immediately
+    pub async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Serialize under the cache lock, then write without holding it
+        let cache = self.inner.cache.lock().await;
+        let generation = self.inner.generation.load(Ordering::SeqCst);
+        if generation == self.inner.flushed_generation.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let json_data = match cache.as_ref() {
+            Some(data) => serde_json::to_string_pretty(data)?,
+            None => return Ok(()),
+        };
+        drop(cache);
+        
+        let _write_guard = self.inner.write_lock.lock().await;
+        // A concurrent flush may already have written a newer snapshot
+        if self.inner.flushed_generation.load(Ordering::SeqCst) >= generation {
+            return Ok(());
+        }
+        self.write_notes_file(&json_data).await?;
+        self.inner.flushed_generation.store(generation, Ordering::SeqCst);
+        
+        Ok(())
+    }
+    
     // Private helper methods
     
+    /// Lock the cache, loading it from disk on first use
+    async fn cached_data(&self) -> Result<MappedMutexGuard<'_, StorageData>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cache = self.inner.cache.lock().await;
+        if cache.is_none() {
+            *cache = Some(self.load_storage_data().await?);
+        }
+        Ok(MutexGuard::map(cache, |cache| cache.get_or_insert_with(StorageData::default)))
+    }
+    
+    /// Record a mutation and make sure a debounced flush is scheduled.
+    /// Called while the cache lock is held so `flush` sees a consistent generation.
+    fn mark_dirty(&self) {
+        self.inner.generation.fetch_add(1, Ordering::SeqCst);
+        self.schedule_flush();
+    }
+    
+    /// Start the debounce timer unless one is already running
+    fn schedule_flush(&self) {
+        if self.inner.flush_scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        
+        // Without a runtime there is nothing to run the timer; callers must flush explicitly
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            self.inner.flush_scheduled.store(false, Ordering::SeqCst);
+            return;
+        };
+        
+        let storage = self.clone();
+        handle.spawn(async move {
+            let started = Instant::now();
+            let max_delay = storage.flush_delay * MAX_FLUSH_DELAY_FACTOR;
+            
+            // Wait until changes stop arriving for a full delay, but never longer than max_delay
+            loop {
+                let seen = storage.inner.generation.load(Ordering::SeqCst);
+                tokio::time::sleep(storage.flush_delay).await;
+                let settled = storage.inner.generation.load(Ordering::SeqCst) == seen;
+                if settled || started.elapsed() >= max_delay {
+                    break;
+                }
+            }
+            
+            storage.inner.flush_scheduled.store(false, Ordering::SeqCst);
+            if let Err(e) = storage.flush().await {
+                eprintln!("Warning: Failed to flush notes: {}", e);
+            }
+            
+            // Changes that arrived while writing need another round
+            if storage.has_pending_changes() {
+                storage.schedule_flush();
+            }
+        });
+    }
+    
     async fn load_storage_data(&self) -> Result<StorageData, Box<dyn std::error::Error + Send + Sync>> {
         if !self.notes_file.exists() {
             return Ok(StorageData::default());
@@ -175,13 +487,28 @@ impl NoteStorage {
             return Ok(StorageData::default());
         }
         
-        let storage_data: StorageData = serde_json::from_str(&contents)?;
+        let mut document: serde_json::Value = serde_json::from_str(&contents)?;
+        let original_version = migrations::migrate_document(&mut document)?;
+        let storage_data: StorageData = serde_json::from_value(document)?;
+        
+        if original_version < CURRENT_SCHEMA_VERSION {
+            // Keep the untouched file around in case the upgrade loses something
+            let backup_file = self.backup_file_for_version(original_version);
+            fs::copy(&self.notes_file, &backup_file).await?;
+            println!(
+                "Migrated notes from schema v{} to v{} (backup: {})",
+                original_version,
+                CURRENT_SCHEMA_VERSION,
+                backup_file.display()
+            );
+            self.mark_dirty();
+        }
+        
         Ok(storage_data)
     }
     
-    async fn save_storage_data(&self, data: &StorageData) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let json_data = serde_json::to_string_pretty(data)?;
-        
+    
+    async fn write_notes_file(&self, json_data: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Write to a temporary file first, then rename for atomic operation
         let temp_file = self.notes_file.with_extension("tmp");
         
@@ -197,6 +524,108 @@ impl NoteStorage {
     }
 }
 
+#[async_trait]
+impl NoteStore for NoteStorage {
+    async fn load(&self) -> StorageResult<()> {
+        drop(self.cached_data().await?);
+        Ok(())
+    }
+    
+    async fn get(&self, note_id: &str) -> StorageResult<Option<AppNote>> {
+        self.get_note(note_id).await
+    }
+    
+    async fn upsert(&self, note: &AppNote) -> StorageResult<()> {
+        self.save_note(note).await
+    }
+    
+    async fn delete(&self, note_id: &str) -> StorageResult<bool> {
+        let mut data = self.cached_data().await?;
+        let existed = data.notes.remove(note_id).is_some();
+        let removed_relations = data.graph.remove_note_relations(note_id);
+        let removed_history = data.revisions.remove(note_id).is_some();
+        if existed || removed_relations > 0 || removed_history {
+            self.mark_dirty();
+        }
+        Ok(existed)
+    }
+    
+    async fn list(&self) -> StorageResult<Vec<AppNote>> {
+        self.query(&NoteQuery::default()).await
+    }
+    
+    async fn query(&self, query: &NoteQuery) -> StorageResult<Vec<AppNote>> {
+        let data = self.cached_data().await?;
+        let mut notes: Vec<AppNote> = data
+            .notes
+            .values()
+            .filter(|note| query.matches(note))
+            .cloned()
+            .collect();
+        drop(data);
+        
+        notes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        if let Some(limit) = query.limit {
+            notes.truncate(limit);
+        }
+        Ok(notes)
+    }
+    
+    async fn list_trash(&self) -> StorageResult<Vec<AppNote>> {
+        let data = self.cached_data().await?;
+        let mut notes: Vec<AppNote> = data.notes.values().filter(|note| note.is_in_trash()).cloned().collect();
+        drop(data);
+        
+        notes.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(notes)
+    }
+    
+    async fn load_graph(&self) -> StorageResult<KnowledgeGraph> {
+        let data = self.cached_data().await?;
+        Ok(data.graph.clone())
+    }
+    
+    async fn save_graph(&self, graph: &KnowledgeGraph) -> StorageResult<()> {
+        let mut data = self.cached_data().await?;
+        data.graph = graph.clone();
+        self.mark_dirty();
+        Ok(())
+    }
+    
+    async fn load_saved_searches(&self) -> StorageResult<Vec<SavedSearch>> {
+        let data = self.cached_data().await?;
+        Ok(data.saved_searches.clone())
+    }
+    
+    async fn save_saved_searches(&self, saved_searches: &[SavedSearch]) -> StorageResult<()> {
+        let mut data = self.cached_data().await?;
+        data.saved_searches = saved_searches.to_vec();
+        self.mark_dirty();
+        Ok(())
+    }
+    
+    async fn load_settings(&self) -> StorageResult<WorkspaceSettings> {
+        let data = self.cached_data().await?;
+        Ok(data.settings.clone())
+    }
+    
+    async fn save_settings(&self, settings: &WorkspaceSettings) -> StorageResult<()> {
+        let mut data = self.cached_data().await?;
+        data.settings = settings.clone();
+        self.mark_dirty();
+        Ok(())
+    }
+    
+    async fn list_revisions(&self, note_id: &str) -> StorageResult<Vec<NoteRevision>> {
+        let data = self.cached_data().await?;
+        Ok(data.revisions.get(note_id).cloned().unwrap_or_default())
+    }
+    
+    async fn flush(&self) -> StorageResult<()> {
+        NoteStorage::flush(self).await
+    }
+}
+
 /// Storage statistics
 #[derive(Debug)]
 pub struct StorageStats {
@@ -218,4 +647,4 @@ impl StorageStats {
             format!("{:.1} GB", bytes / (1024.0 * 1024.0 * 1024.0))
         }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+
+/// Formats timestamps were stored in before they became RFC 3339. They carry no
+/// timezone and were always written from `Utc::now()`, so they are read as UTC.
+const LEGACY_TIMESTAMP_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+/// How dates are shown and typed
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How timestamps are shown, in the viewer's timezone
+pub const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Parse a stored timestamp: RFC 3339, or one of the legacy formats (read as UTC)
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    LEGACY_TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|timestamp| timestamp.and_utc())
+}
+
+/// Parse a stored due date. Due dates used to be free text, so a timestamp counts
+/// as the day it falls on.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .ok()
+        .or_else(|| parse_timestamp(value).map(|timestamp| timestamp.date_naive()))
+}
+
+/// RFC 3339 in UTC with as many fractional digits as needed, as written to disk
+pub fn to_rfc3339(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// RFC 3339 in UTC with a fixed number of fractional digits, so that the text sorts
+/// chronologically (used for indexed SQLite columns)
+pub fn to_sortable(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+/// Show a timestamp in the given timezone
+pub fn format_in<Tz: TimeZone>(timestamp: &DateTime<Utc>, timezone: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    timestamp.with_timezone(timezone).format(DISPLAY_FORMAT).to_string()
+}
+
+/// Show a timestamp in the local timezone
+pub fn format_local(timestamp: &DateTime<Utc>) -> String {
+    format_in(timestamp, &Local)
+}
+
+/// The local calendar day a timestamp falls on
+pub fn local_date(timestamp: &DateTime<Utc>) -> NaiveDate {
+    timestamp.with_timezone(&Local).date_naive()
+}
+
+/// Serde support for `DateTime<Utc>` fields: written as RFC 3339, read from RFC 3339
+/// or the legacy formats. Stores that skip the schema migrations (SQLite rows for
+/// revisions and relations) rely on the lenient read.
+pub mod serde_timestamp {
+    use super::{parse_timestamp, to_rfc3339};
+    use chrono::{DateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(timestamp: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_rfc3339(timestamp))
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        parse_timestamp(&value).ok_or_else(|| D::Error::custom(format!("invalid timestamp `{}`", value)))
+    }
+}
+
+/// Serde support for `Option<DateTime<Utc>>` fields, see [`serde_timestamp`]
+pub mod serde_optional_timestamp {
+    use super::{parse_timestamp, to_rfc3339};
+    use chrono::{DateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(timestamp: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(timestamp) => serializer.serialize_some(&to_rfc3339(timestamp)),
+            None => serializer.serialize_none(),
+        }
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| parse_timestamp(&value).ok_or_else(|| D::Error::custom(format!("invalid timestamp `{}`", value))))
+            .transpose()
+    }
+}
+
+/// Serde support for optional due dates: written as `YYYY-MM-DD`, read from any
+/// format [`parse_date`] understands
+pub mod serde_optional_date {
+    use super::{parse_date, DATE_FORMAT};
+    use chrono::NaiveDate;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.serialize_some(&date.format(DATE_FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| parse_date(&value).ok_or_else(|| D::Error::custom(format!("invalid date `{}`", value))))
+            .transpose()
+    }
+}
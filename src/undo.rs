@@ -0,0 +1,164 @@
+use crate::note::AppNote;
+
+/// Number of steps kept on the undo stack unless configured otherwise
+pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+/// What a command did to its note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+    Create,
+    Edit,
+    Move,
+    ChangeStatus,
+    Checklist,
+    Trash,
+    Restore,
+}
+
+impl CommandKind {
+    /// Short human readable name, e.g. for an "Undo Move" tooltip
+    pub fn label(&self) -> &'static str {
+        match self {
+            CommandKind::Create => "Create",
+            CommandKind::Edit => "Edit",
+            CommandKind::Move => "Move",
+            CommandKind::ChangeStatus => "Status Change",
+            CommandKind::Checklist => "Checklist",
+            CommandKind::Trash => "Delete",
+            CommandKind::Restore => "Restore",
+        }
+    }
+}
+
+/// A reversible change to a single note
+///
+/// The command stores the whole note before and after the change (`None` when the
+/// note doesn't exist), so undoing is putting `before` back and redoing is putting
+/// `after` back, whatever kind of change it was. Other notes changed along with it, such
+/// as the links a rename rewrites, are kept the same way in `linked`.
+#[derive(Debug, Clone)]
+pub struct NoteCommand {
+    pub kind: CommandKind,
+    pub note_id: String,
+    pub before: Option<AppNote>,
+    pub after: Option<AppNote>,
+    pub linked: Vec<(AppNote, AppNote)>,
+}
+
+impl NoteCommand {
+    pub fn new(kind: CommandKind, before: Option<AppNote>, after: Option<AppNote>) -> Self {
+        let note_id = after
+            .as_ref()
+            .or(before.as_ref())
+            .map(|note| note.id.clone())
+            .unwrap_or_default();
+        Self {
+            kind,
+            note_id,
+            before,
+            after,
+            linked: Vec::new(),
+        }
+    }
+    
+    /// A note that didn't exist before
+    pub fn create(note: AppNote) -> Self {
+        Self::new(CommandKind::Create, None, Some(note))
+    }
+    
+    /// A change from one version of a note to another
+    pub fn update(kind: CommandKind, before: AppNote, after: AppNote) -> Self {
+        Self::new(kind, Some(before), Some(after))
+    }
+}
+
+/// Undo/redo history of note commands
+#[derive(Debug)]
+pub struct UndoStack {
+    undo: Vec<NoteCommand>,
+    redo: Vec<NoteCommand>,
+    limit: usize,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit: DEFAULT_UNDO_LIMIT,
+        }
+    }
+    
+    /// Set how many steps can be undone
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+    
+    /// Record a command that has just been applied. Clears the redo history.
+    ///
+    /// Every command is its own step; a drag reports a note's position once, when it is
+    /// released, so a drag is one step without merging moves by how close together they are.
+    pub fn record(&mut self, command: NoteCommand) {
+        self.redo.clear();
+        self.undo.push(command);
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.undo.drain(..excess);
+        }
+    }
+    
+    /// Take the most recent command off the undo stack. The caller puts `before` back.
+    pub fn undo(&mut self) -> Option<NoteCommand> {
+        let command = self.undo.pop()?;
+        self.redo.push(command.clone());
+        Some(command)
+    }
+    
+    /// Take the most recently undone command off the redo stack. The caller puts `after` back.
+    pub fn redo(&mut self) -> Option<NoteCommand> {
+        let command = self.redo.pop()?;
+        self.undo.push(command.clone());
+        Some(command)
+    }
+    
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    
+    /// Kind of the command the next undo would revert
+    pub fn next_undo(&self) -> Option<CommandKind> {
+        self.undo.last().map(|command| command.kind)
+    }
+    
+    /// Kind of the command the next redo would reapply
+    pub fn next_redo(&self) -> Option<CommandKind> {
+        self.redo.last().map(|command| command.kind)
+    }
+    
+    /// Drop every command on a note that no longer exists (e.g. deleted forever), and the
+    /// note from the commands that changed it along the way
+    pub fn forget_note(&mut self, note_id: &str) {
+        for commands in [&mut self.undo, &mut self.redo] {
+            commands.retain(|command| command.note_id != note_id);
+            for command in commands.iter_mut() {
+                command.linked.retain(|(before, _)| before.id != note_id);
+            }
+        }
+    }
+    
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
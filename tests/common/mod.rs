@@ -0,0 +1,15 @@
+//! Fixtures shared by the integration tests; each test crate uses only some of them
+#![allow(dead_code)]
+
+use rust_slint_sticky::AppNote;
+use std::path::PathBuf;
+
+/// A new note with the given title and content and defaults for everything else
+pub fn note(title: &str, content: &str) -> AppNote {
+    AppNote::new(title.to_string(), content.to_string())
+}
+
+/// Fresh, empty data directory for a single test
+pub fn temp_data_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("rust_slint_sticky_test_{}", uuid::Uuid::new_v4()))
+}
@@ -0,0 +1,739 @@
+mod common;
+
+use common::{note, temp_data_dir};
+use rust_slint_sticky::controller::spawn_storage_writer;
+use rust_slint_sticky::history::DiffLine;
+use rust_slint_sticky::note::{KnowledgeGraph, NoteEdit, Priority, RelationError, RelationType, SerializableColor, WorkflowStatus};
+use rust_slint_sticky::timestamp::{parse_date, parse_timestamp};
+use rust_slint_sticky::undo::CommandKind;
+use rust_slint_sticky::{AppController, AppNote, ForceLayout, NoteStorage, NoteStore, SortOrder, StorageOp, WorkspaceSettings};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn controller_with(notes: Vec<AppNote>) -> (AppController, mpsc::UnboundedReceiver<StorageOp>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (AppController::new(notes, KnowledgeGraph::new(), tx), rx)
+}
+
+fn drain(rx: &mut mpsc::UnboundedReceiver<StorageOp>) -> Vec<StorageOp> {
+    let mut ops = Vec::new();
+    while let Ok(op) = rx.try_recv() {
+        ops.push(op);
+    }
+    ops
+}
+
+#[test]
+fn test_quick_add_creates_and_saves_a_note() {
+    let (mut controller, mut rx) = controller_with(Vec::new());
+    
+    let first = controller.quick_add("Buy milk");
+    let second = controller.quick_add("A rather long thought that will not fit in a title");
+    
+    assert_eq!(controller.notes().len(), 2);
+    assert_eq!(controller.note(&first).unwrap().title, "Buy milk");
+    let long = controller.note(&second).unwrap();
+    assert_eq!(long.title, "A rather long thought that ...");
+    assert_eq!(long.content, "A rather long thought that will not fit in a title");
+    assert_eq!((long.x, long.y), (70.0, 70.0));
+    
+    let ops = drain(&mut rx);
+    assert_eq!(ops.len(), 2);
+    assert!(matches!(&ops[0], StorageOp::Save(saved) if saved.id == first));
+    assert!(controller.can_undo());
+}
+
+#[test]
+fn test_edit_and_status_change_can_be_undone() {
+    let original = note("Draft", "Rough idea");
+    let (mut controller, mut rx) = controller_with(vec![original.clone()]);
+    
+    let mut edited = original.clone();
+    edited.update_content("Plan".to_string(), "Worked out".to_string());
+    assert!(controller.update_note(edited));
+    assert!(controller.set_workflow_status(&original.id, WorkflowStatus::Todo));
+    assert!(!controller.update_note(note("Unknown", "")));
+    
+    assert_eq!(controller.undo(), Some(CommandKind::ChangeStatus));
+    assert_eq!(controller.note(&original.id).unwrap().workflow_status, WorkflowStatus::Idea);
+    assert_eq!(controller.undo(), Some(CommandKind::Edit));
+    assert_eq!(controller.note(&original.id).unwrap().title, "Draft");
+    assert_eq!(controller.undo(), None);
+    
+    assert_eq!(controller.redo(), Some(CommandKind::Edit));
+    assert_eq!(controller.note(&original.id).unwrap().title, "Plan");
+    assert!(controller.can_redo());
+    
+    // Two changes, two undos and a redo are all written through
+    assert_eq!(drain(&mut rx).len(), 5);
+}
+
+#[test]
+fn test_checking_a_checklist_item_updates_completion_and_can_be_undone() {
+    let mut release = note("Release", "");
+    let item = release.add_checklist_item("Run tests".to_string());
+    release.add_checklist_item("Publish".to_string());
+    let (mut controller, mut rx) = controller_with(vec![release.clone()]);
+    
+    assert!(controller.toggle_checklist_item(&release.id, &item));
+    assert_eq!(controller.note(&release.id).unwrap().completion_percentage, 50.0);
+    assert!(!controller.toggle_checklist_item(&release.id, "missing"));
+    assert!(matches!(&drain(&mut rx)[..], [StorageOp::Save(saved)] if saved.checklist[0].done));
+    
+    assert_eq!(controller.undo(), Some(CommandKind::Checklist));
+    let undone = controller.note(&release.id).unwrap();
+    assert!(!undone.checklist[0].done);
+    assert_eq!(undone.completion_percentage, 0.0);
+}
+
+#[test]
+fn test_editing_keeps_metadata_that_was_not_changed() {
+    let mut original = note("Release", "Ship 1.0");
+    original.add_tag("work".to_string());
+    original.set_workflow_status(WorkflowStatus::Review);
+    original.priority = Priority::Urgent;
+    original.due_date = parse_date("2025-09-01");
+    original.estimated_time = Some(90);
+    original.completion_percentage = 40.0;
+    original.text_color = Some(SerializableColor { red: 255, green: 255, blue: 255 });
+    original.set_position(300.0, 120.0);
+    original.created_at = parse_timestamp("2025-01-02 03:04").unwrap();
+    let (mut controller, mut rx) = controller_with(vec![original.clone()]);
+    
+    let mut edit = NoteEdit::from_note(&original);
+    assert_eq!(edit.tags_text(), "work");
+    edit.content = "Ship 1.0 on Monday".to_string();
+    edit.tags = NoteEdit::parse_tags("Work, release, ");
+    assert!(controller.edit_note(&original.id, &edit));
+    
+    let edited = controller.note(&original.id).unwrap();
+    assert_eq!(edited.content, "Ship 1.0 on Monday");
+    assert_eq!(edit.tags_text(), "release, work");
+    assert_eq!(edited.tags, edit.tags);
+    assert_eq!(edited.workflow_status, WorkflowStatus::Review);
+    assert_eq!(edited.priority, Priority::Urgent);
+    assert_eq!(edited.due_date, parse_date("2025-09-01"));
+    assert_eq!(edited.estimated_time, Some(90));
+    assert_eq!(edited.completion_percentage, 40.0);
+    assert_eq!(edited.text_color, original.text_color);
+    assert_eq!((edited.x, edited.y), (300.0, 120.0));
+    assert_eq!(edited.created_at, parse_timestamp("2025-01-02 03:04").unwrap());
+    assert_eq!(drain(&mut rx).len(), 1);
+    
+    // Saving the editor without changes writes nothing and adds no undo step
+    let unchanged = NoteEdit::from_note(edited);
+    assert!(!controller.edit_note(&original.id, &unchanged));
+    assert!(drain(&mut rx).is_empty());
+    assert_eq!(controller.undo(), Some(CommandKind::Edit));
+    assert!(!controller.can_undo());
+}
+
+#[test]
+fn test_undoing_a_create_keeps_the_note_and_its_relations_for_redo() {
+    let existing = note("Existing", "");
+    let (mut controller, mut rx) = controller_with(vec![existing.clone()]);
+    let created = note("Created", "");
+    controller.create_note(created.clone());
+    controller.create_relation(&created.id, &existing.id, RelationType::DependsOn, 1.0, None).unwrap();
+    drain(&mut rx);
+    
+    // The note waits in the trash instead of being deleted with its relations and history
+    assert_eq!(controller.undo(), Some(CommandKind::Create));
+    assert!(controller.notes().iter().all(|note| note.id != created.id));
+    assert_eq!(controller.trash()[0].id, created.id);
+    assert!(controller.visible_relations().is_empty());
+    let ops = drain(&mut rx);
+    assert!(!ops.iter().any(|op| matches!(op, StorageOp::Delete(_) | StorageOp::SaveGraph(_))));
+    
+    assert_eq!(controller.redo(), Some(CommandKind::Create));
+    assert!(controller.trash().is_empty());
+    assert_eq!(controller.note(&created.id).unwrap().deleted_at, None);
+    let relations = controller.visible_relations();
+    assert_eq!(relations.len(), 1);
+    assert_eq!(relations[0].relation_type, RelationType::DependsOn);
+}
+
+#[test]
+fn test_each_drag_is_one_undo_step() {
+    let original = note("Card", "");
+    let (mut controller, _rx) = controller_with(vec![original.clone()]);
+    
+    // The window reports a drag once, on release; quick drags stay separate steps
+    controller.move_note(&original.id, 20.0, 20.0);
+    controller.move_note(&original.id, 60.0, 40.0);
+    assert_eq!(controller.note(&original.id).unwrap().x, 60.0);
+    
+    assert_eq!(controller.undo(), Some(CommandKind::Move));
+    assert_eq!(controller.note(&original.id).unwrap().x, 20.0);
+    assert_eq!(controller.undo(), Some(CommandKind::Move));
+    let restored = controller.note(&original.id).unwrap();
+    assert_eq!((restored.x, restored.y), (original.x, original.y));
+    assert!(!controller.can_undo());
+}
+
+#[test]
+fn test_revision_changes_with_everything_but_moves() {
+    let first = note("First", "");
+    let second = note("Second", "");
+    let (mut controller, _rx) = controller_with(vec![first.clone(), second.clone()]);
+    
+    let revision = controller.revision();
+    controller.move_note(&first.id, 80.0, 40.0);
+    controller.undo();
+    assert_eq!(controller.revision(), revision);
+    
+    controller.set_workflow_status(&first.id, WorkflowStatus::Done);
+    assert_ne!(controller.revision(), revision);
+    
+    let revision = controller.revision();
+    controller.create_relation(&first.id, &second.id, RelationType::DependsOn, 0.5, None).unwrap();
+    assert_ne!(controller.revision(), revision);
+    
+    controller.set_search_text("status:done");
+    let revision = controller.revision();
+    controller.save_search("Done");
+    assert_ne!(controller.revision(), revision);
+}
+
+#[test]
+fn test_visible_notes_combine_status_filter_and_search() {
+    let mut rust = note("Rust ownership", "Borrowing rules");
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    let mut slint = note("Slint layouts", "Rows and columns");
+    slint.set_workflow_status(WorkflowStatus::Todo);
+    slint.add_tag("rust".to_string());
+    let groceries = note("Groceries", "Milk and bread");
+    let (mut controller, _rx) = controller_with(vec![rust, slint, groceries]);
+    
+    assert_eq!(controller.visible_notes().len(), 3);
+    
+    controller.set_search_text("RUST");
+    assert_eq!(controller.visible_notes().len(), 2);
+    
+    controller.set_filter_status(Some(WorkflowStatus::Todo));
+    controller.set_search_text("columns");
+    let titles: Vec<&str> = controller.visible_notes().iter().map(|note| note.title.as_str()).collect();
+    assert_eq!(titles, vec!["Slint layouts"]);
+    
+    controller.set_search_text("");
+    controller.set_filter_status(Some(WorkflowStatus::Done));
+    assert!(controller.visible_notes().is_empty());
+    
+    controller.set_filter_status(None);
+    assert_eq!(controller.visible_notes().len(), 3);
+}
+
+#[test]
+fn test_search_ranks_results_and_follows_changes() {
+    let passing = note("Weekly plan", "Maybe look at rust later");
+    let focused = note("Rust book", "Rust chapters on rust traits");
+    let (mut controller, _rx) = controller_with(vec![passing.clone(), focused.clone()]);
+    
+    controller.set_search_text("rust");
+    let titles: Vec<&str> = controller.visible_notes().iter().map(|note| note.title.as_str()).collect();
+    assert_eq!(titles, vec!["Rust book", "Weekly plan"]);
+    
+    // Edits, new notes and trashed notes are reflected without rebuilding anything
+    controller.trash_note(&focused.id);
+    let added = controller.quick_add("Rust meetup on Friday");
+    let mut edited = controller.note(&passing.id).unwrap().clone();
+    edited.update_content("Weekly plan".to_string(), "Groceries".to_string());
+    controller.update_note(edited);
+    let visible: Vec<&str> = controller.visible_notes().iter().map(|note| note.id.as_str()).collect();
+    assert_eq!(visible, vec![added.as_str()]);
+    assert_eq!(controller.search_index().len(), 2);
+    
+    // Punctuation alone is not a search
+    controller.set_search_text("?!");
+    assert_eq!(controller.visible_notes().len(), 2);
+}
+
+#[test]
+fn test_search_queries_combine_with_the_status_filter_and_report_errors() {
+    let mut rust = note("Rust ownership", "Borrowing rules");
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    rust.priority = Priority::High;
+    let mut slint = note("Slint layouts", "Rows and columns in rust");
+    slint.set_workflow_status(WorkflowStatus::Done);
+    let (mut controller, _rx) = controller_with(vec![rust, slint]);
+    
+    controller.set_search_text("rust priority:>=high OR status:done");
+    assert_eq!(controller.visible_notes().len(), 2);
+    assert!(controller.search_error().is_none());
+    
+    controller.set_filter_status(Some(WorkflowStatus::Done));
+    let titles: Vec<&str> = controller.visible_notes().iter().map(|note| note.title.as_str()).collect();
+    assert_eq!(titles, vec!["Slint layouts"]);
+    
+    // A broken query is reported and doesn't hide everything
+    controller.set_filter_status(None);
+    controller.set_search_text("status:someday");
+    assert!(controller.search_error().unwrap().message.contains("unknown status"));
+    assert_eq!(controller.visible_notes().len(), 2);
+    
+    controller.set_search_text("-rows");
+    assert!(controller.search_error().is_none());
+    let titles: Vec<&str> = controller.visible_notes().iter().map(|note| note.title.as_str()).collect();
+    assert_eq!(titles, vec!["Rust ownership"]);
+}
+
+#[test]
+fn test_note_matches_highlight_only_searched_words() {
+    let target = note("Slint layouts", "Rows and columns");
+    let (mut controller, _rx) = controller_with(vec![target.clone()]);
+    assert!(controller.note_match(&target).is_none());
+    
+    // Field filters select notes but have no words to highlight
+    controller.set_search_text("status:idea");
+    assert!(!controller.is_searching_text());
+    assert!(controller.note_match(&target).is_none());
+    
+    controller.set_search_text("slnit -columnz");
+    assert!(controller.is_searching_text());
+    let note_match = controller.note_match(&target).unwrap();
+    assert_eq!(note_match.title.segments(), vec![("Slint", true), (" layouts", false)]);
+    assert!(!note_match.snippet.is_highlighted());
+}
+
+#[test]
+fn test_trash_restore_and_delete_forever() {
+    let keep = note("Keep", "");
+    let drop = note("Drop", "");
+    let mut old = note("Old", "");
+    old.move_to_trash();
+    let (mut controller, mut rx) = controller_with(vec![keep.clone(), drop.clone(), old.clone()]);
+    assert_eq!(controller.notes().len(), 2);
+    assert_eq!(controller.trash().len(), 1);
+    assert_eq!(controller.days_left_in_trash(&old), controller.trash_retention_days());
+    
+    // Only trashed notes can be deleted for good
+    assert!(!controller.delete_forever(&keep.id));
+    
+    assert!(controller.trash_note(&drop.id));
+    assert_eq!(controller.trash().len(), 2);
+    assert!(controller.restore_note(&drop.id));
+    assert!(controller.note(&drop.id).unwrap().deleted_at.is_none());
+    assert_eq!(controller.undo(), Some(CommandKind::Restore));
+    assert!(controller.note(&drop.id).unwrap().is_in_trash());
+    drain(&mut rx);
+    
+    assert!(controller.delete_forever(&drop.id));
+    assert!(controller.note(&drop.id).is_none());
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::Delete(id)] if *id == drop.id));
+    
+    // Deleting forever can't be undone, so the note's history is gone too
+    assert_eq!(controller.undo(), None);
+    
+    assert_eq!(controller.empty_trash(), 1);
+    assert!(controller.trash().is_empty());
+    assert_eq!(controller.notes().len(), 1);
+}
+
+#[test]
+fn test_relations_of_trashed_notes_are_hidden_until_restored() {
+    let a = note("A", "");
+    let b = note("B", "");
+    let mut graph = KnowledgeGraph::new();
+    graph.add_relation(a.id.clone(), b.id.clone(), RelationType::References, 1.0);
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let mut controller = AppController::new(vec![a.clone(), b.clone()], graph, tx);
+    
+    assert_eq!(controller.visible_relations().len(), 1);
+    controller.trash_note(&b.id);
+    assert!(controller.visible_relations().is_empty());
+    assert_eq!(controller.graph().relations.len(), 1);
+    
+    controller.restore_note(&b.id);
+    assert_eq!(controller.visible_relations().len(), 1);
+    
+    controller.trash_note(&b.id);
+    controller.delete_forever(&b.id);
+    assert!(controller.graph().relations.is_empty());
+}
+
+#[test]
+fn test_wiki_links_follow_edits_and_renames() {
+    let target = note("Roadmap", "Q3 goals");
+    let source = note("Standup", "Blocked on [[roadmap|the plan]]");
+    let (mut controller, mut rx) = controller_with(vec![target.clone(), source.clone()]);
+    
+    assert_eq!(controller.backlinks(&target.id).iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec![source.id.as_str()]);
+    assert_eq!(controller.linked_note(&source.id, "ROADMAP").unwrap().id, target.id);
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveGraph(_)]));
+    
+    // Renaming the target rewrites the link, so it keeps pointing at the same note
+    let mut edit = NoteEdit::from_note(&target);
+    edit.title = "Plan 2025".to_string();
+    controller.edit_note(&target.id, &edit);
+    assert_eq!(controller.note(&source.id).unwrap().content, "Blocked on [[Plan 2025|the plan]]");
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    assert!(drain(&mut rx).iter().any(|op| matches!(op, StorageOp::Save(saved) if saved.id == source.id)));
+    
+    // Undo puts the linking note back exactly as it was
+    controller.undo();
+    assert_eq!(controller.note(&source.id).unwrap().content, "Blocked on [[roadmap|the plan]]");
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    
+    // Links come and go with the content, and with the notes they point at
+    let mut edit = NoteEdit::from_note(controller.note(&source.id).unwrap());
+    edit.content = "Unblocked".to_string();
+    controller.edit_note(&source.id, &edit);
+    assert!(controller.backlinks(&target.id).is_empty());
+    controller.undo();
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    controller.trash_note(&target.id);
+    assert!(controller.graph().relations.is_empty());
+    controller.restore_note(&target.id);
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+}
+
+#[test]
+fn test_undoing_a_rename_restores_exactly_the_links_it_rewrote() {
+    let target = note("Roadmap", "Q3 goals");
+    let source = note("Standup", "Blocked on [[Roadmap|the plan]]");
+    let retro = note("Retro", "See [[Plan 2025]]");
+    let (mut controller, _rx) = controller_with(vec![target.clone(), source.clone(), retro.clone()]);
+    let content = |controller: &AppController, id: &str| controller.note(id).unwrap().content.clone();
+    
+    let mut edit = NoteEdit::from_note(&target);
+    edit.title = "Plan 2025".to_string();
+    controller.edit_note(&target.id, &edit);
+    assert_eq!(controller.backlinks(&target.id).len(), 2);
+    let mut edit = NoteEdit::from_note(controller.note(&source.id).unwrap());
+    edit.content = "Blocked on [[Plan 2025|the plan]] until Friday".to_string();
+    controller.edit_note(&source.id, &edit);
+    
+    assert_eq!(controller.undo(), Some(CommandKind::Edit));
+    assert_eq!(content(&controller, &source.id), "Blocked on [[Plan 2025|the plan]]");
+    assert_eq!(controller.undo(), Some(CommandKind::Edit));
+    assert_eq!(content(&controller, &source.id), "Blocked on [[Roadmap|the plan]]");
+    // A link that was written to the new title wasn't rewritten, so it isn't rewritten back
+    assert_eq!(content(&controller, &retro.id), "See [[Plan 2025]]");
+    assert_eq!(controller.backlinks(&target.id).iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec![source.id.as_str()]);
+    
+    controller.redo();
+    assert_eq!(content(&controller, &source.id), "Blocked on [[Plan 2025|the plan]]");
+    assert_eq!(content(&controller, &retro.id), "See [[Plan 2025]]");
+    assert_eq!(controller.backlinks(&target.id).len(), 2);
+    controller.redo();
+    assert_eq!(content(&controller, &source.id), "Blocked on [[Plan 2025|the plan]] until Friday");
+    assert!(!controller.can_redo());
+}
+
+#[test]
+fn test_titles_that_would_break_links_are_refused() {
+    let target = note("Roadmap", "Q3 goals");
+    let source = note("Standup", "Blocked on [[Roadmap]]");
+    let (mut controller, _rx) = controller_with(vec![target.clone(), source.clone()]);
+    
+    let mut edit = NoteEdit::from_note(&target);
+    edit.title = "Plan A|B".to_string();
+    assert!(NoteEdit::check_title(&edit.title).is_err());
+    assert!(!controller.edit_note(&target.id, &edit));
+    assert_eq!(controller.note(&target.id).unwrap().title, "Roadmap");
+    assert_eq!(controller.note(&source.id).unwrap().content, "Blocked on [[Roadmap]]");
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    
+    // A note that already has such a title can still be edited otherwise
+    let odd = note("[WIP] Draft", "");
+    controller.create_note(odd.clone());
+    let mut edit = NoteEdit::from_note(&odd);
+    edit.content = "First pass".to_string();
+    assert!(controller.edit_note(&odd.id, &edit));
+}
+
+#[test]
+fn test_a_linked_note_cannot_take_an_older_notes_title() {
+    let mut plan = note("Plan", "The old plan");
+    plan.created_at = parse_timestamp("2025-01-01 09:00").unwrap();
+    let mut target = note("Roadmap", "Q3 goals");
+    target.created_at = parse_timestamp("2025-02-01 09:00").unwrap();
+    let source = note("Standup", "Blocked on [[Roadmap]]");
+    let (mut controller, _rx) = controller_with(vec![plan.clone(), target.clone(), source.clone()]);
+    
+    // The rewritten links would resolve to the older note and take the backlinks with them
+    assert!(controller.check_title(&target.id, "plan").is_err());
+    let mut edit = NoteEdit::from_note(&target);
+    edit.title = "plan".to_string();
+    assert!(!controller.edit_note(&target.id, &edit));
+    assert_eq!(controller.note(&source.id).unwrap().content, "Blocked on [[Roadmap]]");
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    assert!(controller.backlinks(&plan.id).is_empty());
+    
+    // Nor can the older note take the title of the newer one and its backlinks with it
+    let mut edit = NoteEdit::from_note(&plan);
+    edit.title = "Roadmap".to_string();
+    assert!(!controller.edit_note(&plan.id, &edit));
+    assert_eq!(controller.backlinks(&target.id).len(), 1);
+    
+    // Without backlinks on either side nothing moves
+    assert!(controller.check_title(&source.id, "Plan").is_ok());
+}
+
+#[test]
+fn test_manual_relations_are_validated_and_saved() {
+    let a = note("API", "");
+    let b = note("Frontend", "");
+    let (mut controller, mut rx) = controller_with(vec![a.clone(), b.clone()]);
+    
+    let relation_id = controller.create_relation(&b.id, &a.id, RelationType::DependsOn, 0.7, None).unwrap();
+    assert_eq!(controller.visible_relations().len(), 1);
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveGraph(graph)] if graph.relations.len() == 1));
+    
+    assert_eq!(
+        controller.create_relation(&b.id, "gone", RelationType::Blocks, 1.0, None),
+        Err(RelationError::UnknownNote("gone".to_string()))
+    );
+    assert!(controller.create_relation(&b.id, &a.id, RelationType::DependsOn, 1.0, None).is_err());
+    assert!(drain(&mut rx).is_empty());
+    
+    controller.update_relation(&relation_id, RelationType::Blocks, 1.0, Some("waits on the schema".to_string())).unwrap();
+    assert_eq!(controller.graph().relations[0].relation_type, RelationType::Blocks);
+    controller.delete_relation(&relation_id).unwrap();
+    assert!(controller.graph().relations.is_empty());
+    assert_eq!(drain(&mut rx).len(), 2);
+}
+
+#[test]
+fn test_notes_are_blocked_until_their_dependencies_are_done() {
+    let release = note("Release", "");
+    let tests = note("Tests", "");
+    let docs = note("Docs", "");
+    let (mut controller, _rx) = controller_with(vec![release.clone(), tests.clone(), docs.clone()]);
+    controller.create_relation(&release.id, &tests.id, RelationType::DependsOn, 1.0, None).unwrap();
+    controller.create_relation(&docs.id, &release.id, RelationType::SubtaskOf, 1.0, None).unwrap();
+    
+    let titles = |controller: &AppController| controller.blocked_by(&release.id).iter().map(|n| n.title.clone()).collect::<Vec<_>>();
+    assert_eq!(titles(&controller), vec!["Tests", "Docs"]);
+    assert!(controller.blocked_by(&tests.id).is_empty());
+    
+    controller.set_workflow_status(&tests.id, WorkflowStatus::Done);
+    assert_eq!(titles(&controller), vec!["Docs"]);
+    // Trashed prerequisites don't hold anything up
+    controller.trash_note(&docs.id);
+    assert!(controller.blocked_by(&release.id).is_empty());
+    controller.undo();
+    controller.undo();
+    assert_eq!(titles(&controller), vec!["Tests", "Docs"]);
+    
+    assert!(matches!(
+        controller.create_relation(&tests.id, &release.id, RelationType::DependsOn, 1.0, None),
+        Err(RelationError::DependencyCycle(_))
+    ));
+}
+
+#[test]
+fn test_auto_discover_saves_the_graph_only_when_relations_are_found() {
+    let mut first = note("Rust async", "tokio runtime tasks");
+    first.add_tag("rust".to_string());
+    let mut second = note("Rust channels", "tokio mpsc tasks");
+    second.add_tag("rust".to_string());
+    let (mut controller, mut rx) = controller_with(vec![first, second]);
+    
+    let discovered = controller.auto_discover_relations();
+    assert!(discovered > 0);
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveGraph(graph)] if graph.relations.len() == discovered));
+    
+    assert_eq!(controller.auto_discover_relations(), 0);
+    assert!(drain(&mut rx).is_empty());
+}
+
+#[test]
+fn test_saved_searches_stay_live_and_are_persisted() {
+    let mut urgent = note("Ship release", "Tag and publish");
+    urgent.priority = Priority::Urgent;
+    let (mut controller, mut rx) = controller_with(vec![urgent.clone(), note("Groceries", "Milk")]);
+    
+    // Only a valid, non-empty search under a name can be saved
+    assert_eq!(controller.save_search("Urgent"), None);
+    controller.set_search_text("priority:");
+    assert_eq!(controller.save_search("Urgent"), None);
+    controller.set_search_text("priority:urgent");
+    assert_eq!(controller.save_search("  "), None);
+    let saved_id = controller.save_search("Urgent").unwrap();
+    assert_eq!(controller.active_saved_search().map(|saved| saved.name.as_str()), Some("Urgent"));
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveSavedSearches(saved)] if saved.len() == 1));
+    
+    // Results follow note changes and ignore the status filter
+    controller.set_search_text("");
+    controller.set_filter_status(Some(WorkflowStatus::Done));
+    assert_eq!(controller.active_saved_search(), None);
+    assert_eq!(controller.saved_search_results(&saved_id).len(), 1);
+    let mut groceries = controller.notes().iter().find(|note| note.title == "Groceries").unwrap().clone();
+    groceries.priority = Priority::Urgent;
+    controller.update_note(groceries);
+    assert_eq!(controller.saved_search_results(&saved_id).len(), 2);
+    controller.trash_note(&urgent.id);
+    assert_eq!(controller.saved_search_results(&saved_id).len(), 1);
+    
+    // Saving under an existing name replaces its query
+    controller.set_search_text("milk");
+    assert_eq!(controller.save_search("urgent"), Some(saved_id.clone()));
+    assert_eq!(controller.saved_searches().len(), 1);
+    assert_eq!(controller.saved_searches()[0].query, "milk");
+    
+    controller.set_search_text("");
+    assert!(controller.apply_saved_search(&saved_id));
+    assert_eq!(controller.search_text(), "milk");
+    assert!(!controller.apply_saved_search("missing"));
+    
+    drain(&mut rx);
+    assert!(controller.delete_saved_search(&saved_id));
+    assert!(!controller.delete_saved_search(&saved_id));
+    assert!(controller.saved_searches().is_empty());
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveSavedSearches(saved)] if saved.is_empty()));
+}
+
+#[test]
+fn test_filter_composes_with_search() {
+    let mut rust = note("Rust ownership", "Borrowing rules");
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    rust.add_tag("rust".to_string());
+    let mut slint = note("Slint layouts", "Rows and columns in rust");
+    slint.set_workflow_status(WorkflowStatus::Review);
+    slint.priority = Priority::Urgent;
+    let groceries = note("Groceries", "Milk and bread");
+    let (mut controller, _rx) = controller_with(vec![rust, slint, groceries]);
+    
+    assert_eq!(controller.all_tags(), vec!["rust"]);
+    assert_eq!(controller.all_colors().len(), 1);
+    
+    controller.filter_mut().toggle_status(WorkflowStatus::Todo);
+    controller.filter_mut().toggle_status(WorkflowStatus::Review);
+    controller.set_search_text("rust");
+    assert_eq!(controller.visible_notes().len(), 2);
+    
+    controller.filter_mut().toggle_priority(Priority::Urgent);
+    let titles: Vec<&str> = controller.visible_notes().iter().map(|note| note.title.as_str()).collect();
+    assert_eq!(titles, vec!["Slint layouts"]);
+    
+    // The single-status shortcut replaces only the statuses
+    controller.set_filter_status(Some(WorkflowStatus::Todo));
+    assert!(controller.visible_notes().is_empty());
+    controller.set_filter_status(None);
+    assert_eq!(controller.filter().priorities, vec![Priority::Urgent]);
+    assert_eq!(controller.visible_notes().len(), 1);
+}
+
+#[test]
+fn test_sort_order_applies_to_visible_notes_and_is_remembered() {
+    let mut low = note("Rust later", "Maybe rust");
+    low.priority = Priority::Low;
+    let mut urgent = note("Rust now", "Rust rust rust");
+    urgent.priority = Priority::Urgent;
+    let mut high = note("Groceries", "Milk");
+    high.priority = Priority::High;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let settings = WorkspaceSettings { sort_order: SortOrder::Title };
+    let mut controller = AppController::new(vec![low, urgent, high], KnowledgeGraph::new(), tx).with_settings(settings);
+    
+    let titles = |controller: &AppController| -> Vec<String> {
+        controller.visible_notes().iter().map(|note| note.title.clone()).collect()
+    };
+    assert_eq!(titles(&controller), vec!["Groceries", "Rust later", "Rust now"]);
+    
+    controller.set_sort_order(SortOrder::Priority);
+    assert_eq!(titles(&controller), vec!["Rust now", "Groceries", "Rust later"]);
+    assert!(matches!(drain(&mut rx).as_slice(), [StorageOp::SaveSettings(settings)] if settings.sort_order == SortOrder::Priority));
+    controller.set_sort_order(SortOrder::Priority);
+    assert!(drain(&mut rx).is_empty());
+    
+    // Sorting applies to search results too; relevance ranks them otherwise
+    controller.set_search_text("rust");
+    assert_eq!(titles(&controller), vec!["Rust now", "Rust later"]);
+    controller.set_sort_order(SortOrder::Title);
+    assert_eq!(titles(&controller), vec!["Rust later", "Rust now"]);
+    controller.set_sort_order(SortOrder::Relevance);
+    assert_eq!(titles(&controller), vec!["Rust now", "Rust later"]);
+}
+
+#[test]
+fn test_graph_layout_covers_the_live_notes() {
+    let (first, second) = (note("First", ""), note("Second", ""));
+    let (mut controller, _rx) = controller_with(vec![first.clone(), second.clone()]);
+    let settings = ForceLayout::new(600.0, 400.0);
+    
+    let layout = controller.graph_layout(&settings);
+    assert_eq!(layout.nodes.len(), 2);
+    assert!(layout.node(&first.id).is_some() && layout.node(&second.id).is_some());
+    
+    assert!(controller.trash_note(&second.id));
+    let layout = controller.graph_layout(&settings);
+    assert_eq!(layout.nodes.len(), 1);
+    assert!(layout.node(&second.id).is_none());
+}
+
+#[tokio::test]
+async fn test_storage_writer_shutdown_applies_queued_ops_while_senders_are_alive() {
+    let data_dir = temp_data_dir();
+    let storage = Arc::new(NoteStorage::with_data_dir(data_dir.clone()).await);
+    let (tx, writer) = spawn_storage_writer(storage.clone());
+    let saved = note("Queued", "Written before exit");
+    tx.send(StorageOp::Save(Box::new(saved.clone()))).unwrap();
+    
+    // `tx` stays alive, like the senders held by the window's callbacks
+    tokio::time::timeout(Duration::from_secs(5), writer.shutdown()).await.expect("writer hung").unwrap();
+    assert_eq!(storage.load_notes().await.unwrap()[0].id, saved.id);
+    assert!(tx.send(StorageOp::Delete(saved.id.clone())).is_err());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_storage_writer_shutdown_flushes_debounced_writes() {
+    let data_dir = temp_data_dir();
+    // A debounce far longer than the test, so only the shutdown can write the change
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await.with_flush_delay(Duration::from_secs(60));
+    let (tx, writer) = spawn_storage_writer(Arc::new(storage.clone()));
+    let saved = note("Pending", "Still debounced at exit");
+    tx.send(StorageOp::Save(Box::new(saved.clone()))).unwrap();
+    
+    writer.shutdown().await.unwrap();
+    assert!(!storage.has_pending_changes());
+    let reloaded = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let notes = reloaded.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].id, saved.id);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_restoring_a_revision_is_an_undoable_edit() {
+    let data_dir = temp_data_dir();
+    let storage = Arc::new(NoteStorage::with_data_dir(data_dir.clone()).await);
+    let (tx, writer) = spawn_storage_writer(storage.clone());
+    let mut controller = AppController::new(Vec::new(), KnowledgeGraph::new(), tx);
+    let original = note("Plan", "Step one");
+    controller.create_note(original.clone());
+    let mut edited = original.clone();
+    edited.update_content("Plan".to_string(), "Step one\nStep two".to_string());
+    controller.update_note(edited);
+    
+    // The edit queued just before is already recorded
+    let revisions = controller.revisions(&original.id).await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(
+        controller.diff_revisions(&original.id, &revisions[0]).unwrap(),
+        vec![
+            DiffLine::Unchanged("Plan".to_string()),
+            DiffLine::Unchanged("Step one".to_string()),
+            DiffLine::Added("Step two".to_string()),
+        ]
+    );
+    assert!(!controller.restore_revision("another note", &revisions[0]));
+    
+    // The restore goes through the controller, so the search index, storage and undo follow
+    assert!(controller.restore_revision(&original.id, &revisions[0]));
+    assert_eq!(controller.note(&original.id).unwrap().content, "Step one");
+    controller.set_search_text("two");
+    assert!(controller.visible_notes().is_empty());
+    assert_eq!(controller.revisions(&original.id).await.unwrap().len(), 3);
+    assert_eq!(storage.get(&original.id).await.unwrap().unwrap().content, "Step one");
+    assert_eq!(controller.undo(), Some(CommandKind::Edit));
+    assert_eq!(controller.note(&original.id).unwrap().content, "Step one\nStep two");
+    
+    writer.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(data_dir);
+}
@@ -0,0 +1,110 @@
+use chrono::NaiveDate;
+use rust_slint_sticky::filter::{DateField, DateRange, FilterCondition, NoteFilter};
+use rust_slint_sticky::note::{Priority, SerializableColor, WorkflowStatus};
+use rust_slint_sticky::timestamp::{parse_date, parse_timestamp};
+use rust_slint_sticky::AppNote;
+
+const BLUE: SerializableColor = SerializableColor { red: 33, green: 150, blue: 243 };
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+fn sample_notes() -> Vec<AppNote> {
+    let mut rust = AppNote::new("Rust ownership".to_string(), String::new());
+    rust.add_tag("rust".to_string());
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    rust.priority = Priority::High;
+    rust.created_at = parse_timestamp("2026-09-01 10:00").unwrap();
+    rust.updated_at = parse_timestamp("2026-10-10 08:30").unwrap();
+    rust.due_date = parse_date("2026-10-20");
+    
+    let mut slint = AppNote::new("Slint layouts".to_string(), String::new());
+    slint.add_tag("ui".to_string());
+    slint.set_workflow_status(WorkflowStatus::Review);
+    slint.priority = Priority::Urgent;
+    slint.color = BLUE;
+    slint.created_at = parse_timestamp("2026-10-05 09:00").unwrap();
+    slint.updated_at = parse_timestamp("2026-10-05 09:00").unwrap();
+    
+    let mut groceries = AppNote::new("Groceries".to_string(), String::new());
+    groceries.add_tag("home".to_string());
+    groceries.priority = Priority::Low;
+    groceries.created_at = parse_timestamp("2026-10-12 18:00").unwrap();
+    groceries.updated_at = parse_timestamp("2026-10-16 07:15").unwrap();
+    
+    vec![rust, slint, groceries]
+}
+
+fn matching<'a>(filter: &NoteFilter, notes: &'a [AppNote]) -> Vec<&'a str> {
+    notes.iter().filter(|note| filter.matches(note)).map(|note| note.title.as_str()).collect()
+}
+
+#[test]
+fn test_values_of_a_dimension_are_alternatives_and_dimensions_combine() {
+    let notes = sample_notes();
+    let mut filter = NoteFilter::new();
+    assert!(filter.is_empty());
+    assert_eq!(matching(&filter, &notes).len(), 3);
+    
+    assert!(filter.toggle_status(WorkflowStatus::Todo));
+    assert!(filter.toggle_status(WorkflowStatus::Review));
+    assert_eq!(matching(&filter, &notes), vec!["Rust ownership", "Slint layouts"]);
+    
+    filter.toggle_priority(Priority::Urgent);
+    assert_eq!(matching(&filter, &notes), vec!["Slint layouts"]);
+    
+    filter.toggle_tag(" RUST ");
+    assert!(matching(&filter, &notes).is_empty());
+    filter.toggle_tag("ui");
+    filter.toggle_color(BLUE);
+    assert_eq!(matching(&filter, &notes), vec!["Slint layouts"]);
+    
+    // Toggling again deselects
+    assert!(!filter.toggle_priority(Priority::Urgent));
+    assert!(!filter.toggle_color(BLUE));
+    assert!(!filter.toggle_tag("ui"));
+    assert_eq!(matching(&filter, &notes), vec!["Rust ownership"]);
+}
+
+#[test]
+fn test_date_ranges_are_inclusive_days() {
+    let notes = sample_notes();
+    let mut filter = NoteFilter::new();
+    
+    filter.set_date_range(DateField::Created, DateRange::new(Some(date("2026-10-01")), None));
+    assert_eq!(matching(&filter, &notes), vec!["Slint layouts", "Groceries"]);
+    
+    filter.set_date_range(DateField::Updated, DateRange::new(None, Some(date("2026-10-05"))));
+    assert_eq!(matching(&filter, &notes), vec!["Slint layouts"]);
+    
+    // Notes without a due date never fall into a due range
+    let mut due = NoteFilter::new();
+    due.set_date_range(DateField::Due, DateRange::new(Some(date("2026-10-20")), Some(date("2026-10-20"))));
+    assert_eq!(matching(&due, &notes), vec!["Rust ownership"]);
+    
+    assert_eq!(DateRange::parse("", "").unwrap(), DateRange::default());
+    assert_eq!(DateRange::parse(" 2026-10-01", "2026-10-31 ").unwrap().label(), "2026-10-01 – 2026-10-31");
+    assert!(DateRange::parse("today", "").unwrap().from.is_some());
+    assert!(DateRange::parse("yesterday", "").unwrap_err().contains("not a date"));
+    assert!(DateRange::parse("+1é", "").unwrap_err().contains("not a date"));
+    assert!(DateRange::parse("2026-10-31", "2026-10-01").unwrap_err().contains("after it ends"));
+}
+
+#[test]
+fn test_conditions_list_and_remove_chips() {
+    let mut filter = NoteFilter::new();
+    filter.toggle_status(WorkflowStatus::InProgress);
+    filter.toggle_tag("rust");
+    filter.toggle_color(BLUE);
+    filter.set_date_range(DateField::Due, DateRange::new(None, Some(date("2026-10-31"))));
+    
+    let labels: Vec<String> = filter.conditions().iter().map(FilterCondition::label).collect();
+    assert_eq!(labels, vec!["Status: In Progress", "#rust", "Color: #2196f3", "Due: until 2026-10-31"]);
+    
+    for condition in filter.conditions() {
+        assert!(filter.remove(&condition));
+        assert!(!filter.remove(&condition));
+    }
+    assert!(filter.is_empty());
+}
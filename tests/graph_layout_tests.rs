@@ -0,0 +1,104 @@
+use rust_slint_sticky::graph_layout::{ForceLayout, GraphLayout};
+use rust_slint_sticky::note::{KnowledgeGraph, RelationType};
+use rust_slint_sticky::AppNote;
+
+fn notes(count: usize) -> Vec<AppNote> {
+    (0..count).map(|i| AppNote::new(format!("Note {}", i), String::new())).collect()
+}
+
+fn distance(layout: &GraphLayout, a: &AppNote, b: &AppNote) -> f32 {
+    let (a, b) = (layout.node(&a.id).unwrap(), layout.node(&b.id).unwrap());
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[test]
+fn test_layout_places_every_note_inside_the_margins() {
+    let notes = notes(12);
+    let mut graph = KnowledgeGraph::new();
+    for pair in notes.windows(2) {
+        graph.add_relation(pair[0].id.clone(), pair[1].id.clone(), RelationType::RelatedTo, 0.5);
+    }
+    let settings = ForceLayout::new(800.0, 600.0).with_margin(50.0);
+    
+    let layout = graph.layout(&notes, &settings);
+    assert_eq!(layout.nodes.len(), notes.len());
+    for (node, note) in layout.nodes.iter().zip(&notes) {
+        assert_eq!(node.note_id, note.id);
+        assert!((50.0..=750.0).contains(&node.x) && (50.0..=550.0).contains(&node.y), "{:?}", node);
+    }
+    // Notes are spread out rather than piled up
+    for (i, a) in notes.iter().enumerate() {
+        for b in &notes[i + 1..] {
+            assert!(distance(&layout, a, b) > 40.0);
+        }
+    }
+    // The same graph always gets the same layout
+    assert_eq!(graph.layout(&notes, &settings), layout);
+}
+
+#[test]
+fn test_related_notes_end_up_closer_together() {
+    let notes = notes(6);
+    let mut graph = KnowledgeGraph::new();
+    graph.add_relation(notes[0].id.clone(), notes[1].id.clone(), RelationType::DependsOn, 1.0);
+    graph.add_relation(notes[2].id.clone(), notes[1].id.clone(), RelationType::Extends, 1.0);
+    graph.add_relation(notes[3].id.clone(), notes[4].id.clone(), RelationType::RelatedTo, 0.9);
+    
+    let layout = ForceLayout::new(1000.0, 700.0).layout(&graph, &notes);
+    let related = distance(&layout, &notes[0], &notes[1]).max(distance(&layout, &notes[3], &notes[4]));
+    for unrelated in [(0, 3), (0, 4), (1, 4), (2, 3), (0, 5), (3, 5)] {
+        assert!(related < distance(&layout, &notes[unrelated.0], &notes[unrelated.1]), "{:?}", unrelated);
+    }
+}
+
+#[test]
+fn test_a_strength_that_is_not_a_number_pulls_like_the_weakest() {
+    let notes = notes(4);
+    let graph_with = |strength: f32| {
+        let mut graph = KnowledgeGraph::new();
+        for pair in notes.windows(2) {
+            graph.add_relation(pair[0].id.clone(), pair[1].id.clone(), RelationType::RelatedTo, 0.5);
+        }
+        graph.relations[1].strength = strength;
+        graph
+    };
+    let settings = ForceLayout::new(400.0, 300.0);
+    
+    let weakest = graph_with(0.0).layout(&notes, &settings);
+    assert_eq!(graph_with(f32::NAN).layout(&notes, &settings), weakest);
+    assert_eq!(graph_with(f32::NEG_INFINITY).layout(&notes, &settings), weakest);
+}
+
+#[test]
+fn test_layout_of_no_notes_or_one() {
+    let graph = KnowledgeGraph::new();
+    assert!(graph.layout(&[], &ForceLayout::new(400.0, 300.0)).nodes.is_empty());
+    
+    // Relations to notes that aren't laid out are left out
+    let notes = notes(2);
+    let mut graph = KnowledgeGraph::new();
+    graph.add_relation(notes[0].id.clone(), notes[1].id.clone(), RelationType::RelatedTo, 1.0);
+    let layout = graph.layout(&notes[..1], &ForceLayout::new(400.0, 300.0));
+    assert_eq!(layout.nodes.len(), 1);
+    assert!(layout.node(&notes[1].id).is_none());
+    // A lone note settles in the middle
+    let node = &layout.nodes[0];
+    assert!((node.x - 200.0).abs() < 20.0 && (node.y - 150.0).abs() < 20.0, "{:?}", node);
+}
+
+#[test]
+fn test_large_graphs_are_spread_over_the_drawing() {
+    let notes = notes(1000);
+    let mut graph = KnowledgeGraph::new();
+    for pair in notes.chunks(2) {
+        graph.add_relation(pair[0].id.clone(), pair[1].id.clone(), RelationType::RelatedTo, 1.0);
+    }
+    
+    let layout = graph.layout(&notes, &ForceLayout::new(1200.0, 900.0));
+    assert_eq!(layout.nodes.len(), notes.len());
+    let (xs, ys): (Vec<f32>, Vec<f32>) = layout.nodes.iter().map(|node| (node.x, node.y)).unzip();
+    let spread = |values: &[f32]| values.iter().cloned().fold(f32::MIN, f32::max) - values.iter().cloned().fold(f32::MAX, f32::min);
+    // However many notes there are, they fill the drawing
+    assert!(spread(&xs) > 750.0 && spread(&ys) > 750.0, "{} by {}", spread(&xs), spread(&ys));
+    assert!(layout.nodes.iter().all(|node| (40.0..=1160.0).contains(&node.x) && (40.0..=860.0).contains(&node.y)));
+}
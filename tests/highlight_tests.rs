@@ -0,0 +1,69 @@
+use rust_slint_sticky::search::TypoTolerance;
+use rust_slint_sticky::{AppNote, HighlightedText, NoteMatch};
+
+fn highlighted(text: &HighlightedText) -> Vec<&str> {
+    text.highlights.iter().map(|range| &text.text[range.clone()]).collect()
+}
+
+#[test]
+fn test_find_highlights_matching_words() {
+    let text = HighlightedText::find("Rust's borrow checker, rustc and Slnit", &["rust", "slint"], TypoTolerance::Auto);
+    assert_eq!(highlighted(&text), vec!["Rust", "rustc", "Slnit"]);
+    
+    let strict = HighlightedText::find("Rust's borrow checker, rustc and Slnit", &["rust", "slint"], TypoTolerance::Off);
+    assert_eq!(highlighted(&strict), vec!["Rust", "rustc"]);
+    
+    assert!(!HighlightedText::find("Nothing here", &["rust"], TypoTolerance::Auto).is_highlighted());
+}
+
+#[test]
+fn test_segments_alternate_plain_and_highlighted_text() {
+    let text = HighlightedText::find("learn rust today", &["rust"], TypoTolerance::Off);
+    assert_eq!(text.segments(), vec![("learn ", false), ("rust", true), (" today", false)]);
+    
+    let whole = HighlightedText::find("rust", &["rust"], TypoTolerance::Off);
+    assert_eq!(whole.segments(), vec![("rust", true)]);
+    assert!(HighlightedText::plain("").segments().is_empty());
+}
+
+#[test]
+fn test_snippet_shows_context_around_the_first_match() {
+    let content = "A long introduction that rambles on for a while before it finally gets to the point:\nthe Slint layout engine sizes everything, and then it rambles some more until the end.";
+    let text = HighlightedText::find(content, &["layout"], TypoTolerance::Off);
+    let snippet = text.snippet(60);
+    
+    assert!(snippet.text.starts_with('…'));
+    assert!(snippet.text.ends_with('…'));
+    assert!(!snippet.text.contains('\n'));
+    assert!(snippet.text.chars().count() <= 62);
+    assert_eq!(highlighted(&snippet), vec!["layout"]);
+    // Cut at word boundaries
+    assert!(snippet.text.contains(" the Slint layout engine "));
+    assert!(!snippet.text.trim_start_matches('…').starts_with(' '));
+    
+    // Short text is kept whole
+    let short = HighlightedText::find("Slint layouts\nare flexible", &["layout"], TypoTolerance::Off).snippet(60);
+    assert_eq!(short.text, "Slint layouts are flexible");
+    assert_eq!(highlighted(&short), vec!["layouts"]);
+}
+
+#[test]
+fn test_note_match_falls_back_to_matching_tags() {
+    let mut note = AppNote::new("Weekend plans".to_string(), "Hiking and a long lunch".to_string());
+    note.add_tag("outdoors".to_string());
+    note.add_tag("family".to_string());
+    
+    let by_content = NoteMatch::new(&note, &["hiking"], TypoTolerance::Auto);
+    assert_eq!(by_content.note_id, note.id);
+    assert!(!by_content.title.is_highlighted());
+    assert_eq!(highlighted(&by_content.snippet), vec!["Hiking"]);
+    
+    let by_tag = NoteMatch::new(&note, &["outdoors"], TypoTolerance::Auto);
+    assert_eq!(by_tag.snippet.text, "#family #outdoors");
+    assert_eq!(highlighted(&by_tag.snippet), vec!["outdoors"]);
+    
+    let by_title = NoteMatch::new(&note, &["weekend"], TypoTolerance::Auto);
+    assert_eq!(highlighted(&by_title.title), vec!["Weekend"]);
+    assert_eq!(by_title.snippet.text, "Hiking and a long lunch");
+    assert!(!by_title.snippet.is_highlighted());
+}
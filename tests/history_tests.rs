@@ -0,0 +1,76 @@
+use rust_slint_sticky::history::{self, diff_lines, DiffLine, NoteRevision};
+use rust_slint_sticky::note::WorkflowStatus;
+use rust_slint_sticky::AppNote;
+
+#[test]
+fn test_diff_of_identical_texts_is_unchanged() {
+    let diff = diff_lines("a\nb", "a\nb");
+    assert_eq!(diff, vec![DiffLine::Unchanged("a".to_string()), DiffLine::Unchanged("b".to_string())]);
+}
+
+#[test]
+fn test_diff_reports_added_and_removed_lines() {
+    let diff = diff_lines("- milk\n- bread\n- eggs", "- milk\n- butter\n- eggs\n- coffee");
+    
+    assert_eq!(
+        diff,
+        vec![
+            DiffLine::Unchanged("- milk".to_string()),
+            DiffLine::Removed("- bread".to_string()),
+            DiffLine::Added("- butter".to_string()),
+            DiffLine::Unchanged("- eggs".to_string()),
+            DiffLine::Added("- coffee".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_against_empty_text() {
+    assert_eq!(diff_lines("", "new"), vec![DiffLine::Added("new".to_string())]);
+    assert_eq!(diff_lines("old", ""), vec![DiffLine::Removed("old".to_string())]);
+}
+
+#[test]
+fn test_diff_revisions_includes_title() {
+    let mut note = AppNote::new("Old title".to_string(), "Body".to_string());
+    let old = NoteRevision::from_note(&note, vec!["created".to_string()]);
+    note.update_content("New title".to_string(), "Body".to_string());
+    let new = NoteRevision::from_note(&note, vec!["title".to_string()]);
+    
+    let diff = history::diff_revisions(&old, &new);
+    assert_eq!(
+        diff,
+        vec![
+            DiffLine::Removed("Old title".to_string()),
+            DiffLine::Added("New title".to_string()),
+            DiffLine::Unchanged("Body".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_changed_fields_ignores_layout() {
+    let before = AppNote::new("Title".to_string(), "Body".to_string());
+    let mut after = before.clone();
+    after.set_position(300.0, 300.0);
+    after.set_size(400.0, 400.0);
+    assert!(history::changed_fields(Some(&before), &after).is_empty());
+    
+    after.set_workflow_status(WorkflowStatus::Done);
+    after.add_tag("finished".to_string());
+    assert_eq!(history::changed_fields(Some(&before), &after), vec!["tags", "workflow_status"]);
+    assert_eq!(history::changed_fields(None, &after), vec!["created"]);
+}
+
+#[test]
+fn test_retention_keeps_newest_revisions() {
+    let note = AppNote::new("Title".to_string(), "Body".to_string());
+    let mut revisions: Vec<NoteRevision> = (0..5)
+        .map(|i| NoteRevision::from_note(&note, vec![format!("change {}", i)]))
+        .collect();
+    
+    history::enforce_retention(&mut revisions, 2);
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].changed_fields, vec!["change 3"]);
+    assert_eq!(revisions[1].changed_fields, vec!["change 4"]);
+}
@@ -1,16 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use rust_slint_sticky::note::{StickyNote, SerializableColor};
+    use rust_slint_sticky::note::{AppNote, NoteEdit, SerializableColor};
 
     #[test]
     fn test_create_new_note() {
-        let note = StickyNote::new("Test Title".to_string(), "Test Content".to_string());
+        let note = AppNote::new("Test Title".to_string(), "Test Content".to_string());
         
         assert_eq!(note.title, "Test Title");
         assert_eq!(note.content, "Test Content");
         assert!(!note.id.is_empty());
-        assert!(!note.created_at.is_empty());
-        assert!(!note.updated_at.is_empty());
+        assert_eq!(note.created_at, note.updated_at);
         assert_eq!(note.x, 10.0);
         assert_eq!(note.y, 10.0);
         assert_eq!(note.width, 220.0);
@@ -30,8 +29,8 @@ mod tests {
 
     #[test]
     fn test_note_update_content() {
-        let mut note = StickyNote::new("Original".to_string(), "Original Content".to_string());
-        let original_created_at = note.created_at.clone();
+        let mut note = AppNote::new("Original".to_string(), "Original Content".to_string());
+        let original_created_at = note.created_at;
         
         // Sleep a bit to ensure timestamp difference
         std::thread::sleep(std::time::Duration::from_millis(10));
@@ -41,14 +40,12 @@ mod tests {
         assert_eq!(note.title, "Updated Title");
         assert_eq!(note.content, "Updated Content");
         assert_eq!(note.created_at, original_created_at); // Should not change
-        assert!(!note.updated_at.is_empty()); // Should have a valid timestamp
-        // Note: Due to time format precision (minutes), timestamps might be the same
-        // This is expected behavior in real usage
+        assert!(note.updated_at > original_created_at);
     }
 
     #[test]
     fn test_note_set_color() {
-        let mut note = StickyNote::new("Test".to_string(), "Test".to_string());
+        let mut note = AppNote::new("Test".to_string(), "Test".to_string());
         let new_color = SerializableColor { red: 100, green: 150, blue: 200 };
         
         note.set_color(new_color);
@@ -60,7 +57,7 @@ mod tests {
 
     #[test]
     fn test_note_set_position() {
-        let mut note = StickyNote::new("Test".to_string(), "Test".to_string());
+        let mut note = AppNote::new("Test".to_string(), "Test".to_string());
         
         note.set_position(100.0, 200.0);
         
@@ -70,7 +67,7 @@ mod tests {
 
     #[test]
     fn test_note_set_size() {
-        let mut note = StickyNote::new("Test".to_string(), "Test".to_string());
+        let mut note = AppNote::new("Test".to_string(), "Test".to_string());
         
         note.set_size(300.0, 250.0);
         
@@ -79,10 +76,10 @@ mod tests {
     }
 
     #[test]
-    fn test_slint_note_conversion() {
-        let original_note = StickyNote::new("Test Title".to_string(), "Test Content".to_string());
-        let slint_note = original_note.to_slint_note();
-        let converted_back = StickyNote::from_slint_note(&slint_note);
+    fn test_note_serialization_round_trip() {
+        let original_note = AppNote::new("Test Title".to_string(), "Test Content".to_string());
+        let json = serde_json::to_string(&original_note).unwrap();
+        let converted_back: AppNote = serde_json::from_str(&json).unwrap();
         
         assert_eq!(original_note.id, converted_back.id);
         assert_eq!(original_note.title, converted_back.title);
@@ -92,4 +89,50 @@ mod tests {
         assert_eq!(original_note.width, converted_back.width);
         assert_eq!(original_note.height, converted_back.height);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_checklist_drives_completion() {
+        let mut note = AppNote::new("Release".to_string(), "".to_string());
+        note.completion_percentage = 30.0;
+        
+        let tests = note.add_checklist_item("Run tests".to_string());
+        let notes = note.add_checklist_item("Write notes".to_string());
+        note.add_checklist_item("Tag".to_string());
+        note.add_checklist_item("Publish".to_string());
+        assert_eq!(note.completion_percentage, 0.0);
+        
+        assert!(note.toggle_checklist_item(&tests));
+        assert!(note.toggle_checklist_item(&notes));
+        assert_eq!(note.checklist_progress(), (2, 4));
+        assert_eq!(note.completion_percentage, 50.0);
+        
+        assert!(note.move_checklist_item(&notes, 0));
+        assert_eq!(note.checklist[0].text, "Write notes");
+        assert!(note.remove_checklist_item(&tests));
+        assert!(!note.toggle_checklist_item(&tests));
+        assert!((note.completion_percentage - 100.0 / 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_checklist_text_round_trips_and_keeps_ids() {
+        let mut note = AppNote::new("Groceries".to_string(), "".to_string());
+        let milk = note.add_checklist_item("Milk".to_string());
+        note.add_checklist_item("Bread".to_string());
+        note.toggle_checklist_item(&milk);
+        
+        let text = NoteEdit::from_note(&note).checklist_text();
+        assert_eq!(text, "[x] Milk\n[ ] Bread");
+        
+        let parsed = NoteEdit::parse_checklist("- [ ] Eggs\n[x] Milk\n\n[X] Bread", &note.checklist);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!((parsed[0].text.as_str(), parsed[0].done), ("Eggs", false));
+        assert_eq!(parsed[1].id, milk);
+        assert_eq!(parsed[2].id, note.checklist[1].id);
+        assert!(parsed[2].done);
+        
+        let mut edit = NoteEdit::from_note(&note);
+        edit.checklist = parsed;
+        assert!(note.apply_edit(&edit));
+        assert_eq!(note.completion_percentage, 100.0 * 2.0 / 3.0);
+    }
+}
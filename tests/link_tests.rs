@@ -0,0 +1,76 @@
+mod common;
+
+use common::note;
+use rust_slint_sticky::links::{linked_titles, rename_links, resolve};
+use rust_slint_sticky::note::{KnowledgeGraph, RelationOrigin, RelationType};
+
+#[test]
+fn test_links_are_found_once_and_not_in_code() {
+    let content = "See [[Roadmap]] and [[roadmap|the plan]].\n`[[Not a link]]`\n```\n[[Nor this]]\n```\n- [[ Budget ]] [[]]";
+    
+    assert_eq!(linked_titles(content), vec!["Roadmap", "Budget"]);
+    assert!(linked_titles("[single] [[unclosed").is_empty());
+}
+
+#[test]
+fn test_titles_resolve_case_insensitively_to_the_oldest_note() {
+    let source = note("Source", "[[roadmap]]");
+    let original = note("Roadmap", "");
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let newer = note("ROADMAP", "");
+    let notes = vec![source.clone(), newer, original.clone()];
+    
+    assert_eq!(resolve(&notes, &source.id, "roadmap").unwrap().id, original.id);
+    // A note never links to itself
+    assert!(resolve(&notes, &source.id, "Source").is_none());
+    assert!(resolve(&notes, &source.id, "Missing").is_none());
+}
+
+#[test]
+fn test_renaming_keeps_labels() {
+    let content = "[[Plan]], [[plan|our plan]] and [[Planet]]";
+    
+    assert_eq!(
+        rename_links(content, "Plan", "Roadmap").as_deref(),
+        Some("[[Roadmap]], [[Roadmap|our plan]] and [[Planet]]")
+    );
+    assert_eq!(rename_links(content, "Budget", "Costs"), None);
+}
+
+#[test]
+fn test_renaming_leaves_code_alone() {
+    let content = "[[Plan]] but not `[[Plan]]`\n```\n[[Plan]]\n```\n\\`[[Plan]]\\`";
+    
+    assert_eq!(
+        rename_links(content, "Plan", "Roadmap").as_deref(),
+        Some("[[Roadmap]] but not `[[Plan]]`\n```\n[[Plan]]\n```\n\\`[[Roadmap]]\\`")
+    );
+    assert_eq!(rename_links("`[[Plan]]`", "Plan", "Roadmap"), None);
+}
+
+#[test]
+fn test_sync_links_follows_content_and_keeps_other_relations() {
+    let mut source = note("Source", "Needs [[Target]] and [[Missing]]");
+    let target = note("Target", "");
+    let mut graph = KnowledgeGraph::new();
+    graph.add_relation(target.id.clone(), source.id.clone(), RelationType::DependsOn, 1.0);
+    
+    assert!(graph.sync_links(&[source.clone(), target.clone()]));
+    assert_eq!(graph.relations.len(), 2);
+    let link = &graph.relations[1];
+    assert_eq!((link.from_note_id.as_str(), link.to_note_id.as_str()), (source.id.as_str(), target.id.as_str()));
+    assert_eq!(link.relation_type, RelationType::References);
+    assert_eq!(link.origin, RelationOrigin::Link);
+    assert_eq!(graph.backlinks(&target.id), vec![source.id.as_str()]);
+    
+    // Unchanged links keep their relation
+    let link_id = link.id.clone();
+    assert!(!graph.sync_links(&[source.clone(), target.clone()]));
+    assert_eq!(graph.relations[1].id, link_id);
+    
+    source.content = "No links any more".to_string();
+    assert!(graph.sync_links(&[source, target.clone()]));
+    assert_eq!(graph.relations.len(), 1);
+    assert_eq!(graph.relations[0].origin, RelationOrigin::Manual);
+    assert!(graph.backlinks(&target.id).is_empty());
+}
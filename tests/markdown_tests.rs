@@ -0,0 +1,94 @@
+use rust_slint_sticky::markdown::{parse, parse_inline, wrap, BlockKind, Span};
+
+fn kinds(text: &str) -> Vec<(BlockKind, usize)> {
+    parse(text).into_iter().map(|block| (block.kind, block.depth)).collect()
+}
+
+#[test]
+fn test_lines_become_blocks() {
+    let content = "# Plan\nIntro line\n\n- milk\n  - oat\n1. first\n- [ ] call Bob\n- [x] book room\n> quoted\n---\n```\nlet x = **1**;\n```";
+    
+    assert_eq!(
+        kinds(content),
+        vec![
+            (BlockKind::Heading(1), 0),
+            (BlockKind::Paragraph, 0),
+            (BlockKind::Bullet, 0),
+            (BlockKind::Bullet, 1),
+            (BlockKind::Numbered(1), 0),
+            (BlockKind::Task(false), 0),
+            (BlockKind::Task(true), 0),
+            (BlockKind::Quote, 0),
+            (BlockKind::Rule, 0),
+            (BlockKind::Code, 0),
+        ]
+    );
+    
+    let blocks = parse(content);
+    assert_eq!(blocks[0].plain_text(), "Plan");
+    assert_eq!(blocks[3].plain_text(), "oat");
+    assert_eq!(blocks[4].marker(), "1.");
+    assert_eq!(blocks[6].marker(), "☑");
+    // Code is kept verbatim
+    assert_eq!(blocks[9].plain_text(), "let x = **1**;");
+    assert!(blocks[9].spans[0].code);
+    // Not Markdown without the space
+    assert_eq!(kinds("#hashtag"), vec![(BlockKind::Paragraph, 0)]);
+}
+
+#[test]
+fn test_inline_styles_and_links() {
+    let spans = parse_inline("Use **bold**, *italic* and `code` — see [docs](https://slint.dev).");
+    
+    assert_eq!(
+        spans,
+        vec![
+            Span::plain("Use "),
+            Span { bold: true, ..Span::plain("bold") },
+            Span::plain(", "),
+            Span { italic: true, ..Span::plain("italic") },
+            Span::plain(" and "),
+            Span { code: true, ..Span::plain("code") },
+            Span::plain(" — see "),
+            Span { link: Some("https://slint.dev".to_string()), ..Span::plain("docs") },
+            Span::plain("."),
+        ]
+    );
+}
+
+#[test]
+fn test_unmatched_markers_stay_text() {
+    assert_eq!(parse_inline("2 * 3 = 6"), vec![Span::plain("2 * 3 = 6")]);
+    assert_eq!(parse_inline("snake_case_name"), vec![Span::plain("snake_case_name")]);
+    assert_eq!(parse_inline("a \\*literal\\* star"), vec![Span::plain("a *literal* star")]);
+    assert_eq!(parse_inline("[not a link] (here)"), vec![Span::plain("[not a link] (here)")]);
+    assert_eq!(parse_inline("[[]] and [[open"), vec![Span::plain("[[]] and [[open")]);
+}
+
+#[test]
+fn test_wiki_links_show_their_label() {
+    let note_link = |text: &str, title: &str| Span { note_link: Some(title.to_string()), ..Span::plain(text) };
+    
+    assert_eq!(
+        parse_inline("See [[Roadmap]] and [[Budget 2025|the budget]]"),
+        vec![
+            Span::plain("See "),
+            note_link("Roadmap", "Roadmap"),
+            Span::plain(" and "),
+            note_link("the budget", "Budget 2025"),
+        ]
+    );
+}
+
+#[test]
+fn test_wrap_breaks_long_lines_between_words() {
+    let blocks = parse("- one two three four five six\n# A heading that is too long to fit");
+    let lines = wrap(&blocks, 12);
+    
+    let texts: Vec<String> = lines.iter().map(|line| line.plain_text().trim_end().to_string()).collect();
+    assert_eq!(texts, vec!["one two", "three four", "five six", "A heading that is too long to fit"]);
+    assert_eq!(lines[0].marker(), "•");
+    assert!(lines[1].continued);
+    assert_eq!(lines[1].marker(), "");
+    assert_eq!(lines[1].kind, BlockKind::Bullet);
+}
@@ -0,0 +1,421 @@
+mod common;
+
+use common::temp_data_dir;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use rust_slint_sticky::migrations::{self, MigrationError, CURRENT_SCHEMA_VERSION};
+use rust_slint_sticky::note::{Priority, RelationOrigin, WorkflowStatus};
+use rust_slint_sticky::{NoteQuery, NoteStorage, NoteStore, SortOrder, SqliteNoteStorage, WorkspaceSettings};
+use std::path::{Path, PathBuf};
+
+/// Fresh data directory containing a copy of the given fixture as `notes.json`
+fn data_dir_with_fixture(fixture: &str) -> PathBuf {
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::copy(fixture_path(fixture), data_dir.join("notes.json")).unwrap();
+    data_dir
+}
+
+/// Fresh data directory containing a `notes.db` that holds the given fixture's notes,
+/// relations and revisions the way a SQLite store at the fixture's version kept them
+fn sqlite_db_with_fixture(fixture: &str) -> PathBuf {
+    let contents = std::fs::read_to_string(fixture_path(fixture)).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    
+    let conn = rusqlite::Connection::open(data_dir.join("notes.db")).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE notes (
+            id TEXT PRIMARY KEY, status TEXT NOT NULL, priority TEXT NOT NULL,
+            due_date TEXT, created_at TEXT NOT NULL, deleted_at TEXT, data TEXT NOT NULL
+        );
+        CREATE TABLE relations (id TEXT PRIMARY KEY, from_note_id TEXT NOT NULL, to_note_id TEXT NOT NULL, data TEXT NOT NULL);
+        CREATE TABLE revisions (id TEXT PRIMARY KEY, note_id TEXT NOT NULL, data TEXT NOT NULL);",
+    )
+    .unwrap();
+    for note in document["notes"].as_object().unwrap().values() {
+        conn.execute(
+            "INSERT INTO notes (id, status, priority, created_at, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                note["id"].as_str(),
+                note["workflow_status"].as_str(),
+                note["priority"].as_str(),
+                note["created_at"].as_str(),
+                note.to_string(),
+            ],
+        )
+        .unwrap();
+    }
+    for relation in document["graph"]["relations"].as_array().unwrap() {
+        conn.execute(
+            "INSERT INTO relations (id, from_note_id, to_note_id, data) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                relation["id"].as_str(),
+                relation["from_note_id"].as_str(),
+                relation["to_note_id"].as_str(),
+                relation.to_string(),
+            ],
+        )
+        .unwrap();
+    }
+    for revision in document["revisions"].as_object().unwrap().values().flat_map(|revisions| revisions.as_array().unwrap()) {
+        conn.execute(
+            "INSERT INTO revisions (id, note_id, data) VALUES (?1, ?2, ?3)",
+            rusqlite::params![revision["id"].as_str(), revision["note_id"].as_str(), revision.to_string()],
+        )
+        .unwrap();
+    }
+    conn.pragma_update(None, "user_version", migrations::schema_version(&document)).unwrap();
+    data_dir
+}
+
+fn fixture_path(fixture: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(fixture)
+}
+
+fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+}
+
+/// Schema version recorded in the notes file on disk
+fn on_disk_version(data_dir: &Path) -> u32 {
+    let contents = std::fs::read_to_string(data_dir.join("notes.json")).unwrap();
+    migrations::schema_version(&serde_json::from_str(&contents).unwrap())
+}
+
+#[test]
+fn test_registry_covers_every_historic_version() {
+    for version in migrations::LEGACY_SCHEMA_VERSION..CURRENT_SCHEMA_VERSION {
+        assert!(
+            migrations::migrations().iter().any(|m| m.from_version == version),
+            "missing migration from v{}",
+            version
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_v1_basic_notes_gain_extended_fields() {
+    let data_dir = data_dir_with_fixture("notes_v1_basic.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    let note = &notes[0];
+    assert_eq!(note.title, "Shopping list");
+    assert_eq!(note.created_at, utc(2025, 1, 5, 9, 15));
+    assert!(note.tags.is_empty());
+    assert_eq!(note.workflow_status, WorkflowStatus::Idea);
+    assert_eq!(note.priority, Priority::Medium);
+    assert!(note.text_color.is_none());
+    assert!(note.actual_time.is_none());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v1_notes_keep_their_fields() {
+    let data_dir = data_dir_with_fixture("notes_v1.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    let note = &notes[0];
+    assert_eq!(note.workflow_status, WorkflowStatus::InProgress);
+    assert_eq!(note.priority, Priority::High);
+    assert!(note.tags.contains("project"));
+    assert_eq!(note.estimated_time, Some(240));
+    assert_eq!(note.due_date, NaiveDate::from_ymd_opt(2025, 6, 30));
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_migration_backs_up_and_rewrites_file() {
+    let data_dir = data_dir_with_fixture("notes_v1.json");
+    let original = std::fs::read_to_string(data_dir.join("notes.json")).unwrap();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    storage.load_notes().await.unwrap();
+    
+    let backup = storage.backup_file_for_version(1);
+    assert_eq!(std::fs::read_to_string(&backup).unwrap(), original);
+    assert!(storage.has_pending_changes());
+    
+    storage.flush().await.unwrap();
+    assert_eq!(on_disk_version(&data_dir), CURRENT_SCHEMA_VERSION);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v2_notes_gain_an_empty_graph() {
+    let data_dir = data_dir_with_fixture("notes_v2.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].title, "Rust & Slint Research");
+    assert!(notes[0].text_color.is_some());
+    
+    let graph = storage.load_graph().await.unwrap();
+    assert!(graph.relations.is_empty());
+    assert!(storage.backup_file_for_version(2).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v3_graph_is_kept_and_history_starts_empty() {
+    let data_dir = data_dir_with_fixture("notes_v3.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 2);
+    
+    let graph = storage.load_graph().await.unwrap();
+    assert_eq!(graph.relations.len(), 1);
+    assert_eq!(graph.relations[0].description.as_deref(), Some("Components implement the design thoughts"));
+    
+    for note in &notes {
+        assert!(storage.list_revisions(&note.id).await.unwrap().is_empty());
+    }
+    assert!(storage.backup_file_for_version(3).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v4_history_is_kept_and_trash_starts_empty() {
+    let data_dir = data_dir_with_fixture("notes_v4.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    assert_eq!(storage.list().await.unwrap().len(), 2);
+    assert!(storage.list_trash().await.unwrap().is_empty());
+    
+    let revisions = storage.list_revisions("1f2e3d4c-5b6a-4978-8a9b-0c1d2e3f4a51").await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[1].changed_fields, vec!["title", "content"]);
+    assert!(storage.backup_file_for_version(4).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v5_trash_is_kept_and_saved_searches_start_empty() {
+    let data_dir = data_dir_with_fixture("notes_v5.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    assert_eq!(storage.list().await.unwrap().len(), 1);
+    assert_eq!(storage.list_trash().await.unwrap().len(), 1);
+    assert!(storage.load_saved_searches().await.unwrap().is_empty());
+    assert!(storage.backup_file_for_version(5).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v6_saved_searches_are_kept_and_settings_start_at_defaults() {
+    let data_dir = data_dir_with_fixture("notes_v6.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    assert_eq!(storage.load_saved_searches().await.unwrap().len(), 1);
+    assert_eq!(storage.load_settings().await.unwrap(), WorkspaceSettings::default());
+    assert!(storage.backup_file_for_version(6).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v7_timestamps_are_rewritten_as_rfc3339() {
+    let data_dir = data_dir_with_fixture("notes_v7.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    let design = notes.iter().find(|note| note.title == "UI/UX Design Thoughts").unwrap();
+    assert_eq!(design.created_at, utc(2025, 8, 1, 9, 0));
+    assert_eq!(design.updated_at, utc(2025, 8, 1, 9, 30));
+    assert_eq!(storage.list_trash().await.unwrap()[0].deleted_at, Some(utc(2025, 8, 3, 18, 45)));
+    assert_eq!(storage.load_graph().await.unwrap().relations[0].created_at, utc(2025, 8, 2, 10, 5));
+    let revisions = storage.list_revisions(&design.id).await.unwrap();
+    assert_eq!(revisions[1].timestamp, utc(2025, 8, 1, 9, 30));
+    assert!(storage.backup_file_for_version(7).exists());
+    
+    storage.flush().await.unwrap();
+    let contents = std::fs::read_to_string(data_dir.join("notes.json")).unwrap();
+    assert!(contents.contains("\"2025-08-01T09:30:00Z\""));
+    assert!(!contents.contains("\"2025-08-01 09:30\""));
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_v7_due_dates_become_plain_dates() {
+    let note = |due_date: &str| {
+        serde_json::json!({ "created_at": "2025-08-01 09:00", "updated_at": "2025-08-01 09:00", "due_date": due_date })
+    };
+    let mut document = serde_json::json!({
+        "schema_version": 7,
+        "notes": { "a": note("2025-06-30"), "b": note("2025-07-01 17:00"), "c": note("next friday") },
+    });
+    
+    migrations::migrate_document(&mut document).unwrap();
+    assert_eq!(document["notes"]["a"]["due_date"], "2025-06-30");
+    assert_eq!(document["notes"]["b"]["due_date"], "2025-07-01");
+    assert!(document["notes"]["c"]["due_date"].is_null());
+    assert_eq!(document["notes"]["a"]["created_at"], "2025-08-01T09:00:00Z");
+    
+    let mut broken = serde_json::json!({ "schema_version": 7, "notes": { "a": { "created_at": "yesterday" } } });
+    let result = migrations::migrate_document(&mut broken);
+    assert!(matches!(result, Err(MigrationError::InvalidDocument(_))));
+}
+
+#[tokio::test]
+async fn test_v8_notes_start_without_a_checklist() {
+    let data_dir = data_dir_with_fixture("notes_v8.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 2);
+    assert!(notes.iter().all(|note| note.checklist.is_empty()));
+    assert!(storage.backup_file_for_version(8).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_v9_relations_record_their_origin() {
+    let data_dir = data_dir_with_fixture("notes_v9.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let graph = storage.load_graph().await.unwrap();
+    assert_eq!(graph.relations.len(), 1);
+    assert_eq!(graph.relations[0].origin, RelationOrigin::Manual);
+    assert!(storage.backup_file_for_version(9).exists());
+    
+    let mut document = serde_json::json!({
+        "schema_version": 9,
+        "notes": {},
+        "graph": { "relations": [{ "relation_type": "RelatedTo" }, { "relation_type": "DependsOn" }] },
+    });
+    migrations::migrate_document(&mut document).unwrap();
+    assert_eq!(document["graph"]["relations"][0]["origin"], "Discovered");
+    assert_eq!(document["graph"]["relations"][1]["origin"], "Manual");
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_current_version_is_not_migrated() {
+    let data_dir = data_dir_with_fixture("notes_v10.json");
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    let notes = storage.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 2);
+    let design = notes.iter().find(|note| note.title == "UI/UX Design Thoughts").unwrap();
+    assert_eq!(design.checklist_progress(), (1, 2));
+    assert_eq!(design.checklist[0].text, "Support drag operations");
+    assert_eq!(storage.load_graph().await.unwrap().relations.len(), 1);
+    assert_eq!(storage.list_revisions("1f2e3d4c-5b6a-4978-8a9b-0c1d2e3f4a51").await.unwrap().len(), 2);
+    
+    let trash = storage.list_trash().await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].title, "Slint components");
+    assert_eq!(trash[0].deleted_at, Some(utc(2025, 8, 3, 18, 45)));
+    
+    let saved_searches = storage.load_saved_searches().await.unwrap();
+    assert_eq!(saved_searches.len(), 1);
+    assert_eq!(saved_searches[0].name, "Design in review");
+    assert_eq!(saved_searches[0].query, "design status:review");
+    assert_eq!(storage.load_settings().await.unwrap().sort_order, SortOrder::Priority);
+    assert!(!storage.has_pending_changes());
+    assert!(!storage.backup_file_for_version(10).exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_v7_rows_are_migrated() {
+    let data_dir = sqlite_db_with_fixture("notes_v7.json");
+    let db_path = data_dir.join("notes.db");
+    let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+    
+    let notes = storage.list().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].updated_at, utc(2025, 8, 1, 9, 30));
+    assert!(notes[0].checklist.is_empty());
+    let graph = storage.load_graph().await.unwrap();
+    assert_eq!(graph.relations[0].origin, RelationOrigin::Manual);
+    
+    // Revisions are rewritten in the current format, not just read leniently
+    let revisions = storage.list_revisions(&notes[0].id).await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].timestamp, utc(2025, 8, 1, 9, 0));
+    drop(storage);
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let stored: Vec<String> = conn
+        .prepare("SELECT data FROM revisions ORDER BY rowid")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    let timestamps: Vec<serde_json::Value> = stored
+        .iter()
+        .map(|data| serde_json::from_str::<serde_json::Value>(data).unwrap()["timestamp"].clone())
+        .collect();
+    assert_eq!(timestamps, vec![serde_json::json!("2025-08-01T09:00:00Z"), serde_json::json!("2025-08-01T09:30:00Z")]);
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+    assert_eq!(version, CURRENT_SCHEMA_VERSION);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_databases_from_before_versions_are_migrated() {
+    let contents = std::fs::read_to_string(fixture_path("notes_v1_basic.json")).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let db_path = data_dir.join("notes.db");
+    {
+        // No `user_version` and none of the indexed columns: v1 notes
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch("CREATE TABLE notes (id TEXT PRIMARY KEY, data TEXT NOT NULL)").unwrap();
+        for note in document["notes"].as_object().unwrap().values() {
+            conn.execute(
+                "INSERT INTO notes (id, data) VALUES (?1, ?2)",
+                rusqlite::params![note["id"].as_str(), note.to_string()],
+            )
+            .unwrap();
+        }
+    }
+    
+    let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+    let notes = storage.list().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].created_at, utc(2025, 1, 5, 9, 15));
+    assert_eq!(notes[0].workflow_status, WorkflowStatus::Idea);
+    let ideas = storage.query(&NoteQuery { status: Some(WorkflowStatus::Idea), ..Default::default() }).await.unwrap();
+    assert_eq!(ideas.len(), 1);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_newer_schema_versions_are_rejected() {
+    let mut document = serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1, "notes": {} });
+    
+    let result = migrations::migrate_document(&mut document);
+    assert!(matches!(result, Err(MigrationError::UnsupportedVersion(_))));
+}
+
+#[test]
+fn test_migrate_document_reports_original_version() {
+    let contents = std::fs::read_to_string(fixture_path("notes_v1_basic.json")).unwrap();
+    let mut document: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    
+    let original = migrations::migrate_document(&mut document).unwrap();
+    assert_eq!(original, 1);
+    assert_eq!(migrations::schema_version(&document), CURRENT_SCHEMA_VERSION);
+}
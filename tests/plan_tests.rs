@@ -0,0 +1,72 @@
+use rust_slint_sticky::note::{KnowledgeGraph, Priority, RelationType, WorkflowStatus};
+use rust_slint_sticky::timestamp::parse_date;
+use rust_slint_sticky::AppNote;
+
+fn task(title: &str, minutes: u32) -> AppNote {
+    let mut note = AppNote::new(title.to_string(), String::new());
+    note.estimated_time = Some(minutes);
+    note
+}
+
+fn titles<'a>(notes: &'a [AppNote], ids: impl IntoIterator<Item = &'a String>) -> Vec<&'a str> {
+    ids.into_iter()
+        .map(|id| notes.iter().find(|note| &note.id == id).unwrap().title.as_str())
+        .collect()
+}
+
+#[test]
+fn test_plan_orders_open_notes_after_what_they_wait_for() {
+    let mut spec = task("Spec", 45);
+    spec.set_workflow_status(WorkflowStatus::Done);
+    let design = task("Design", 60);
+    let api = task("API", 120);
+    let mut ui = task("UI", 30);
+    ui.due_date = parse_date("2026-11-01");
+    let release = task("Release", 15);
+    let mut chore = task("Chore", 10);
+    chore.priority = Priority::Urgent;
+    let mut shelved = task("Shelved", 500);
+    shelved.set_workflow_status(WorkflowStatus::Archived);
+    
+    let mut graph = KnowledgeGraph::new();
+    for (from, to) in [(&design, &spec), (&api, &design), (&ui, &design), (&release, &api), (&release, &ui), (&release, &shelved)] {
+        graph.create_relation(&from.id, &to.id, RelationType::DependsOn, 1.0, None).unwrap();
+    }
+    let notes = vec![spec, design, api, ui, release, chore, shelved];
+    
+    let plan = graph.plan(&notes);
+    let order: Vec<&String> = plan.tasks.iter().map(|task| &task.note_id).collect();
+    // The urgent chore first, and the UI before the API because it is due
+    assert_eq!(titles(&notes, order), vec!["Chore", "Design", "UI", "API", "Release"]);
+    let ready: Vec<bool> = plan.tasks.iter().map(|task| task.ready).collect();
+    assert_eq!(ready, vec![true, true, false, false, false]);
+    
+    let release_task = &plan.tasks[4];
+    assert_eq!((release_task.earliest_start, release_task.earliest_finish), (180, 195));
+    assert_eq!(plan.earliest_finish, 195);
+    assert_eq!(titles(&notes, &plan.critical_path), vec!["Design", "API", "Release"]);
+    assert!(plan.is_critical(&notes[2].id));
+    assert!(!plan.is_critical(&notes[3].id));
+}
+
+#[test]
+fn test_plan_without_dependencies_or_with_a_legacy_loop() {
+    let graph = KnowledgeGraph::new();
+    assert_eq!(graph.plan(&[]).earliest_finish, 0);
+    assert!(graph.plan(&[]).critical_path.is_empty());
+    
+    let a = task("A", 20);
+    let b = task("B", 40);
+    let c = task("C", 5);
+    let plan = graph.plan(&[a.clone(), b.clone(), c.clone()]);
+    assert_eq!(plan.earliest_finish, 40);
+    assert_eq!(plan.critical_path, vec![b.id.clone()]);
+    
+    // Loops can't be created any more, but older graphs may have one; it goes last
+    let mut looped = KnowledgeGraph::new();
+    looped.add_relation(a.id.clone(), b.id.clone(), RelationType::DependsOn, 1.0);
+    looped.add_relation(b.id.clone(), a.id.clone(), RelationType::DependsOn, 1.0);
+    let plan = looped.plan(&[a.clone(), b.clone(), c.clone()]);
+    let order: Vec<&str> = plan.tasks.iter().map(|task| task.note_id.as_str()).collect();
+    assert_eq!(order, vec![c.id.as_str(), a.id.as_str(), b.id.as_str()]);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::note;
+use rust_slint_sticky::note::{Priority, WorkflowStatus};
+use rust_slint_sticky::query::{Comparison, QueryExpr};
+use rust_slint_sticky::timestamp::parse_date;
+use rust_slint_sticky::{AppNote, Query};
+
+fn matching<'a>(query: &str, notes: &'a [AppNote]) -> Vec<&'a str> {
+    let query = Query::parse(query).unwrap();
+    notes.iter().filter(|note| query.matches(note)).map(|note| note.title.as_str()).collect()
+}
+
+fn sample_notes() -> Vec<AppNote> {
+    let mut rust = note("Rust ownership", "Borrowing and lifetimes explained");
+    rust.add_tag("rust".to_string());
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    rust.priority = Priority::High;
+    rust.due_date = parse_date("2026-10-20");
+    
+    let mut slint = note("Slint layouts", "Rows, columns and exact phrase matching");
+    slint.add_tag("ui".to_string());
+    slint.set_workflow_status(WorkflowStatus::InProgress);
+    slint.priority = Priority::Urgent;
+    slint.due_date = parse_date("2026-12-01");
+    
+    let mut groceries = note("Groceries", "Milk, bread and rust remover");
+    groceries.priority = Priority::Low;
+    
+    vec![rust, slint, groceries]
+}
+
+#[test]
+fn test_parse_builds_the_expected_tree() {
+    let query = Query::parse(r#"tag:Rust -draft "exact  Phrase" OR priority:>=high"#).unwrap();
+    assert_eq!(
+        query.expr(),
+        Some(&QueryExpr::Or(vec![
+            QueryExpr::And(vec![
+                QueryExpr::Tag("rust".to_string()),
+                QueryExpr::Not(Box::new(QueryExpr::Term("draft".to_string()))),
+                QueryExpr::Phrase(vec!["exact".to_string(), "phrase".to_string()]),
+            ]),
+            QueryExpr::Priority(Comparison::GreaterOrEqual, Priority::High),
+        ]))
+    );
+    assert_eq!(query.terms(), vec!["draft", "exact", "phrase"]);
+    assert_eq!(query.ranking_terms(), vec!["exact", "phrase"]);
+    
+    assert!(Query::parse("   ").unwrap().is_empty());
+    assert!(Query::parse("?!").unwrap().is_empty());
+}
+
+#[test]
+fn test_fields_filter_on_note_metadata() {
+    let notes = sample_notes();
+    
+    assert_eq!(matching("tag:rust", &notes), vec!["Rust ownership"]);
+    assert_eq!(matching("status:in-progress", &notes), vec!["Slint layouts"]);
+    assert_eq!(matching("status:TODO", &notes), vec!["Rust ownership"]);
+    assert_eq!(matching("priority:>=high", &notes), vec!["Rust ownership", "Slint layouts"]);
+    assert_eq!(matching("priority:<medium", &notes), vec!["Groceries"]);
+    assert_eq!(matching("priority:urgent", &notes), vec!["Slint layouts"]);
+    assert_eq!(matching("due:<2026-11-01", &notes), vec!["Rust ownership"]);
+    // Notes without a due date never match a due date condition
+    assert_eq!(matching("due:>=2026-01-01", &notes), vec!["Rust ownership", "Slint layouts"]);
+}
+
+#[test]
+fn test_due_dates_can_be_relative_to_today() {
+    let notes = sample_notes();
+    let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+    let matching = |query: &str| -> Vec<&str> {
+        let query = Query::parse_relative_to(query, today).unwrap();
+        notes.iter().filter(|note| query.matches(note)).map(|note| note.title.as_str()).collect()
+    };
+    
+    assert_eq!(matching("due:<=+7d"), vec!["Rust ownership"]);
+    assert_eq!(matching("due:<=+1w"), vec!["Rust ownership"]);
+    assert_eq!(matching("due:<+3d"), Vec::<&str>::new());
+    assert_eq!(matching("due:>today"), vec!["Rust ownership", "Slint layouts"]);
+    assert_eq!(matching("due:>=-2w priority:urgent"), vec!["Slint layouts"]);
+    assert!(Query::parse_relative_to("due:<+7x", today).is_err());
+    assert!(Query::parse_relative_to("due:+d", today).is_err());
+    assert!(Query::parse_relative_to("due:+1é", today).is_err());
+    assert!(Query::parse_relative_to("due:-é", today).is_err());
+    assert!(Query::parse_relative_to("due:+-3d", today).is_err());
+    assert!(Query::parse_relative_to("due:--1w", today).is_err());
+    assert!(Query::parse_relative_to("due:<=++7d", today).is_err());
+}
+
+#[test]
+fn test_words_phrases_exclusion_and_or() {
+    let notes = sample_notes();
+    
+    assert_eq!(matching("rust", &notes), vec!["Rust ownership", "Groceries"]);
+    assert_eq!(matching("rust -tag:rust", &notes), vec!["Groceries"]);
+    assert_eq!(matching("rust -milk", &notes), vec!["Rust ownership"]);
+    assert_eq!(matching("borrow", &notes), vec!["Rust ownership"]);
+    assert_eq!(matching(r#""exact phrase""#, &notes), vec!["Slint layouts"]);
+    assert_eq!(matching(r#""phrase exact""#, &notes), Vec::<&str>::new());
+    assert_eq!(matching("milk OR tag:ui", &notes), vec!["Slint layouts", "Groceries"]);
+    assert_eq!(matching("(milk OR lifetimes) priority:>=high", &notes), vec!["Rust ownership"]);
+    assert_eq!(matching("", &notes).len(), 3);
+}
+
+#[test]
+fn test_syntax_errors_say_what_and_where() {
+    let error = |query: &str| Query::parse(query).unwrap_err();
+    
+    assert_eq!(error("status:someday").position, 0);
+    assert!(error("status:someday").message.contains("unknown status"));
+    assert!(error("rust priority:>=huge").message.contains("unknown priority"));
+    assert_eq!(error("rust priority:>=huge").position, 5);
+    assert!(error("due:<tomorrow").message.contains("not a date"));
+    assert!(error("colour:red").message.contains("unknown field"));
+    assert!(error("tag:").message.contains("missing value"));
+    assert_eq!(error(r#"rust "open"#).position, 5);
+    assert_eq!(error("rust OR").message, "`OR` needs a search term on both sides");
+    assert_eq!(error("OR rust").position, 0);
+    assert_eq!(error("rust -").position, 5);
+    assert_eq!(error("(rust").message, "missing `)`");
+    assert_eq!(error("rust)").message, "unmatched `)`");
+    assert_eq!(error("()").message, "empty parentheses");
+    assert_eq!(error("tag:").to_string(), "missing value after `tag:` (at column 1)");
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::note;
+use rust_slint_sticky::note::{KnowledgeGraph, RelationError, RelationOrigin, RelationType};
+
+#[test]
+fn test_relation_types_round_trip_through_labels() {
+    for relation_type in RelationType::ALL {
+        assert_eq!(RelationType::from_label(relation_type.label()), relation_type);
+    }
+    assert_eq!(RelationType::from_label("Depends on"), RelationType::DependsOn);
+    assert_eq!(RelationType::from_label("nonsense"), RelationType::RelatedTo);
+}
+
+#[test]
+fn test_create_relation_validates() {
+    let mut graph = KnowledgeGraph::new();
+    
+    let id = graph
+        .create_relation("a", "b", RelationType::DependsOn, 0.8, Some("  needs the API  ".to_string()))
+        .unwrap();
+    let relation = &graph.relations[0];
+    assert_eq!(relation.id, id);
+    assert_eq!(relation.origin, RelationOrigin::Manual);
+    assert_eq!(relation.description.as_deref(), Some("needs the API"));
+    
+    assert_eq!(graph.create_relation("a", "a", RelationType::Extends, 1.0, None), Err(RelationError::SelfRelation));
+    assert_eq!(graph.create_relation("a", "c", RelationType::Blocks, 1.5, None), Err(RelationError::InvalidStrength(1.5)));
+    assert!(matches!(graph.create_relation("a", "c", RelationType::Blocks, f32::NAN, None), Err(RelationError::InvalidStrength(_))));
+    assert_eq!(
+        graph.create_relation("a", "b", RelationType::DependsOn, 0.5, None),
+        Err(RelationError::Duplicate(RelationType::DependsOn))
+    );
+    
+    // Another type, or the other direction of a directed type, is a different relation
+    graph.create_relation("a", "b", RelationType::Extends, 0.5, Some(" ".to_string())).unwrap();
+    graph.create_relation("b", "a", RelationType::Extends, 0.5, None).unwrap();
+    assert_eq!(graph.relations[1].description, None);
+    
+    // Symmetric types read the same both ways
+    graph.create_relation("a", "c", RelationType::Conflicts, 0.5, None).unwrap();
+    assert_eq!(
+        graph.create_relation("c", "a", RelationType::Conflicts, 0.5, None),
+        Err(RelationError::Duplicate(RelationType::Conflicts))
+    );
+}
+
+#[test]
+fn test_update_and_delete_relations() {
+    let first = note("Rust ownership", "Learning rust ownership rules");
+    let second = note("Rust borrowing", "Learning rust borrowing rules");
+    let mut graph = KnowledgeGraph::new();
+    graph.auto_discover_relations(&[first.clone(), second.clone()]);
+    let discovered = graph.relations[0].id.clone();
+    let manual = graph.create_relation(&first.id, &second.id, RelationType::Extends, 1.0, None).unwrap();
+    
+    // Editing a discovered relation makes it a manual one
+    graph.update_relation(&discovered, RelationType::PartOf, 0.4, Some("chapter".to_string())).unwrap();
+    let relation = &graph.relations[0];
+    assert_eq!((relation.relation_type, relation.strength), (RelationType::PartOf, 0.4));
+    assert_eq!(relation.description.as_deref(), Some("chapter"));
+    assert_eq!(relation.origin, RelationOrigin::Manual);
+    
+    // Keeping its own type isn't a duplicate, taking the other relation's is
+    graph.update_relation(&manual, RelationType::Extends, 0.9, None).unwrap();
+    assert_eq!(
+        graph.update_relation(&manual, RelationType::PartOf, 0.9, None),
+        Err(RelationError::Duplicate(RelationType::PartOf))
+    );
+    
+    assert_eq!(graph.delete_relation(&manual).unwrap().id, manual);
+    assert_eq!(graph.relations.len(), 1);
+    assert!(matches!(graph.delete_relation(&manual), Err(RelationError::UnknownRelation(_))));
+}
+
+#[test]
+fn test_link_relations_are_changed_through_the_content() {
+    let source = note("Source", "[[Target]]");
+    let target = note("Target", "");
+    let mut graph = KnowledgeGraph::new();
+    graph.sync_links(&[source, target]);
+    let link = graph.relations[0].id.clone();
+    
+    assert_eq!(graph.update_relation(&link, RelationType::DependsOn, 1.0, None), Err(RelationError::LinkRelation));
+    assert!(matches!(graph.delete_relation(&link), Err(RelationError::LinkRelation)));
+    assert_eq!(graph.relations.len(), 1);
+}
+
+#[test]
+fn test_dependency_direction_follows_the_relation_type() {
+    let mut graph = KnowledgeGraph::new();
+    graph.create_relation("release", "tests", RelationType::DependsOn, 1.0, None).unwrap();
+    graph.create_relation("schema", "release", RelationType::Blocks, 1.0, None).unwrap();
+    graph.create_relation("docs", "release", RelationType::SubtaskOf, 1.0, None).unwrap();
+    graph.create_relation("release", "notes", RelationType::RelatedTo, 1.0, None).unwrap();
+    
+    assert_eq!(graph.prerequisites("release"), vec!["tests", "schema", "docs"]);
+    assert_eq!(graph.dependents("tests"), vec!["release"]);
+    assert!(graph.prerequisites("notes").is_empty());
+}
+
+#[test]
+fn test_relations_closing_a_dependency_loop_are_refused() {
+    let mut graph = KnowledgeGraph::new();
+    graph.create_relation("a", "b", RelationType::DependsOn, 1.0, None).unwrap();
+    graph.create_relation("b", "c", RelationType::DependsOn, 1.0, None).unwrap();
+    assert_eq!(graph.dependency_path("a", "c"), Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+    assert_eq!(graph.dependency_path("c", "a"), None);
+    
+    // a blocking c makes c wait for a, which already waits for c
+    let cycle = vec!["c", "a", "b", "c"].into_iter().map(String::from).collect();
+    assert_eq!(
+        graph.create_relation("a", "c", RelationType::Blocks, 1.0, None),
+        Err(RelationError::DependencyCycle(cycle))
+    );
+    assert!(matches!(
+        graph.create_relation("a", "c", RelationType::SubtaskOf, 1.0, None),
+        Err(RelationError::DependencyCycle(_))
+    ));
+    assert!(matches!(
+        graph.create_relation("b", "a", RelationType::DependsOn, 1.0, None),
+        Err(RelationError::DependencyCycle(cycle)) if cycle.len() == 3
+    ));
+    // Relations that don't order work can go either way
+    graph.create_relation("c", "a", RelationType::Extends, 1.0, None).unwrap();
+    
+    // Turning a relation into a dependency is checked too, without counting its old self
+    let extends = graph.relations[2].id.clone();
+    assert!(matches!(
+        graph.update_relation(&extends, RelationType::DependsOn, 1.0, None),
+        Err(RelationError::DependencyCycle(_))
+    ));
+    let first = graph.relations[0].id.clone();
+    graph.update_relation(&first, RelationType::DependsOn, 0.5, None).unwrap();
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::note;
+use rust_slint_sticky::search::{tokenize, typo_distance, TypoTolerance};
+use rust_slint_sticky::SearchIndex;
+
+fn ids(index: &SearchIndex, query: &str) -> Vec<String> {
+    index.search(query).into_iter().map(|hit| hit.note_id).collect()
+}
+
+#[test]
+fn test_tokenize_lowercases_and_splits_on_punctuation() {
+    assert_eq!(tokenize("Rust's async/await, 2024!"), vec!["rust", "s", "async", "await", "2024"]);
+    assert!(tokenize("  -- ").is_empty());
+}
+
+#[test]
+fn test_best_matches_come_first() {
+    let passing = note("Groceries", "Remember rust remover for the bike");
+    let focused = note("Rust", "Rust ownership and rust borrowing");
+    let mut tagged = note("Weekend reading", "A book about systems programming");
+    tagged.add_tag("rust".to_string());
+    let unrelated = note("Holiday", "Book the flights");
+    let index = SearchIndex::from_notes([&passing, &focused, &tagged, &unrelated]);
+    
+    let hits = index.search("rust");
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0].note_id, focused.id);
+    assert_eq!(hits[2].note_id, passing.id);
+    assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    
+    assert!(index.search("").is_empty());
+    assert!(index.search("python").is_empty());
+}
+
+#[test]
+fn test_every_term_must_match_and_prefixes_complete() {
+    let both = note("Rust async", "Tokio runtime");
+    let one = note("Rust ownership", "Borrowing");
+    let index = SearchIndex::from_notes([&both, &one]);
+    
+    assert_eq!(ids(&index, "rust tokio"), vec![both.id.clone()]);
+    assert_eq!(ids(&index, "RUST Tok"), vec![both.id.clone()]);
+    assert_eq!(index.search("ru").len(), 2);
+    
+    // The whole word outranks a word it only starts
+    let exact = note("Car", "");
+    let longer = note("Cargo", "");
+    let index = SearchIndex::from_notes([&exact, &longer]);
+    assert_eq!(ids(&index, "car"), vec![exact.id.clone(), longer.id.clone()]);
+}
+
+#[test]
+fn test_index_is_updated_incrementally() {
+    let mut draft = note("Draft", "Meeting notes");
+    let other = note("Other", "Meeting agenda");
+    let mut index = SearchIndex::new();
+    index.upsert(&draft);
+    index.upsert(&other);
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.search("meeting").len(), 2);
+    
+    draft.update_content("Draft".to_string(), "Budget review".to_string());
+    index.upsert(&draft);
+    assert_eq!(index.len(), 2);
+    assert_eq!(ids(&index, "meeting"), vec![other.id.clone()]);
+    assert_eq!(ids(&index, "budget"), vec![draft.id.clone()]);
+    
+    assert!(index.remove(&other.id));
+    assert!(!index.remove(&other.id));
+    assert!(!index.contains(&other.id));
+    assert!(index.search("meeting").is_empty());
+    
+    assert!(index.remove(&draft.id));
+    assert!(index.is_empty());
+    assert!(index.search("budget").is_empty());
+}
+
+#[test]
+fn test_typo_distance_counts_edits_and_swaps() {
+    assert_eq!(typo_distance("slint", "slint", 2), Some(0));
+    assert_eq!(typo_distance("slnit", "slint", 2), Some(1));
+    assert_eq!(typo_distance("knowlege", "knowledge", 2), Some(1));
+    assert_eq!(typo_distance("grafh", "graph", 2), Some(1));
+    assert_eq!(typo_distance("kitten", "sitting", 3), Some(3));
+    assert_eq!(typo_distance("kitten", "sitting", 2), None);
+    assert_eq!(typo_distance("rust", "rustacean", 2), None);
+}
+
+#[test]
+fn test_misspelled_words_still_match_below_exact_ones() {
+    let slint = note("Slint components", "Building a knowledge graph view");
+    let typo = note("Slnit notes", "Misspelled on purpose");
+    let index = SearchIndex::from_notes([&slint, &typo]);
+    
+    assert_eq!(ids(&index, "knowlege"), vec![slint.id.clone()]);
+    assert_eq!(ids(&index, "slnit"), vec![typo.id.clone(), slint.id.clone()]);
+    assert_eq!(ids(&index, "slint"), vec![slint.id.clone(), typo.id.clone()]);
+    // Short words have to be spelled right
+    assert!(index.search("vew").is_empty());
+    
+    let strict = index.clone().with_typo_tolerance(TypoTolerance::Off);
+    assert!(strict.search("knowlege").is_empty());
+    assert_eq!(ids(&strict, "slnit"), vec![typo.id.clone()]);
+    
+    let lenient = index.with_typo_tolerance(TypoTolerance::Max(1));
+    assert_eq!(ids(&lenient, "vew"), vec![slint.id.clone()]);
+}
+
+#[test]
+fn test_typo_tolerance_settings() {
+    assert_eq!(TypoTolerance::Auto.max_typos("web"), 0);
+    assert_eq!(TypoTolerance::Auto.max_typos("slint"), 1);
+    assert_eq!(TypoTolerance::Auto.max_typos("knowledge"), 2);
+    assert_eq!(TypoTolerance::Max(2).max_typos("ab"), 0);
+    assert_eq!(TypoTolerance::Off.max_typos("knowledge"), 0);
+    
+    assert_eq!("auto".parse::<TypoTolerance>(), Ok(TypoTolerance::Auto));
+    assert_eq!(" OFF ".parse::<TypoTolerance>(), Ok(TypoTolerance::Off));
+    assert_eq!("3".parse::<TypoTolerance>(), Ok(TypoTolerance::Max(3)));
+    assert!("lots".parse::<TypoTolerance>().is_err());
+}
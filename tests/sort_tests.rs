@@ -0,0 +1,53 @@
+use rust_slint_sticky::note::Priority;
+use rust_slint_sticky::timestamp::{parse_date, parse_timestamp};
+use rust_slint_sticky::{AppNote, SortOrder};
+
+fn sample_notes() -> Vec<AppNote> {
+    let mut plan = AppNote::new("plan release".to_string(), String::new());
+    plan.created_at = parse_timestamp("2026-10-01 09:00").unwrap();
+    plan.updated_at = parse_timestamp("2026-10-16 12:00").unwrap();
+    plan.due_date = parse_date("2026-10-30");
+    plan.priority = Priority::High;
+    plan.completion_percentage = 50.0;
+    
+    let mut bug = AppNote::new("Fix crash".to_string(), String::new());
+    bug.created_at = parse_timestamp("2026-10-10 14:00").unwrap();
+    bug.updated_at = parse_timestamp("2026-10-11 08:00").unwrap();
+    bug.due_date = parse_date("2026-10-18");
+    bug.priority = Priority::Urgent;
+    
+    let mut idea = AppNote::new("Adopt a cat".to_string(), String::new());
+    idea.created_at = parse_timestamp("2026-09-20 18:30").unwrap();
+    idea.updated_at = parse_timestamp("2026-09-20 18:30").unwrap();
+    idea.priority = Priority::Low;
+    idea.completion_percentage = 100.0;
+    
+    vec![plan, bug, idea]
+}
+
+fn sorted(order: SortOrder, notes: &[AppNote]) -> Vec<&str> {
+    let mut notes: Vec<&AppNote> = notes.iter().collect();
+    order.sort(&mut notes);
+    notes.into_iter().map(|note| note.title.as_str()).collect()
+}
+
+#[test]
+fn test_each_order_sorts_by_its_field() {
+    let notes = sample_notes();
+    
+    assert_eq!(sorted(SortOrder::Relevance, &notes), vec!["plan release", "Fix crash", "Adopt a cat"]);
+    assert_eq!(sorted(SortOrder::Updated, &notes), vec!["plan release", "Fix crash", "Adopt a cat"]);
+    assert_eq!(sorted(SortOrder::Created, &notes), vec!["Fix crash", "plan release", "Adopt a cat"]);
+    assert_eq!(sorted(SortOrder::DueDate, &notes), vec!["Fix crash", "plan release", "Adopt a cat"]);
+    assert_eq!(sorted(SortOrder::Priority, &notes), vec!["Fix crash", "plan release", "Adopt a cat"]);
+    assert_eq!(sorted(SortOrder::Title, &notes), vec!["Adopt a cat", "Fix crash", "plan release"]);
+    assert_eq!(sorted(SortOrder::Completion, &notes), vec!["Adopt a cat", "plan release", "Fix crash"]);
+}
+
+#[test]
+fn test_labels_round_trip() {
+    for order in SortOrder::ALL {
+        assert_eq!(SortOrder::from_label(order.label()), order);
+    }
+    assert_eq!(SortOrder::from_label("Shuffle"), SortOrder::Relevance);
+}
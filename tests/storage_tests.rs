@@ -0,0 +1,434 @@
+mod common;
+
+use common::temp_data_dir;
+use rust_slint_sticky::note::{KnowledgeGraph, Priority, RelationOrigin, RelationType, WorkflowStatus};
+use rust_slint_sticky::timestamp::{parse_date, parse_timestamp};
+use rust_slint_sticky::{AppNote, NoteQuery, NoteStorage, NoteStore, SavedSearch, SortOrder, SqliteNoteStorage, WorkspaceSettings};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_saved_note_survives_reload_after_flush() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let note = AppNote::new("Persisted".to_string(), "Survives a restart".to_string());
+    
+    storage.save_note(&note).await.unwrap();
+    storage.flush().await.unwrap();
+    
+    let reopened = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let loaded = reopened.get_note(&note.id).await.unwrap().expect("note should be on disk");
+    assert_eq!(loaded.title, "Persisted");
+    assert_eq!(loaded.content, "Survives a restart");
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_mutations_are_coalesced_until_flush() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await
+        .with_flush_delay(Duration::from_secs(60));
+    let mut note = AppNote::new("Dragged".to_string(), "".to_string());
+    
+    // Simulate a drag gesture firing many position updates
+    for step in 0..50 {
+        note.set_position(step as f32, step as f32);
+        storage.save_note(&note).await.unwrap();
+    }
+    
+    // Nothing hits the disk until the debounce elapses or we flush
+    assert!(storage.has_pending_changes());
+    assert!(!data_dir.join("notes.json").exists());
+    assert_eq!(storage.load_notes().await.unwrap().len(), 1);
+    
+    storage.flush().await.unwrap();
+    assert!(!storage.has_pending_changes());
+    
+    let reopened = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let loaded = reopened.get_note(&note.id).await.unwrap().unwrap();
+    assert_eq!(loaded.x, 49.0);
+    assert_eq!(loaded.y, 49.0);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_debounce_timer_flushes_automatically() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await
+        .with_flush_delay(Duration::from_millis(20));
+    let note = AppNote::new("Auto".to_string(), "Flushed by the timer".to_string());
+    
+    storage.save_note(&note).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    
+    assert!(!storage.has_pending_changes());
+    assert!(data_dir.join("notes.json").exists());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_delete_note_is_persisted() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let keep = AppNote::new("Keep".to_string(), "".to_string());
+    let remove = AppNote::new("Remove".to_string(), "".to_string());
+    
+    storage.save_note(&keep).await.unwrap();
+    storage.save_note(&remove).await.unwrap();
+    storage.delete_note(&remove.id).await.unwrap();
+    storage.flush().await.unwrap();
+    
+    let reopened = NoteStorage::with_data_dir(data_dir.clone()).await;
+    let notes = reopened.load_notes().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].id, keep.id);
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+/// Notes with a spread of statuses, priorities, tags and due dates for query tests
+fn query_fixture() -> Vec<AppNote> {
+    let mut rust = AppNote::new("Learn Rust".to_string(), "Ownership and borrowing".to_string());
+    rust.add_tag("Rust".to_string());
+    rust.set_workflow_status(WorkflowStatus::Todo);
+    rust.priority = Priority::High;
+    rust.due_date = parse_date("2026-11-01");
+    
+    let mut slint = AppNote::new("Slint layouts".to_string(), "Boxes and grids".to_string());
+    slint.add_tag("rust".to_string());
+    slint.add_tag("ui".to_string());
+    slint.set_workflow_status(WorkflowStatus::InProgress);
+    slint.due_date = parse_date("2026-12-15 09:30");
+    
+    let mut groceries = AppNote::new("Groceries".to_string(), "Milk".to_string());
+    groceries.set_workflow_status(WorkflowStatus::Todo);
+    
+    vec![rust, slint, groceries]
+}
+
+/// Exercise the `NoteStore` contract so every backend is held to the same behavior
+async fn check_note_store_contract(store: &dyn NoteStore) {
+    store.load().await.unwrap();
+    let notes = query_fixture();
+    for note in &notes {
+        store.upsert(note).await.unwrap();
+    }
+    
+    assert_eq!(store.list().await.unwrap().len(), 3);
+    assert_eq!(store.get(&notes[0].id).await.unwrap().unwrap().title, "Learn Rust");
+    
+    let todo = store.query(&NoteQuery { status: Some(WorkflowStatus::Todo), ..Default::default() }).await.unwrap();
+    assert_eq!(todo.len(), 2);
+    
+    let high = store.query(&NoteQuery { priority: Some(Priority::High), ..Default::default() }).await.unwrap();
+    assert_eq!(high.len(), 1);
+    assert_eq!(high[0].id, notes[0].id);
+    
+    let tagged = store.query(&NoteQuery { tag: Some("RUST".to_string()), ..Default::default() }).await.unwrap();
+    assert_eq!(tagged.len(), 2);
+    
+    let due_soon = store.query(&NoteQuery { due_before: parse_date("2026-11-30"), ..Default::default() }).await.unwrap();
+    assert_eq!(due_soon.len(), 1);
+    assert_eq!(due_soon[0].id, notes[0].id);
+    
+    let due_later = store.query(&NoteQuery { due_after: parse_date("2026-12-15"), ..Default::default() }).await.unwrap();
+    assert_eq!(due_later.len(), 1);
+    assert_eq!(due_later[0].id, notes[1].id);
+    
+    // Updating a note replaces its indexed columns and tags
+    let mut updated = notes[1].clone();
+    updated.remove_tag("rust");
+    updated.set_workflow_status(WorkflowStatus::Done);
+    store.upsert(&updated).await.unwrap();
+    let tagged = store.query(&NoteQuery { tag: Some("rust".to_string()), ..Default::default() }).await.unwrap();
+    assert_eq!(tagged.len(), 1);
+    let done = store.query(&NoteQuery { status: Some(WorkflowStatus::Done), ..Default::default() }).await.unwrap();
+    assert_eq!(done.len(), 1);
+    
+    let limited = store.query(&NoteQuery { limit: Some(2), ..Default::default() }).await.unwrap();
+    assert_eq!(limited.len(), 2);
+    
+    // The graph round-trips, including manual relation descriptions
+    let mut graph = KnowledgeGraph::new();
+    graph.add_relation(notes[0].id.clone(), notes[1].id.clone(), RelationType::RelatedTo, 0.5);
+    graph.add_relation(notes[1].id.clone(), notes[2].id.clone(), RelationType::DependsOn, 1.0);
+    graph.relations[1].description = Some("Needs groceries first".to_string());
+    graph.auto_relation_threshold = 0.4;
+    store.save_graph(&graph).await.unwrap();
+    let loaded = store.load_graph().await.unwrap();
+    assert_eq!(loaded.relations.len(), 2);
+    assert_eq!(loaded.relations[1].relation_type, RelationType::DependsOn);
+    assert_eq!(loaded.relations[1].description.as_deref(), Some("Needs groceries first"));
+    assert_eq!(loaded.auto_relation_threshold, 0.4);
+    
+    // Saved searches round-trip in order, and saving replaces the whole list
+    assert!(store.load_saved_searches().await.unwrap().is_empty());
+    let urgent = SavedSearch::new("My urgent this week", "priority:urgent due:<=+7d");
+    let rust = SavedSearch::new("Rust", "tag:rust");
+    store.save_saved_searches(&[urgent.clone(), rust.clone()]).await.unwrap();
+    assert_eq!(store.load_saved_searches().await.unwrap(), vec![urgent, rust.clone()]);
+    store.save_saved_searches(std::slice::from_ref(&rust)).await.unwrap();
+    assert_eq!(store.load_saved_searches().await.unwrap(), vec![rust]);
+    
+    // Settings start at their defaults and round-trip
+    assert_eq!(store.load_settings().await.unwrap(), WorkspaceSettings::default());
+    let settings = WorkspaceSettings { sort_order: SortOrder::DueDate };
+    store.save_settings(&settings).await.unwrap();
+    assert_eq!(store.load_settings().await.unwrap(), settings);
+    
+    assert!(store.delete(&notes[2].id).await.unwrap());
+    assert!(!store.delete(&notes[2].id).await.unwrap());
+    assert!(store.get(&notes[2].id).await.unwrap().is_none());
+    assert_eq!(store.list().await.unwrap().len(), 2);
+    
+    // Deleting a note drops the relations that involve it
+    let loaded = store.load_graph().await.unwrap();
+    assert_eq!(loaded.relations.len(), 1);
+    assert_eq!(loaded.relations[0].to_note_id, notes[1].id);
+    
+    // Every save that changed a tracked field left a revision behind
+    let revisions = store.list_revisions(&notes[1].id).await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].changed_fields, vec!["created"]);
+    assert_eq!(revisions[1].changed_fields, vec!["tags", "workflow_status"]);
+    
+    // Moving a note is layout, not an edit
+    let mut moved = updated.clone();
+    moved.set_position(500.0, 20.0);
+    store.upsert(&moved).await.unwrap();
+    assert_eq!(store.list_revisions(&notes[1].id).await.unwrap().len(), 2);
+    
+    store.flush().await.unwrap();
+}
+
+/// Exercise revision recording, restore and retention for a backend
+async fn check_revision_history(store: &dyn NoteStore) {
+    let mut note = AppNote::new("Draft".to_string(), "First line".to_string());
+    store.upsert(&note).await.unwrap();
+    note.update_content("Draft".to_string(), "First line\nSecond line".to_string());
+    store.upsert(&note).await.unwrap();
+    note.update_content("Final".to_string(), "Rewritten".to_string());
+    store.upsert(&note).await.unwrap();
+    
+    let revisions = store.list_revisions(&note.id).await.unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[1].changed_fields, vec!["content"]);
+    assert_eq!(revisions[2].changed_fields, vec!["title", "content"]);
+    
+    let restored = store.restore_revision(&note.id, &revisions[1].id).await.unwrap();
+    assert_eq!(restored.title, "Draft");
+    assert_eq!(restored.content, "First line\nSecond line");
+    assert_eq!(store.get(&note.id).await.unwrap().unwrap().content, "First line\nSecond line");
+    
+    // The restore is recorded, and the limit of 3 drops the oldest revision
+    let revisions = store.list_revisions(&note.id).await.unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2].title, "Draft");
+    assert!(revisions.iter().all(|r| r.changed_fields != vec!["created"]));
+    
+    assert!(store.restore_revision(&note.id, "missing").await.is_err());
+    
+    store.delete(&note.id).await.unwrap();
+    assert!(store.list_revisions(&note.id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_json_store_revision_history() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await.with_revision_limit(3);
+    
+    check_revision_history(&storage).await;
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_revision_history() {
+    let storage = SqliteNoteStorage::open_in_memory().unwrap().with_revision_limit(3);
+    
+    check_revision_history(&storage).await;
+}
+
+/// Exercise soft deletion, restore and purging for a backend
+async fn check_trash(store: &dyn NoteStore) {
+    let mut notes = query_fixture();
+    for note in &notes {
+        store.upsert(note).await.unwrap();
+    }
+    
+    // Trashing is a plain upsert; trashed notes drop out of list and query
+    notes[0].move_to_trash();
+    store.upsert(&notes[0]).await.unwrap();
+    notes[2].deleted_at = parse_timestamp("2020-01-01 08:00");
+    store.upsert(&notes[2]).await.unwrap();
+    
+    assert_eq!(store.list().await.unwrap().len(), 1);
+    let todo = store.query(&NoteQuery { status: Some(WorkflowStatus::Todo), ..Default::default() }).await.unwrap();
+    assert!(todo.is_empty());
+    let trash = store.list_trash().await.unwrap();
+    assert_eq!(trash.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(), vec!["Learn Rust", "Groceries"]);
+    
+    // Only the note that has been in the trash long enough is purged
+    let purged = store.purge_trash(30).await.unwrap();
+    assert_eq!(purged, vec![notes[2].id.clone()]);
+    assert!(store.get(&notes[2].id).await.unwrap().is_none());
+    
+    notes[0].restore_from_trash();
+    store.upsert(&notes[0]).await.unwrap();
+    assert_eq!(store.list().await.unwrap().len(), 2);
+    assert!(store.list_trash().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_json_store_trash() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    check_trash(&storage).await;
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_trash() {
+    let storage = SqliteNoteStorage::open_in_memory().unwrap();
+    
+    check_trash(&storage).await;
+}
+
+#[tokio::test]
+async fn test_json_store_implements_note_store() {
+    let data_dir = temp_data_dir();
+    let storage = NoteStorage::with_data_dir(data_dir.clone()).await;
+    
+    check_note_store_contract(&storage).await;
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_implements_note_store() {
+    let storage = SqliteNoteStorage::open_in_memory().unwrap();
+    
+    check_note_store_contract(&storage).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_store_persists_to_file() {
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let db_path = data_dir.join("notes.db");
+    let note = AppNote::new("On disk".to_string(), "Stored in SQLite".to_string());
+    
+    {
+        let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+        storage.upsert(&note).await.unwrap();
+    }
+    
+    let reopened = SqliteNoteStorage::open(&db_path).await.unwrap();
+    let loaded = reopened.get(&note.id).await.unwrap().unwrap();
+    assert_eq!(loaded.content, "Stored in SQLite");
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_upgrades_databases_without_trash_column() {
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let db_path = data_dir.join("notes.db");
+    let mut note = serde_json::to_value(AppNote::new("Old".to_string(), "Before the trash".to_string())).unwrap();
+    note.as_object_mut().unwrap().remove("deleted_at");
+    
+    {
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (
+                id TEXT PRIMARY KEY, status TEXT NOT NULL, priority TEXT NOT NULL,
+                due_date TEXT, created_at TEXT NOT NULL, data TEXT NOT NULL
+            );
+            PRAGMA user_version = 4;",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO notes (id, status, priority, created_at, data) VALUES (?1, 'Idea', 'Medium', '', ?2)",
+            rusqlite::params![note["id"].as_str().unwrap(), note.to_string()],
+        )
+        .unwrap();
+    }
+    
+    let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+    let notes = storage.list().await.unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].deleted_at, None);
+    assert!(storage.list_trash().await.unwrap().is_empty());
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_migrates_relation_origins() {
+    let data_dir = temp_data_dir();
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let db_path = data_dir.join("notes.db");
+    let notes: Vec<AppNote> = ["Rust", "Slint", "Groceries"]
+        .into_iter()
+        .map(|title| AppNote::new(title.to_string(), String::new()))
+        .collect();
+    
+    {
+        let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+        for note in &notes {
+            storage.upsert(note).await.unwrap();
+        }
+        let mut graph = KnowledgeGraph::new();
+        graph.add_relation(notes[0].id.clone(), notes[1].id.clone(), RelationType::RelatedTo, 0.5);
+        graph.add_relation(notes[1].id.clone(), notes[2].id.clone(), RelationType::DependsOn, 1.0);
+        storage.save_graph(&graph).await.unwrap();
+    }
+    {
+        // Rewrite the relation rows the way a v9 database stored them
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        let rows: Vec<(String, String)> = conn
+            .prepare("SELECT id, data FROM relations")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        for (id, data) in rows {
+            let mut relation: serde_json::Value = serde_json::from_str(&data).unwrap();
+            relation.as_object_mut().unwrap().remove("origin");
+            conn.execute("UPDATE relations SET data = ?2 WHERE id = ?1", rusqlite::params![id, relation.to_string()])
+                .unwrap();
+        }
+        conn.execute_batch("PRAGMA user_version = 9;").unwrap();
+    }
+    
+    let storage = SqliteNoteStorage::open(&db_path).await.unwrap();
+    let graph = storage.load_graph().await.unwrap();
+    let origins: Vec<_> = graph.relations.iter().map(|relation| (relation.relation_type, relation.origin)).collect();
+    assert_eq!(
+        origins,
+        vec![(RelationType::RelatedTo, RelationOrigin::Discovered), (RelationType::DependsOn, RelationOrigin::Manual)]
+    );
+    
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_sqlite_store_handles_large_collections() {
+    let storage = SqliteNoteStorage::open_in_memory().unwrap();
+    for i in 0..2000 {
+        let mut note = AppNote::new(format!("Note {}", i), String::new());
+        if i % 10 == 0 {
+            note.add_tag("tenth".to_string());
+        }
+        storage.upsert(&note).await.unwrap();
+    }
+    
+    let tagged = storage.query(&NoteQuery { tag: Some("tenth".to_string()), ..Default::default() }).await.unwrap();
+    assert_eq!(tagged.len(), 200);
+}
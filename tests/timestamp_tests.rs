@@ -0,0 +1,44 @@
+use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+use rust_slint_sticky::timestamp::{self, format_in, parse_date, parse_timestamp};
+use rust_slint_sticky::AppNote;
+
+#[test]
+fn test_legacy_and_rfc3339_timestamps_parse_as_utc() {
+    let expected = Utc.with_ymd_and_hms(2025, 8, 1, 9, 30, 0).unwrap();
+    
+    assert_eq!(parse_timestamp("2025-08-01 09:30"), Some(expected));
+    assert_eq!(parse_timestamp("2025-08-01 09:30:00"), Some(expected));
+    assert_eq!(parse_timestamp("2025-08-01T09:30:00Z"), Some(expected));
+    assert_eq!(parse_timestamp("2025-08-01T11:30:00+02:00"), Some(expected));
+    assert_eq!(parse_timestamp("last tuesday"), None);
+    
+    assert_eq!(parse_date("2025-08-01"), NaiveDate::from_ymd_opt(2025, 8, 1));
+    assert_eq!(parse_date("2025-08-01 23:00"), NaiveDate::from_ymd_opt(2025, 8, 1));
+    assert_eq!(parse_date("soon"), None);
+}
+
+#[test]
+fn test_display_uses_the_given_timezone() {
+    let timestamp = Utc.with_ymd_and_hms(2025, 12, 31, 23, 30, 0).unwrap();
+    
+    assert_eq!(format_in(&timestamp, &Utc), "2025-12-31 23:30");
+    assert_eq!(format_in(&timestamp, &FixedOffset::east_opt(2 * 3600).unwrap()), "2026-01-01 01:30");
+    assert_eq!(format_in(&timestamp, &FixedOffset::west_opt(5 * 3600).unwrap()), "2025-12-31 18:30");
+}
+
+#[test]
+fn test_notes_round_trip_with_full_precision() {
+    let mut note = AppNote::new("Precise".to_string(), "".to_string());
+    note.due_date = parse_date("2026-10-20");
+    note.move_to_trash();
+    
+    let json = serde_json::to_value(&note).unwrap();
+    assert_eq!(json["due_date"], "2026-10-20");
+    assert_eq!(json["created_at"], timestamp::to_rfc3339(&note.created_at));
+    
+    let restored: AppNote = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.created_at, note.created_at);
+    assert_eq!(restored.updated_at, note.updated_at);
+    assert_eq!(restored.deleted_at, note.deleted_at);
+    assert_eq!(restored.due_date, note.due_date);
+}
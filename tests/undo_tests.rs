@@ -0,0 +1,105 @@
+use rust_slint_sticky::undo::{CommandKind, NoteCommand, UndoStack};
+use rust_slint_sticky::AppNote;
+
+fn moved(note: &AppNote, x: f32, y: f32) -> AppNote {
+    let mut moved = note.clone();
+    moved.set_position(x, y);
+    moved
+}
+
+#[test]
+fn test_undo_and_redo_walk_the_history() {
+    let mut stack = UndoStack::new();
+    let note = AppNote::new("Draft".to_string(), "".to_string());
+    let mut edited = note.clone();
+    edited.update_content("Final".to_string(), "Done".to_string());
+    
+    stack.record(NoteCommand::create(note.clone()));
+    stack.record(NoteCommand::update(CommandKind::Edit, note.clone(), edited.clone()));
+    assert_eq!(stack.next_undo(), Some(CommandKind::Edit));
+    
+    let undone = stack.undo().unwrap();
+    assert_eq!(undone.before.unwrap().title, "Draft");
+    assert_eq!(stack.next_undo(), Some(CommandKind::Create));
+    assert_eq!(stack.next_redo(), Some(CommandKind::Edit));
+    
+    let created = stack.undo().unwrap();
+    assert!(created.before.is_none());
+    assert!(!stack.can_undo());
+    
+    assert!(stack.redo().unwrap().after.is_some());
+    assert_eq!(stack.redo().unwrap().after.unwrap().title, "Final");
+    assert!(!stack.can_redo());
+}
+
+#[test]
+fn test_recording_clears_redo() {
+    let mut stack = UndoStack::new();
+    let note = AppNote::new("Note".to_string(), "".to_string());
+    
+    stack.record(NoteCommand::create(note.clone()));
+    stack.undo();
+    assert!(stack.can_redo());
+    
+    stack.record(NoteCommand::create(AppNote::new("Other".to_string(), "".to_string())));
+    assert!(!stack.can_redo());
+}
+
+#[test]
+fn test_each_move_is_its_own_step() {
+    let mut stack = UndoStack::new();
+    let first = AppNote::new("First".to_string(), "".to_string());
+    let second = AppNote::new("Second".to_string(), "".to_string());
+    
+    // Two quick drags of the same note are undone one at a time
+    let dragged = moved(&first, 1.0, 1.0);
+    stack.record(NoteCommand::update(CommandKind::Move, first.clone(), dragged.clone()));
+    stack.record(NoteCommand::update(CommandKind::Move, dragged.clone(), moved(&first, 2.0, 2.0)));
+    assert_eq!(stack.undo().unwrap().before.unwrap().x, 1.0);
+    assert_eq!(stack.undo().unwrap().before.unwrap().x, first.x);
+    assert!(!stack.can_undo());
+    assert_eq!(stack.next_redo(), Some(CommandKind::Move));
+    
+    let mut stack = UndoStack::new();
+    stack.record(NoteCommand::update(CommandKind::Move, first.clone(), moved(&first, 1.0, 1.0)));
+    stack.record(NoteCommand::update(CommandKind::Move, second.clone(), moved(&second, 1.0, 1.0)));
+    assert_eq!(stack.undo().unwrap().note_id, second.id);
+    
+    // After an undo, a new move is a new step
+    stack.record(NoteCommand::update(CommandKind::Move, first.clone(), moved(&first, 5.0, 5.0)));
+    assert_eq!(stack.undo().unwrap().after.unwrap().x, 5.0);
+    assert_eq!(stack.undo().unwrap().after.unwrap().x, 1.0);
+}
+
+#[test]
+fn test_limit_and_forget_note() {
+    let mut stack = UndoStack::new().with_limit(2);
+    let notes: Vec<AppNote> = (0..3).map(|i| AppNote::new(format!("Note {}", i), "".to_string())).collect();
+    for note in &notes {
+        stack.record(NoteCommand::create(note.clone()));
+    }
+    
+    stack.forget_note(&notes[2].id);
+    assert_eq!(stack.undo().unwrap().note_id, notes[1].id);
+    assert!(!stack.can_undo());
+}
+
+#[test]
+fn test_forgetting_a_note_keeps_the_commands_that_changed_it_along_the_way() {
+    let mut stack = UndoStack::new();
+    let target = AppNote::new("Roadmap".to_string(), "".to_string());
+    let source = AppNote::new("Standup".to_string(), "[[Roadmap]]".to_string());
+    let mut renamed = target.clone();
+    renamed.update_content("Plan".to_string(), "".to_string());
+    let mut relinked = source.clone();
+    relinked.update_content("Standup".to_string(), "[[Plan]]".to_string());
+    
+    let mut command = NoteCommand::update(CommandKind::Edit, target.clone(), renamed);
+    command.linked.push((source.clone(), relinked));
+    stack.record(command);
+    
+    stack.forget_note(&source.id);
+    let command = stack.undo().unwrap();
+    assert_eq!(command.note_id, target.id);
+    assert!(command.linked.is_empty());
+}